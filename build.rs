@@ -47,9 +47,11 @@ fn main() {
 
 fn compile_linux() {
     // First check the features enabled for the crate.
-    // Only one linux backend should be enabled at a time.
+    // Only one linux backend should be enabled at a time, except for
+    // linux-native or linux-native-sysfs, which can be added to any of the
+    // others.
 
-    let avail_backends: [(&'static str, Box<dyn Fn()>); 5] = [
+    let avail_backends: [(&'static str, Box<dyn Fn()>); 6] = [
         (
             "LINUX_STATIC_HIDRAW",
             Box::new(|| {
@@ -106,18 +108,32 @@ fn compile_linux() {
                 // The udev crate takes care of finding its library
             }),
         ),
+        (
+            "LINUX_NATIVE_SYSFS",
+            Box::new(|| {
+                // Reads sysfs, without a library
+            }),
+        ),
     ];
 
-    let mut backends = avail_backends
+    let backends = avail_backends
         .iter()
         .filter(|f| env::var(format!("CARGO_FEATURE_{}", f.0)).is_ok());
 
-    if backends.clone().count() != 1 {
-        panic!("Exactly one linux hidapi backend must be selected.");
+    // linux-native can be combined with one of the C library backends, to
+    // choose between them at runtime
+    let c_backends = backends
+        .clone()
+        .filter(|f| !f.0.starts_with("LINUX_NATIVE"))
+        .count();
+    if backends.clone().count() == 0 || c_backends > 1 {
+        panic!("Exactly one linux hidapi backend must be selected, optionally together with linux-native or linux-native-sysfs.");
     }
 
-    // Build it!
-    (backends.next().unwrap().1)();
+    // Build them!
+    for backend in backends {
+        (backend.1)();
+    }
 }
 
 //#[cfg(all(feature = "shared-libusb", not(feature = "shared-hidraw")))]
@@ -1,6 +1,25 @@
-use hidapi::HidApi;
+//! Print the report descriptors of all devices, or of the files given as
+//! arguments, one item per line.
+//!
+//! ```text
+//! cargo run --example dump_descriptors [DESCRIPTOR_FILE...]
+//! ```
+
+use hidapi::descriptor::disassemble;
+use hidapi::{HidApi, MAX_REPORT_DESCRIPTOR_SIZE};
 
 fn main() {
+    let files = std::env::args().skip(1).collect::<Vec<_>>();
+    if !files.is_empty() {
+        for file in files {
+            match std::fs::read(&file) {
+                Ok(descriptor) => print!("{}:\n{}", file, disassemble(&descriptor)),
+                Err(err) => eprintln!("Failed to read {} ({})", file, err),
+            }
+        }
+        return;
+    }
+
     println!("Printing all available hid devices:");
 
     match HidApi::new() {
@@ -14,12 +33,12 @@ fn main() {
                     },
                     device.interface_number()
                 );
-                let mut descriptor = vec![0u8; 2048];
+                let mut descriptor = vec![0u8; MAX_REPORT_DESCRIPTOR_SIZE];
                 match device
                     .open_device(&api)
                     .and_then(|dev| dev.get_report_descriptor(&mut descriptor))
                 {
-                    Ok(length) => println!("    {:?}", &mut descriptor[..length]),
+                    Ok(length) => print!("{}", disassemble(&descriptor[..length])),
                     Err(err) => println!("    Failed to retrieve descriptor ({:?})", err),
                 }
             }
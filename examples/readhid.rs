@@ -16,9 +16,17 @@ fn main() {
     let api = HidApi::new().expect("Failed to create API instance");
 
     let joystick = api.open(1103, 45320).expect("Failed to open device");
+    let caps = joystick
+        .capabilities()
+        .expect("Failed to get device capabilities");
 
+    if caps.input_report_byte_length() == 0 {
+        eprintln!("The device has no Input reports to read");
+        std::process::exit(1);
+    }
+
+    let mut buf = vec![0u8; caps.input_report_byte_length()];
     loop {
-        let mut buf = [0u8; 256];
         let res = joystick.read(&mut buf[..]).unwrap();
 
         let mut data_string = String::new();
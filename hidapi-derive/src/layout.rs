@@ -0,0 +1,105 @@
+//! Just enough of a report descriptor parser to find the main items of each
+//! report
+
+use std::collections::HashMap;
+
+use crate::ReportType;
+
+/// An Input, Output or Feature item
+pub(crate) struct MainItem {
+    /// Bit offset within the report data
+    pub offset: u32,
+    pub size: u32,
+    pub count: u32,
+    pub constant: bool,
+}
+
+pub(crate) struct Layout {
+    /// Whether the descriptor has Report ID items
+    pub numbered: bool,
+    pub reports: HashMap<(ReportType, u8), Vec<MainItem>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Globals {
+    report_size: u32,
+    report_count: u32,
+    report_id: u8,
+}
+
+impl Layout {
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut layout = Layout {
+            numbered: false,
+            reports: HashMap::new(),
+        };
+        let mut globals = Globals::default();
+        let mut stack = Vec::new();
+
+        let mut offset = 0;
+        while let Some(&prefix) = bytes.get(offset) {
+            if prefix == 0xFE {
+                let size = *bytes.get(offset + 1).ok_or_else(|| truncated(offset))?;
+                offset += 3 + size as usize;
+                continue;
+            }
+            let size = [0, 1, 2, 4][(prefix & 0x03) as usize];
+            let data = bytes
+                .get(offset + 1..offset + 1 + size)
+                .ok_or_else(|| truncated(offset))?;
+            let value = data
+                .iter()
+                .rev()
+                .fold(0u32, |value, b| value << 8 | *b as u32);
+
+            let report_type = match prefix & 0xFC {
+                0x80 => Some(ReportType::Input),
+                0x90 => Some(ReportType::Output),
+                0xB0 => Some(ReportType::Feature),
+                0x74 => {
+                    globals.report_size = value;
+                    None
+                }
+                0x94 => {
+                    globals.report_count = value;
+                    None
+                }
+                0x84 => {
+                    globals.report_id = value as u8;
+                    layout.numbered = true;
+                    None
+                }
+                0xA4 => {
+                    stack.push(globals);
+                    None
+                }
+                0xB4 => {
+                    globals = stack
+                        .pop()
+                        .ok_or_else(|| format!("Pop without Push at offset {offset}"))?;
+                    None
+                }
+                _ => None,
+            };
+            if let Some(report_type) = report_type {
+                let items = layout
+                    .reports
+                    .entry((report_type, globals.report_id))
+                    .or_default();
+                let start = items.last().map_or(0, |i| i.offset + i.size * i.count);
+                items.push(MainItem {
+                    offset: start,
+                    size: globals.report_size,
+                    count: globals.report_count,
+                    constant: value & 0x01 != 0,
+                });
+            }
+            offset += 1 + size;
+        }
+        Ok(layout)
+    }
+}
+
+fn truncated(offset: usize) -> String {
+    format!("the report descriptor ends in the item at offset {offset}")
+}
@@ -0,0 +1,407 @@
+//! Derive macro for the `HidReport` trait of [hidapi](https://docs.rs/hidapi)
+//!
+//! Use it through the `derive` feature of `hidapi`, which re-exports it as
+//! `hidapi::report::HidReport`.
+
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitInt, LitStr, Result, Type};
+
+mod layout;
+
+use layout::Layout;
+
+/// Implement `hidapi::report::HidReport` for a struct with named fields
+///
+/// The struct attribute `#[hid(...)]` takes
+///
+/// - `input`, `output` or `feature`: the report type, required
+/// - `id = N`: the report ID, 0 if the device does not use numbered reports
+/// - `length = N`: the length of the report data in bytes, without the
+///   report ID byte. The default is the end of the last field.
+/// - `descriptor = "path"`: a report descriptor file, relative to the
+///   directory of `Cargo.toml`. The layout of the struct is checked against
+///   the report of the same type and ID.
+///
+/// The field attribute `#[hid(offset = N, size = N)]` sets the bit offset
+/// of the field within the report data and its size in bits. A field starts
+/// at the end of the previous field by default, and `bool`, `u8` to `u64`
+/// and `i8` to `i64` fields have the size of their type. An integer field
+/// cannot be larger than its type, while a `bool` field of more than one bit
+/// is true if any bit is set. The elements of an array field follow each
+/// other, and `size` is the size of one element.
+///
+/// The struct also implements the marker trait of its report type,
+/// `hidapi::report::InputReport`, `OutputReport` or `FeatureReport`.
+///
+/// ```ignore
+/// #[derive(HidReport)]
+/// #[hid(input, id = 1)]
+/// struct Pointer {
+///     #[hid(size = 5)]
+///     buttons: u8,
+///     #[hid(offset = 8)]
+///     x: i16,
+///     y: i16,
+/// }
+/// ```
+#[proc_macro_derive(HidReport, attributes(hid))]
+pub fn derive_hid_report(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum ReportType {
+    Input,
+    Output,
+    Feature,
+}
+
+impl ReportType {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Input => "Input",
+            Self::Output => "Output",
+            Self::Feature => "Feature",
+        }
+    }
+}
+
+/// The options of the struct attribute
+struct ReportAttr {
+    report_type: Option<ReportType>,
+    report_id: u8,
+    length: Option<u32>,
+    descriptor: Option<LitStr>,
+}
+
+/// A field and where it is in the report data
+struct Field<'a> {
+    field: &'a syn::Field,
+    offset: u32,
+    /// Size of the field, or of one element of an array field
+    size: u32,
+    /// Number of elements of an array field
+    count: Option<u32>,
+}
+
+impl Field<'_> {
+    fn end(&self) -> u32 {
+        self.offset + self.size * self.count.unwrap_or(1)
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            input,
+            "HidReport can only be derived for structs",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(Error::new_spanned(
+            input,
+            "HidReport can only be derived for structs with named fields",
+        ));
+    };
+
+    let attr = parse_report_attr(input)?;
+    let Some(report_type) = attr.report_type else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "missing report type, add #[hid(input)], #[hid(output)] or #[hid(feature)]",
+        ));
+    };
+
+    let fields = layout_fields(named.named.iter())?;
+    let end = fields.iter().map(Field::end).max().unwrap_or(0);
+    let length = match attr.length {
+        Some(length) if length * 8 < end => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                format!("the fields need {end} bits, more than the report length"),
+            ))
+        }
+        Some(length) => length,
+        None => end.div_ceil(8),
+    };
+
+    let mut checks = TokenStream::new();
+    if let Some(path) = &attr.descriptor {
+        let full_path = manifest_dir()?.join(path.value());
+        let bytes = std::fs::read(&full_path).map_err(|e| {
+            Error::new_spanned(path, format!("cannot read {}: {e}", full_path.display()))
+        })?;
+        let layout = Layout::parse(&bytes).map_err(|e| Error::new_spanned(path, e))?;
+        check_layout(&layout, report_type, attr.report_id, length, &fields, input)?;
+
+        // Rebuild when the descriptor changes
+        let full_path = full_path.to_string_lossy();
+        checks = quote!(
+            const _: &[u8] = include_bytes!(#full_path);
+        );
+    }
+
+    let private = quote!(::hidapi::report::__private);
+    let decode = fields.iter().map(|f| {
+        let (ident, offset, size) = (&f.field.ident, f.offset as usize, f.size);
+        let value = |offset| {
+            quote!(#private::ReportValue::from_bits(#private::get_bits(data, #offset, #size), #size))
+        };
+        match f.count {
+            None => {
+                let value = value(quote!(#offset));
+                quote!(#ident: #value)
+            }
+            Some(_) => {
+                let value = value(quote!(#offset + i * #size as usize));
+                quote!(#ident: ::std::array::from_fn(|i| #value))
+            }
+        }
+    });
+    let encode = fields.iter().map(|f| {
+        let (ident, offset, size) = (&f.field.ident, f.offset as usize, f.size);
+        match f.count {
+            None => quote! {
+                #private::set_bits(&mut bytes[1..], #offset, #size, #private::ReportValue::to_bits(self.#ident));
+            },
+            Some(_) => quote! {
+                for (i, value) in self.#ident.iter().enumerate() {
+                    let offset = #offset + i * #size as usize;
+                    #private::set_bits(&mut bytes[1..], offset, #size, #private::ReportValue::to_bits(*value));
+                }
+            },
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let marker = syn::Ident::new(&format!("{}Report", report_type.name()), Span::call_site());
+    let report_type = syn::Ident::new(report_type.name(), Span::call_site());
+    let report_id = attr.report_id;
+    let length = length as usize + 1;
+    Ok(quote! {
+        #checks
+
+        impl #impl_generics ::hidapi::report::HidReport for #name #ty_generics #where_clause {
+            const REPORT_TYPE: ::hidapi::descriptor::ReportType =
+                ::hidapi::descriptor::ReportType::#report_type;
+            const REPORT_ID: u8 = #report_id;
+            const LENGTH: usize = #length;
+
+            fn from_bytes(bytes: &[u8]) -> ::hidapi::HidResult<Self> {
+                let data = #private::report_data(bytes, Self::REPORT_ID, Self::LENGTH)?;
+                Ok(Self { #(#decode),* })
+            }
+
+            fn to_bytes(&self) -> ::std::vec::Vec<u8> {
+                let mut bytes = ::std::vec![0u8; Self::LENGTH];
+                bytes[0] = Self::REPORT_ID;
+                #(#encode)*
+                bytes
+            }
+        }
+
+        impl #impl_generics ::hidapi::report::#marker for #name #ty_generics #where_clause {}
+    })
+}
+
+fn parse_report_attr(input: &DeriveInput) -> Result<ReportAttr> {
+    let mut attr = ReportAttr {
+        report_type: None,
+        report_id: 0,
+        length: None,
+        descriptor: None,
+    };
+    for a in input.attrs.iter().filter(|a| a.path().is_ident("hid")) {
+        a.parse_nested_meta(|meta| {
+            let report_type = if meta.path.is_ident("input") {
+                ReportType::Input
+            } else if meta.path.is_ident("output") {
+                ReportType::Output
+            } else if meta.path.is_ident("feature") {
+                ReportType::Feature
+            } else if meta.path.is_ident("id") {
+                attr.report_id = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                return Ok(());
+            } else if meta.path.is_ident("length") {
+                attr.length = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                return Ok(());
+            } else if meta.path.is_ident("descriptor") {
+                attr.descriptor = Some(meta.value()?.parse()?);
+                return Ok(());
+            } else {
+                return Err(meta.error("unknown report option"));
+            };
+            if attr.report_type.replace(report_type).is_some() {
+                return Err(meta.error("more than one report type"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attr)
+}
+
+fn layout_fields<'a>(fields: impl Iterator<Item = &'a syn::Field>) -> Result<Vec<Field<'a>>> {
+    let mut result = Vec::<Field>::new();
+    let mut next_offset = 0;
+    for field in fields {
+        let mut offset = None;
+        let mut size = None;
+        for a in field.attrs.iter().filter(|a| a.path().is_ident("hid")) {
+            a.parse_nested_meta(|meta| {
+                let value = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                if meta.path.is_ident("offset") {
+                    offset = value;
+                } else if meta.path.is_ident("size") {
+                    size = value;
+                } else {
+                    return Err(meta.error("unknown field option"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let (ty, count) = match &field.ty {
+            Type::Array(array) => (&*array.elem, Some(array_len(&array.len)?)),
+            ty => (ty, None),
+        };
+        let offset = offset.unwrap_or(next_offset);
+        let type_size = type_size(ty);
+        let Some(size) = size.or(type_size) else {
+            return Err(Error::new_spanned(field, "missing #[hid(size = N)]"));
+        };
+        if !(1..=64).contains(&size) {
+            return Err(Error::new_spanned(field, "the size must be 1 to 64 bits"));
+        }
+        if let Some(type_size) = type_size.filter(|_| !is_bool(ty)) {
+            if size > type_size {
+                return Err(Error::new_spanned(
+                    field,
+                    format!("the size must be at most the {type_size} bits of the type"),
+                ));
+            }
+        }
+        let new = Field {
+            field,
+            offset,
+            size,
+            count,
+        };
+        if let Some(other) = result
+            .iter()
+            .find(|f| offset < f.end() && f.offset < new.end())
+        {
+            let other = other.field.ident.as_ref().map(ToString::to_string);
+            return Err(Error::new_spanned(
+                field,
+                format!("overlaps the field `{}`", other.unwrap_or_default()),
+            ));
+        }
+        next_offset = new.end();
+        result.push(new);
+    }
+    Ok(result)
+}
+
+fn array_len(len: &syn::Expr) -> Result<u32> {
+    match len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(len),
+            ..
+        }) => len.base10_parse(),
+        len => Err(Error::new_spanned(
+            len,
+            "the array length must be a literal",
+        )),
+    }
+}
+
+/// The size in bits of the types that are always stored whole
+fn type_size(ty: &Type) -> Option<u32> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let ident = path.path.get_ident()?.to_string();
+    match ident.as_str() {
+        "bool" => Some(1),
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        "u64" | "i64" => Some(64),
+        _ => None,
+    }
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("bool"))
+}
+
+fn manifest_dir() -> Result<PathBuf> {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| Error::new(Span::call_site(), "CARGO_MANIFEST_DIR is not set"))
+}
+
+/// Check that the report in the descriptor has the length of the struct, and
+/// that every field covers whole data elements of a single main item
+fn check_layout(
+    layout: &Layout,
+    report_type: ReportType,
+    report_id: u8,
+    length: u32,
+    fields: &[Field],
+    input: &DeriveInput,
+) -> Result<()> {
+    let name = report_type.name();
+    if layout.numbered && report_id == 0 {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "the descriptor uses report IDs, add #[hid(id = N)]",
+        ));
+    }
+    let Some(items) = layout.reports.get(&(report_type, report_id)) else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!("the descriptor has no {name} report with ID {report_id}"),
+        ));
+    };
+    let bits = items.last().map_or(0, |i| i.offset + i.size * i.count);
+    if bits.div_ceil(8) != length {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!(
+                "the {name} report has {} bytes of data, not {length}",
+                bits.div_ceil(8)
+            ),
+        ));
+    }
+
+    for field in fields {
+        let covers_elements = items.iter().any(|i| {
+            !i.constant
+                && i.size > 0
+                && field.offset >= i.offset
+                && field.end() <= i.offset + i.size * i.count
+                && (field.offset - i.offset) % i.size == 0
+                && field.size % i.size == 0
+        });
+        if !covers_elements {
+            return Err(Error::new(
+                field.field.span(),
+                format!(
+                    "bits {}..{} are not data elements of the {name} report",
+                    field.offset,
+                    field.end()
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
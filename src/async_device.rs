@@ -0,0 +1,237 @@
+//! Asynchronous access to hidraw devices with tokio
+
+use std::io;
+use std::ops::Deref;
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::io::unix::AsyncFd;
+use tokio::task::spawn_blocking;
+
+use crate::{HidDevice, HidError, HidResult};
+
+/// A [`HidDevice`] that is serviced by the tokio reactor instead of a
+/// blocking thread
+///
+/// Input and Output reports wait for the file descriptor of the device to
+/// become ready, so the backend must provide one, see [`HidDevice::as_fd`].
+/// Feature reports are synchronous control transfers in the kernel, so they
+/// run on the blocking thread pool of the runtime.
+///
+/// The methods take `&mut self`, which keeps their futures `Send` even
+/// though [`HidDevice`] is not `Sync`.
+#[derive(Debug)]
+pub struct AsyncHidDevice {
+    inner: AsyncFd<Pollable>,
+}
+
+/// The device together with its file descriptor, shared with the blocking
+/// tasks of the Feature reports
+#[derive(Debug)]
+struct Pollable {
+    device: Arc<Mutex<HidDevice>>,
+    fd: RawFd,
+}
+
+impl AsRawFd for Pollable {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+/// A panic in a blocking task leaves the device itself intact
+fn lock(device: &Mutex<HidDevice>) -> MutexGuard<'_, HidDevice> {
+    device.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl AsyncHidDevice {
+    /// Register the device with the reactor of the current tokio runtime
+    ///
+    /// Fails if the backend of the device has no file descriptor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime with IO enabled.
+    pub fn new(device: HidDevice) -> HidResult<Self> {
+        let fd = match device.as_fd() {
+            Some(fd) => fd.as_raw_fd(),
+            None => {
+                return Err(HidError::IoError {
+                    error: io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "the backend of the device has no file descriptor",
+                    ),
+                })
+            }
+        };
+        Ok(Self {
+            inner: AsyncFd::new(Pollable {
+                device: Arc::new(Mutex::new(device)),
+                fd,
+            })?,
+        })
+    }
+
+    /// Access the device, waits for a running Feature report
+    pub fn get_ref(&self) -> impl Deref<Target = HidDevice> + '_ {
+        lock(&self.inner.get_ref().device)
+    }
+
+    /// Deregister the device from the reactor
+    ///
+    /// Waits for Feature reports that are still running on the blocking
+    /// thread pool, because their futures were dropped.
+    pub fn into_inner(self) -> HidDevice {
+        let mut device = self.inner.into_inner().device;
+        loop {
+            match Arc::try_unwrap(device) {
+                Ok(device) => return device.into_inner().unwrap_or_else(|e| e.into_inner()),
+                Err(shared) => {
+                    // The blocking task holds the lock until it is done
+                    drop(lock(&shared));
+                    device = shared;
+                }
+            }
+        }
+    }
+
+    /// Wait for the next Input report, see [`HidDevice::read`]
+    pub async fn read(&mut self, buf: &mut [u8]) -> HidResult<usize> {
+        loop {
+            let mut guard = self.inner.readable_mut().await?;
+            let len = lock(&guard.get_inner().device).read_timeout(buf, 0)?;
+            match len {
+                0 => guard.clear_ready(),
+                n => return Ok(n),
+            }
+        }
+    }
+
+    /// Write an Output report, see [`HidDevice::write`]
+    pub async fn write(&mut self, data: &[u8]) -> HidResult<usize> {
+        loop {
+            let mut guard = self.inner.writable_mut().await?;
+            let result = lock(&guard.get_inner().device).write(data);
+            match result {
+                Err(HidError::IoError { error }) if error.kind() == io::ErrorKind::WouldBlock => {
+                    guard.clear_ready()
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Send a Feature report, see [`HidDevice::send_feature_report`]
+    pub async fn send_feature_report(&mut self, data: &[u8]) -> HidResult<()> {
+        let device = self.inner.get_ref().device.clone();
+        let data = data.to_vec();
+        spawn_blocking(move || lock(&device).send_feature_report(&data))
+            .await
+            .map_err(io::Error::from)?
+    }
+
+    /// Get a Feature report, see [`HidDevice::get_feature_report`]
+    pub async fn get_feature_report(&mut self, buf: &mut [u8]) -> HidResult<usize> {
+        let device = self.inner.get_ref().device.clone();
+        let mut data = buf.to_vec();
+        let (data, result) = spawn_blocking(move || {
+            let result = lock(&device).get_feature_report(&mut data);
+            (data, result)
+        })
+        .await
+        .map_err(io::Error::from)?;
+        buf.copy_from_slice(&data);
+        result
+    }
+
+    /// Get an Input report over the Control endpoint, see
+    /// [`HidDevice::get_input_report`]
+    pub async fn get_input_report(&mut self, buf: &mut [u8]) -> HidResult<usize> {
+        let device = self.inner.get_ref().device.clone();
+        let mut data = buf.to_vec();
+        let (data, result) = spawn_blocking(move || {
+            let result = lock(&device).get_input_report(&mut data);
+            (data, result)
+        })
+        .await
+        .map_err(io::Error::from)?;
+        buf.copy_from_slice(&data);
+        result
+    }
+
+    /// Send an Output report over the Control endpoint, see
+    /// [`HidDevice::send_output_report`]
+    pub async fn send_output_report(&mut self, data: &[u8]) -> HidResult<()> {
+        let device = self.inner.get_ref().device.clone();
+        let data = data.to_vec();
+        spawn_blocking(move || lock(&device).send_output_report(&data))
+            .await
+            .map_err(io::Error::from)?
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::VirtualDevice;
+
+    #[test]
+    fn test_read_virtual_device() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        let virtual_device = VirtualDevice::builder(0x046D, 0xC534)
+            .on_write(|device, data| {
+                device.push_input_report(data);
+                Ok(data.len())
+            })
+            .build();
+
+        runtime.block_on(async {
+            let mut device = AsyncHidDevice::new(virtual_device.open().unwrap()).unwrap();
+            let mut buf = [0u8; 4];
+            device.write(&[0x01, 0x02]).await.unwrap();
+            assert_eq!(device.read(&mut buf).await.unwrap(), 2);
+
+            let reader = tokio::spawn(async move {
+                let mut buf = [0u8; 4];
+                device.read(&mut buf).await
+            });
+            // Let the reader wait for the device first
+            tokio::task::yield_now().await;
+            virtual_device.disconnect();
+            assert!(reader.await.unwrap().is_err());
+        });
+    }
+
+    #[test]
+    fn test_control_reports_virtual_device() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        let virtual_device = VirtualDevice::builder(0x046D, 0xC534)
+            .feature_report(&[0x02, 0x11])
+            .input_report(&[0x03, 0x22])
+            .build();
+
+        let device = runtime.block_on(async {
+            let mut device = AsyncHidDevice::new(virtual_device.open().unwrap()).unwrap();
+            let mut buf = [0x02, 0x00];
+            assert_eq!(device.get_feature_report(&mut buf).await.unwrap(), 2);
+            assert_eq!(buf, [0x02, 0x11]);
+            device.send_feature_report(&[0x02, 0x33]).await.unwrap();
+
+            let mut buf = [0x03, 0x00];
+            assert_eq!(device.get_input_report(&mut buf).await.unwrap(), 2);
+            assert_eq!(buf, [0x03, 0x22]);
+            device.send_output_report(&[0x04, 0x44]).await.unwrap();
+            device.into_inner()
+        });
+
+        assert_eq!(virtual_device.sent_feature_reports(), [vec![0x02, 0x33]]);
+        assert_eq!(virtual_device.sent_output_reports(), [vec![0x04, 0x44]]);
+        assert!(device.as_fd().is_some());
+    }
+}
@@ -0,0 +1,287 @@
+//! Backends that enumerate and open devices
+//!
+//! [`HidApi::new`](crate::HidApi::new) uses the backend selected by the
+//! Cargo features. On Linux, `linux-native` can be enabled together with one
+//! of the `hidapi` C library backends, and
+//! [`HidApi::with_backend`](crate::HidApi::with_backend) chooses between them
+//! at runtime, e.g. to fall back to the C library on systems without udev.
+//!
+//! Other crates can provide devices from somewhere else, like a remote
+//! machine or a recording, by implementing [`Backend`] for the enumeration
+//! and [`DeviceBackend`] for the opened devices.
+
+use std::ffi::CStr;
+#[cfg(unix)]
+use std::os::fd::BorrowedFd;
+
+use crate::{DeviceInfo, HidDevice, HidError, HidResult};
+
+/// A source of HID devices, see [`HidApi::with_backend`]
+///
+/// [`HidApi::with_backend`]: crate::HidApi::with_backend
+pub trait Backend: Send + Sync {
+    /// Get the devices that match the given VID and PID filters.
+    /// 0 indicates no filter.
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>>;
+
+    /// Open a device by the path of one of its [`DeviceInfo`]s
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice>;
+
+    /// Open the first device with the given VID and PID
+    fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
+        match self.enumerate(vid, pid)?.first() {
+            Some(info) => self.open_path(info.path()),
+            None => Err(device_not_found()),
+        }
+    }
+
+    /// Open the first device with the given VID, PID and serial number
+    fn open_serial(&self, vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
+        match self
+            .enumerate(vid, pid)?
+            .iter()
+            .find(|info| info.serial_number() == Some(sn))
+        {
+            Some(info) => self.open_path(info.path()),
+            None => Err(device_not_found()),
+        }
+    }
+
+    /// Watch for devices being connected and disconnected, see
+    /// [`HidApi::monitor`](crate::HidApi::monitor)
+    #[cfg(all(feature = "linux-native", target_os = "linux"))]
+    fn monitor(&self) -> HidResult<crate::DeviceMonitor> {
+        Err(not_supported("monitor"))
+    }
+}
+
+/// An opened device, wrapped by [`HidDevice::from_backend`]
+///
+/// The methods correspond to the ones of [`HidDevice`]. The platform specific
+/// methods have default implementations that report them as not supported.
+pub trait DeviceBackend: Send {
+    fn write(&self, data: &[u8]) -> HidResult<usize>;
+    fn read(&self, buf: &mut [u8]) -> HidResult<usize>;
+    fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize>;
+    fn send_feature_report(&self, data: &[u8]) -> HidResult<()>;
+    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize>;
+    fn set_blocking_mode(&self, blocking: bool) -> HidResult<()>;
+    fn get_device_info(&self) -> HidResult<DeviceInfo>;
+    fn get_manufacturer_string(&self) -> HidResult<Option<String>>;
+    fn get_product_string(&self) -> HidResult<Option<String>>;
+    fn get_serial_number_string(&self) -> HidResult<Option<String>>;
+    fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize>;
+
+    fn get_indexed_string(&self, _index: i32) -> HidResult<Option<String>> {
+        Err(not_supported("get_indexed_string"))
+    }
+
+    fn get_input_report(&self, _buf: &mut [u8]) -> HidResult<usize> {
+        Err(not_supported("get_input_report"))
+    }
+
+    fn send_output_report(&self, _data: &[u8]) -> HidResult<()> {
+        Err(not_supported("send_output_report"))
+    }
+
+    /// A file descriptor that becomes readable when an Input report is
+    /// available, to integrate the device into event loops
+    #[cfg(unix)]
+    fn as_fd(&self) -> Option<BorrowedFd<'_>> {
+        None
+    }
+
+    /// Get the container ID for a HID device
+    #[cfg(target_os = "windows")]
+    fn get_container_id(&self) -> HidResult<crate::windows::GUID> {
+        Err(not_supported("get_container_id"))
+    }
+
+    /// Get the location ID for a HID device
+    #[cfg(target_os = "macos")]
+    fn get_location_id(&self) -> HidResult<u32> {
+        Err(not_supported("get_location_id"))
+    }
+
+    /// Check if the device was opened in exclusive mode
+    #[cfg(target_os = "macos")]
+    fn is_open_exclusive(&self) -> HidResult<bool> {
+        Ok(false)
+    }
+
+    /// The last error of the device in the `hidapi` C library
+    #[cfg(hidapi)]
+    #[doc(hidden)]
+    fn check_error(&self) -> HidResult<HidError> {
+        Err(HidError::HidApiErrorEmpty)
+    }
+}
+
+fn device_not_found() -> HidError {
+    HidError::HidApiError {
+        message: "device not found".into(),
+    }
+}
+
+fn not_supported(function: &str) -> HidError {
+    HidError::HidApiError {
+        message: format!("{function}: not supported"),
+    }
+}
+
+/// The `linux-native` backend, which finds devices with udev and talks to
+/// hidraw directly
+#[cfg(all(feature = "linux-native", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LinuxNative;
+
+#[cfg(all(feature = "linux-native", target_os = "linux"))]
+impl Backend for LinuxNative {
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        crate::linux_native::HidApiBackend::get_hid_device_info_vector(vid, pid)
+    }
+
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        let dev = crate::linux_native::HidApiBackend::open_path(device_path)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
+        let dev = crate::linux_native::HidApiBackend::open(vid, pid)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn open_serial(&self, vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
+        let dev = crate::linux_native::HidApiBackend::open_serial(vid, pid, sn)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn monitor(&self) -> HidResult<crate::DeviceMonitor> {
+        crate::linux_native::HidApiBackend::monitor()
+    }
+}
+
+/// The `linux-native` backend, but finding the devices by reading sysfs
+/// instead of asking udev
+///
+/// This works where libudev is missing or has no data, like in containers
+/// and the initramfs. The [`DeviceInfo`]s are the same as with
+/// `LinuxNative`. The `linux-native-sysfs` feature builds only this backend,
+/// without linking libudev.
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+#[derive(Clone, Debug)]
+pub struct LinuxSysfs {
+    root: std::path::PathBuf,
+}
+
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+impl LinuxSysfs {
+    /// Read sysfs at `/sys`
+    pub fn new() -> Self {
+        Self::with_root(crate::linux_native::sysfs::SYSFS_ROOT)
+    }
+
+    /// Read sysfs mounted somewhere else, or a copy of it for tests
+    ///
+    /// The device nodes are still expected in `/dev`.
+    pub fn with_root(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+impl Default for LinuxSysfs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+impl Backend for LinuxSysfs {
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        Ok(crate::linux_native::sysfs::enumerate(&self.root)
+            .into_iter()
+            .filter(|info| vid == 0 || info.vendor_id == vid)
+            .filter(|info| pid == 0 || info.product_id == pid)
+            .collect())
+    }
+
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        let dev = crate::linux_native::HidApiBackend::open_path(device_path)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+}
+
+/// The `windows-native` backend, which talks to hid.dll directly
+#[cfg(all(feature = "windows-native", target_os = "windows"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WindowsNative;
+
+#[cfg(all(feature = "windows-native", target_os = "windows"))]
+impl Backend for WindowsNative {
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        crate::windows_native::HidApiBackend::get_hid_device_info_vector(vid, pid)
+    }
+
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        let dev = crate::windows_native::HidApiBackend::open_path(device_path)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
+        let dev = crate::windows_native::HidApiBackend::open(vid, pid)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn open_serial(&self, vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
+        let dev = crate::windows_native::HidApiBackend::open_serial(vid, pid, sn)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+}
+
+/// The backend that uses the `hidapi` C library
+#[cfg(hidapi)]
+#[derive(Clone, Copy, Debug)]
+pub struct Hidapi {
+    _private: (),
+}
+
+#[cfg(hidapi)]
+impl Hidapi {
+    /// Initialize the C library like [`HidApi::new`](crate::HidApi::new)
+    ///
+    /// # Panics
+    ///
+    /// Panics if hidapi is already initialized in "without enumerate" mode.
+    pub fn new() -> HidResult<Self> {
+        crate::lazy_init(true)?;
+        Ok(Self::initialized())
+    }
+
+    /// For contexts that initialized the C library already
+    pub(crate) fn initialized() -> Self {
+        Self { _private: () }
+    }
+}
+
+#[cfg(hidapi)]
+impl Backend for Hidapi {
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        crate::hidapi_backend::HidApiBackend::get_hid_device_info_vector(vid, pid)
+    }
+
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        let dev = crate::hidapi_backend::HidApiBackend::open_path(device_path)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
+        let dev = crate::hidapi_backend::HidApiBackend::open(vid, pid)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+
+    fn open_serial(&self, vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
+        let dev = crate::hidapi_backend::HidApiBackend::open_serial(vid, pid, sn)?;
+        Ok(HidDevice::from_backend(Box::new(dev)))
+    }
+}
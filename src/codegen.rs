@@ -0,0 +1,603 @@
+//! Generate report types from a report descriptor
+//!
+//! [`generate`] turns a report descriptor into Rust source code with a
+//! struct for every Input, Output and Feature report. The structs derive
+//! `hidapi::report::HidReport`, so the generated code needs the `derive`
+//! feature. Fields are named after their usages, and array fields,
+//! which report the selected usages like the keys of a keyboard, come with an
+//! enum of those usages. An enum per report type decodes a report by its ID.
+//!
+//! The generator is meant for build scripts. If the descriptor of the
+//! firmware changes, the generated types change with it, and code that relies
+//! on the old layout fails to compile instead of misreading reports.
+//!
+//! ```no_run
+//! // build.rs
+//! use std::{env, fs, path::Path};
+//!
+//! let descriptor = fs::read("device.desc").unwrap();
+//! let code = hidapi::codegen::generate(&descriptor).unwrap();
+//! let out_dir = env::var("OUT_DIR").unwrap();
+//! fs::write(Path::new(&out_dir).join("reports.rs"), code).unwrap();
+//! println!("cargo:rerun-if-changed=device.desc");
+//! ```
+//!
+//! ```ignore
+//! // src/main.rs
+//! #[allow(dead_code)]
+//! mod reports {
+//!     include!(concat!(env!("OUT_DIR"), "/reports.rs"));
+//! }
+//! ```
+//!
+//! `tests/assets/mouse2.rs` is the code generated from
+//! `tests/assets/mouse2.data`.
+
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+
+use crate::descriptor::{DescriptorError, Field, Report, ReportDescriptor, ReportType, Usage};
+use crate::usage;
+
+/// Array fields with more usages than this do not get an enum
+const MAX_ENUM_USAGES: usize = 1024;
+
+/// Generate the report types of a report descriptor as Rust source code
+///
+/// The code consists of
+///
+/// - `REPORT_DESCRIPTOR`, the bytes of the descriptor
+/// - a struct per report, named after its type and ID like `InputReport1`,
+///   or `InputReport` if the descriptor does not use report IDs
+/// - an enum per array field with named usages, like `InputReport1Keyboard`
+/// - an enum per report type with a variant for every report of the type,
+///   like `Input::Report1`, and a `from_bytes` function
+///
+/// Fields of more than 64 bits are left out.
+pub fn generate(descriptor: &[u8]) -> Result<String, DescriptorError> {
+    let parsed = ReportDescriptor::parse(descriptor)?;
+    let mut out = String::new();
+    write_code(&mut out, descriptor, &parsed).expect("writing to a String");
+    Ok(out)
+}
+
+fn write_code(out: &mut String, descriptor: &[u8], parsed: &ReportDescriptor) -> fmt::Result {
+    out.push_str("// Generated by hidapi::codegen from a report descriptor, do not edit\n\n");
+    out.push_str("/// The report descriptor the types were generated from\n");
+    out.push_str("pub const REPORT_DESCRIPTOR: &[u8] = &[\n");
+    for chunk in descriptor.chunks(16) {
+        let bytes = chunk
+            .iter()
+            .map(|b| format!("0x{b:02X},"))
+            .collect::<Vec<_>>();
+        writeln!(out, "    {}", bytes.join(" "))?;
+    }
+    out.push_str("];\n");
+
+    for report in parsed.reports() {
+        write_report(out, report)?;
+    }
+    for report_type in ReportType::values() {
+        let reports = parsed
+            .reports()
+            .iter()
+            .filter(|r| r.report_type() == report_type)
+            .collect::<Vec<_>>();
+        if !reports.is_empty() {
+            write_report_enum(out, report_type, &reports)?;
+        }
+    }
+    Ok(())
+}
+
+/// A field of a generated struct
+struct StructField {
+    name: String,
+    doc: String,
+    ty: String,
+    offset: usize,
+    size: u32,
+    count: Option<u32>,
+}
+
+fn write_report(out: &mut String, report: &Report) -> fmt::Result {
+    let struct_name = struct_name(report.report_type(), report.report_id());
+    let mut names = HashSet::new();
+    let mut fields = Vec::new();
+    let mut enums = String::new();
+
+    for field in report.fields() {
+        if field.flags().is_constant() {
+            continue;
+        }
+        if field.report_size() > 64 {
+            continue;
+        }
+        if field.flags().is_array() {
+            let base = array_field_name(field);
+            let name = unique(&mut names, base);
+            let ty = int_type(field.report_size(), field.logical_minimum() < 0);
+            let doc = match write_usage_enum(&mut enums, &struct_name, &name, field)? {
+                Some(enum_name) => format!("Selected usages, see [`{enum_name}`]"),
+                None => "Selected usages".to_owned(),
+            };
+            fields.push(StructField {
+                name,
+                doc,
+                ty: ty.to_owned(),
+                offset: field.bit_offset(),
+                size: field.report_size(),
+                count: (field.report_count() > 1).then_some(field.report_count()),
+            });
+            continue;
+        }
+
+        let count = field.report_count() as usize;
+        let usages = (0..count)
+            .map(|i| field.control_usage(i))
+            .collect::<Vec<_>>();
+        let distinct = usages.iter().collect::<HashSet<_>>().len() == count;
+        let ty = if field.report_size() == 1 {
+            "bool"
+        } else {
+            int_type(field.report_size(), field.logical_minimum() < 0)
+        };
+        let range = format!(
+            ", logical {}..={}",
+            field.logical_minimum(),
+            field.logical_maximum()
+        );
+        let range = if ty == "bool" { "" } else { &range };
+
+        if distinct || count == 1 {
+            for (i, usage) in usages.iter().enumerate() {
+                fields.push(StructField {
+                    name: unique(&mut names, usage_field_name(*usage)),
+                    doc: format!("{}{range}", usage_doc(*usage)),
+                    ty: ty.to_owned(),
+                    offset: field.bit_offset() + i * field.report_size() as usize,
+                    size: field.report_size(),
+                    count: None,
+                });
+            }
+        } else {
+            fields.push(StructField {
+                name: unique(&mut names, usage_field_name(usages[0])),
+                doc: format!("{}{range}", usage_doc(usages[0])),
+                ty: ty.to_owned(),
+                offset: field.bit_offset(),
+                size: field.report_size(),
+                count: Some(field.report_count()),
+            });
+        }
+    }
+
+    let kind = type_name(report.report_type()).to_ascii_lowercase();
+    let id = report.report_id();
+    writeln!(out)?;
+    match id {
+        0 => writeln!(out, "/// {} report", type_name(report.report_type()))?,
+        _ => writeln!(out, "/// {} report {id}", type_name(report.report_type()))?,
+    }
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, ::hidapi::report::HidReport)]\n");
+    writeln!(
+        out,
+        "#[hid({kind}, id = {id}, length = {})]",
+        report.byte_length()
+    )?;
+    writeln!(out, "pub struct {struct_name} {{")?;
+    let mut next_offset = 0;
+    for field in &fields {
+        let mut options = Vec::new();
+        if field.offset != next_offset {
+            options.push(format!("offset = {}", field.offset));
+        }
+        if Some(field.size) != type_size(&field.ty) {
+            options.push(format!("size = {}", field.size));
+        }
+        next_offset = field.offset + (field.size * field.count.unwrap_or(1)) as usize;
+
+        writeln!(out, "    /// {}", field.doc)?;
+        if !options.is_empty() {
+            writeln!(out, "    #[hid({})]", options.join(", "))?;
+        }
+        match field.count {
+            Some(count) => writeln!(out, "    pub {}: [{}; {count}],", field.name, field.ty)?,
+            None => writeln!(out, "    pub {}: {},", field.name, field.ty)?,
+        }
+    }
+    out.push_str("}\n");
+    out.push_str(&enums);
+    Ok(())
+}
+
+/// Write the enum of the named usages of an array field, and return its
+/// name
+///
+/// The enum is left out if no usage has a name.
+fn write_usage_enum(
+    out: &mut String,
+    struct_name: &str,
+    field_name: &str,
+    field: &Field,
+) -> Result<Option<String>, fmt::Error> {
+    let count = field.usage_count();
+    if count > MAX_ENUM_USAGES {
+        return Ok(None);
+    }
+    // The value of the field is the index of the usage plus the logical minimum
+    let named = (0..count)
+        .filter_map(|i| {
+            let usage = field.usage_at(i)?;
+            let value = field.logical_minimum() as i64 + i as i64;
+            Some((value, usage, usage::name(usage)?))
+        })
+        .collect::<Vec<_>>();
+    if named.is_empty() {
+        return Ok(None);
+    }
+
+    let enum_name = format!("{struct_name}{}", camel_case(field_name));
+    let mut variants = HashSet::new();
+    let variants = named
+        .iter()
+        .map(|(value, usage, name)| (value, usage, name, unique(&mut variants, camel_case(name))))
+        .collect::<Vec<_>>();
+    let value_type = if field.logical_minimum() < 0 {
+        "i64"
+    } else {
+        "u64"
+    };
+
+    writeln!(out)?;
+    writeln!(out, "/// The usages of `{struct_name}::{field_name}`")?;
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n");
+    writeln!(out, "pub enum {enum_name} {{")?;
+    for (_, _, name, variant) in &variants {
+        writeln!(out, "    /// {name}")?;
+        writeln!(out, "    {variant},")?;
+    }
+    out.push_str("}\n\n");
+
+    writeln!(out, "impl {enum_name} {{")?;
+    out.push_str("    /// The usage selected by a value of the field\n");
+    writeln!(
+        out,
+        "    pub fn from_value(value: {value_type}) -> Option<Self> {{"
+    )?;
+    out.push_str("        match value {\n");
+    for (value, _, _, variant) in &variants {
+        writeln!(out, "            {value} => Some(Self::{variant}),")?;
+    }
+    out.push_str("            _ => None,\n        }\n    }\n\n");
+
+    out.push_str("    /// The value of the field that selects this usage\n");
+    writeln!(out, "    pub fn value(self) -> {value_type} {{")?;
+    out.push_str("        match self {\n");
+    for (value, _, _, variant) in &variants {
+        writeln!(out, "            Self::{variant} => {value},")?;
+    }
+    out.push_str("        }\n    }\n\n");
+
+    out.push_str("    pub fn usage(self) -> ::hidapi::descriptor::Usage {\n");
+    out.push_str("        use ::hidapi::descriptor::Usage;\n\n");
+    out.push_str("        match self {\n");
+    for (_, usage, _, variant) in &variants {
+        writeln!(
+            out,
+            "            Self::{variant} => Usage::new(0x{:04X}, 0x{:04X}),",
+            usage.page, usage.id
+        )?;
+    }
+    out.push_str("        }\n    }\n}\n");
+    Ok(Some(enum_name))
+}
+
+fn write_report_enum(
+    out: &mut String,
+    report_type: ReportType,
+    reports: &[&Report],
+) -> fmt::Result {
+    let name = type_name(report_type);
+    let variant = |report: &Report| match report.report_id() {
+        0 => "Report".to_owned(),
+        id => format!("Report{id}"),
+    };
+
+    writeln!(out)?;
+    writeln!(out, "/// The {name} reports")?;
+    out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n");
+    writeln!(out, "pub enum {name} {{")?;
+    for report in reports {
+        let struct_name = struct_name(report_type, report.report_id());
+        writeln!(out, "    {}({struct_name}),", variant(report))?;
+    }
+    out.push_str("}\n\n");
+
+    writeln!(out, "impl {name} {{")?;
+    writeln!(out, "    /// Decode an {name} report by its report ID")?;
+    out.push_str("    pub fn from_bytes(bytes: &[u8]) -> ::hidapi::HidResult<Self> {\n");
+    out.push_str("        use ::hidapi::report::HidReport;\n\n");
+    out.push_str("        match bytes.first() {\n");
+    for report in reports {
+        let struct_name = struct_name(report_type, report.report_id());
+        writeln!(
+            out,
+            "            Some({}) => {struct_name}::from_bytes(bytes).map(Self::{}),",
+            report.report_id(),
+            variant(report)
+        )?;
+    }
+    out.push_str("            _ => Err(::hidapi::HidError::IoError {\n");
+    out.push_str("                error: ::std::io::Error::new(\n");
+    out.push_str("                    ::std::io::ErrorKind::InvalidData,\n");
+    writeln!(out, "                    \"unknown {name} report ID\",")?;
+    out.push_str("                ),\n            }),\n        }\n    }\n}\n");
+    Ok(())
+}
+
+fn type_name(report_type: ReportType) -> &'static str {
+    match report_type {
+        ReportType::Input => "Input",
+        ReportType::Output => "Output",
+        ReportType::Feature => "Feature",
+    }
+}
+
+fn struct_name(report_type: ReportType, report_id: u8) -> String {
+    match report_id {
+        0 => format!("{}Report", type_name(report_type)),
+        id => format!("{}Report{id}", type_name(report_type)),
+    }
+}
+
+/// The smallest integer type of `size` bits
+fn int_type(size: u32, signed: bool) -> &'static str {
+    match (size, signed) {
+        (..=8, false) => "u8",
+        (..=16, false) => "u16",
+        (..=32, false) => "u32",
+        (_, false) => "u64",
+        (..=8, true) => "i8",
+        (..=16, true) => "i16",
+        (..=32, true) => "i32",
+        (_, true) => "i64",
+    }
+}
+
+/// The size the derive macro assumes for a field type
+fn type_size(ty: &str) -> Option<u32> {
+    match ty {
+        "bool" => Some(1),
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        "u64" | "i64" => Some(64),
+        _ => None,
+    }
+}
+
+fn usage_doc(usage: Option<Usage>) -> String {
+    let Some(usage) = usage else {
+        return "No usage".to_owned();
+    };
+    match (usage::page_name(usage.page), usage::name(usage)) {
+        (Some(page), Some(name)) => format!("{page}: {name}"),
+        _ => format!("Usage 0x{:04X}:0x{:04X}", usage.page, usage.id),
+    }
+}
+
+fn usage_field_name(usage: Option<Usage>) -> String {
+    let Some(usage) = usage else {
+        return "value".to_owned();
+    };
+    match usage::name(usage) {
+        Some(name) => snake_case(&name),
+        None => format!("usage_{:04x}_{:04x}", usage.page, usage.id),
+    }
+}
+
+/// Array fields are named after the page of their usages
+fn array_field_name(field: &Field) -> String {
+    let pages = field
+        .usages()
+        .iter()
+        .map(|u| u.page)
+        .collect::<HashSet<_>>();
+    match field.usages().first().map(|u| u.page) {
+        Some(page) if pages.len() == 1 => match usage::page_name(page) {
+            Some(name) => snake_case(name),
+            None => format!("usages_{page:04x}"),
+        },
+        _ => "usages".to_owned(),
+    }
+}
+
+/// Split a name into words at non-alphanumeric characters and at the start
+/// of capitalized words, like `LampArray`
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else if c.is_ascii_uppercase() && previous_lower {
+            words.push(std::mem::take(&mut word));
+            word.push(c);
+        } else {
+            word.push(c);
+        }
+        previous_lower = c.is_ascii_lowercase();
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn snake_case(name: &str) -> String {
+    let name = words(name).join("_").to_ascii_lowercase();
+    if name.is_empty() {
+        return "value".to_owned();
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("_{name}");
+    }
+    if is_keyword(&name) {
+        return format!("{name}_");
+    }
+    name
+}
+
+fn camel_case(name: &str) -> String {
+    let name = words(name)
+        .iter()
+        .map(|w| {
+            let lower = w.to_ascii_lowercase();
+            let mut chars = lower.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect::<String>();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return format!("Usage{name}");
+    }
+    if name == "Self" {
+        return "Self_".to_owned();
+    }
+    name
+}
+
+/// Make `name` unique among `names` by appending a number
+fn unique(names: &mut HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while !names.insert(candidate.clone()) {
+        candidate = format!("{name}_{n}");
+        n += 1;
+    }
+    candidate
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "as" | "async"
+            | "await"
+            | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "dyn"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "abstract"
+            | "become"
+            | "box"
+            | "do"
+            | "final"
+            | "gen"
+            | "macro"
+            | "override"
+            | "priv"
+            | "try"
+            | "typeof"
+            | "unsized"
+            | "virtual"
+            | "yield"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let code = generate(include_bytes!("../tests/assets/mouse2.data")).unwrap();
+        assert_eq!(code, include_str!("../tests/assets/mouse2.rs"));
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(snake_case("Keyboard a and A"), "keyboard_a_and_a");
+        assert_eq!(snake_case("LampArray"), "lamp_array");
+        assert_eq!(snake_case("3D Digitizer"), "_3d_digitizer");
+        assert_eq!(snake_case("Move"), "move_");
+        assert_eq!(camel_case("AC Pan"), "AcPan");
+        assert_eq!(camel_case("D-pad Up"), "DPadUp");
+        assert_eq!(camel_case("+10"), "Usage10");
+
+        let mut names = HashSet::new();
+        assert_eq!(unique(&mut names, "x".to_owned()), "x");
+        assert_eq!(unique(&mut names, "x".to_owned()), "x_2");
+    }
+
+    #[cfg(feature = "derive")]
+    #[allow(dead_code)]
+    mod mouse2 {
+        include!("../tests/assets/mouse2.rs");
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_generated_types() {
+        use mouse2::{Input, InputReport3Consumer};
+
+        let report = Input::from_bytes(&[0x01, 0x05, 0xFE, 0xFF, 0x2C, 0x01, 0xFF, 0x01]);
+        let Ok(Input::Report1(pointer)) = report else {
+            panic!("{report:?}");
+        };
+        assert!(pointer.button_1 && !pointer.button_2 && pointer.button_3);
+        assert_eq!(
+            (pointer.x, pointer.y, pointer.wheel, pointer.ac_pan),
+            (-2, 300, -1, 1)
+        );
+
+        let report = Input::from_bytes(&[0x03, 0xE9, 0x00]);
+        let Ok(Input::Report3(consumer)) = report else {
+            panic!("{report:?}");
+        };
+        let usage = InputReport3Consumer::from_value(consumer.consumer.into()).unwrap();
+        assert_eq!(usage, InputReport3Consumer::VolumeIncrement);
+        assert_eq!(usage.usage(), usage::consumer::VOLUME_INCREMENT);
+        assert_eq!(usage.value(), 0xE9);
+
+        assert!(Input::from_bytes(&[0x04, 0x00]).is_err());
+        assert_eq!(
+            mouse2::REPORT_DESCRIPTOR,
+            include_bytes!("../tests/assets/mouse2.data")
+        );
+    }
+}
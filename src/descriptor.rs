@@ -0,0 +1,837 @@
+//! Parser for HID report descriptors
+//!
+//! A report descriptor describes the layout of every report a device sends
+//! or accepts. [`ReportDescriptor::parse`] turns the raw bytes returned by
+//! [`HidDevice::get_report_descriptor`](crate::HidDevice::get_report_descriptor)
+//! into collections, reports and fields. The lower level [`items`] function
+//! gives access to the individual items in the order they appear.
+//!
+//! [`ReportDescriptor::decode`] uses the parsed layout to turn the bytes of a
+//! report into the values of its usages, and [`ReportDescriptor::builder`]
+//! assembles Output and Feature reports from usage values. [`disassemble`]
+//! prints the items of a descriptor for humans, and [`Builder`] or the
+//! [`hid_descriptor!`](crate::hid_descriptor) macro assemble new descriptors.
+//! [`validate`] lists all problems of a descriptor, including the ones that
+//! the parser tolerates.
+//!
+//! The parser follows chapter 6.2.2 of the USB "Device Class Definition for
+//! Human Interface Devices (HID)" 1.11 specification.
+//!
+//! ```
+//! use hidapi::descriptor::{ReportDescriptor, ReportType, Usage};
+//!
+//! // A three button mouse with relative X and Y axes
+//! let bytes = [
+//!     0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, 0x09, 0x01, 0xA1, 0x00, 0x05, 0x09, 0x19, 0x01,
+//!     0x29, 0x03, 0x15, 0x00, 0x25, 0x01, 0x75, 0x01, 0x95, 0x03, 0x81, 0x02, 0x95, 0x05,
+//!     0x81, 0x03, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x81, 0x25, 0x7F, 0x75, 0x08,
+//!     0x95, 0x02, 0x81, 0x06, 0xC0, 0xC0,
+//! ];
+//!
+//! let descriptor = ReportDescriptor::parse(&bytes).unwrap();
+//! let report = descriptor.report(ReportType::Input, 0).unwrap();
+//! assert_eq!(report.byte_length(), 3);
+//! assert_eq!(report.fields().len(), 3);
+//!
+//! // Button 2 pressed, moved 5 to the left
+//! let values = descriptor.decode_input(&[0b010, 0xFB, 0x00]).unwrap();
+//! assert!(values.is_active(Usage::new(0x09, 2)));
+//! assert_eq!(values.value(Usage::new(0x01, 0x30)).unwrap().logical(), -5);
+//! ```
+
+mod builder;
+mod caps;
+mod decode;
+mod disassemble;
+mod encode;
+mod parser;
+#[cfg(test)]
+mod tests;
+mod validate;
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{BitOr, BitOrAssign, RangeInclusive};
+
+pub(crate) use builder::write_item;
+pub use builder::Builder;
+pub use caps::{ButtonCaps, Capabilities, ValueCaps};
+pub use decode::{ReportValues, UsageValue};
+pub use disassemble::{disassemble, Disassembly};
+pub use encode::ReportBuilder;
+pub use parser::{items, Item, Items};
+pub use validate::{validate, Diagnostic, Lint, Severity};
+
+/// The type of a report descriptor item, encoded in bits 2 and 3 of its prefix
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ItemType {
+    Main,
+    Global,
+    Local,
+    Reserved,
+}
+
+/// The tag of a short report descriptor item
+///
+/// The discriminant is the item prefix with the size bits cleared.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum Tag {
+    Input = 0x80,
+    Output = 0x90,
+    Feature = 0xB0,
+    Collection = 0xA0,
+    EndCollection = 0xC0,
+    UsagePage = 0x04,
+    LogicalMinimum = 0x14,
+    LogicalMaximum = 0x24,
+    PhysicalMinimum = 0x34,
+    PhysicalMaximum = 0x44,
+    UnitExponent = 0x54,
+    Unit = 0x64,
+    ReportSize = 0x74,
+    ReportId = 0x84,
+    ReportCount = 0x94,
+    Push = 0xA4,
+    Pop = 0xB4,
+    Usage = 0x08,
+    UsageMinimum = 0x18,
+    UsageMaximum = 0x28,
+    DesignatorIndex = 0x38,
+    DesignatorMinimum = 0x48,
+    DesignatorMaximum = 0x58,
+    StringIndex = 0x78,
+    StringMinimum = 0x88,
+    StringMaximum = 0x98,
+    Delimiter = 0xA8,
+}
+
+impl Tag {
+    /// Look up the tag of a short item prefix. The size bits are ignored.
+    pub fn from_prefix(prefix: u8) -> Option<Tag> {
+        let tag = match prefix & 0xFC {
+            0x80 => Tag::Input,
+            0x90 => Tag::Output,
+            0xB0 => Tag::Feature,
+            0xA0 => Tag::Collection,
+            0xC0 => Tag::EndCollection,
+            0x04 => Tag::UsagePage,
+            0x14 => Tag::LogicalMinimum,
+            0x24 => Tag::LogicalMaximum,
+            0x34 => Tag::PhysicalMinimum,
+            0x44 => Tag::PhysicalMaximum,
+            0x54 => Tag::UnitExponent,
+            0x64 => Tag::Unit,
+            0x74 => Tag::ReportSize,
+            0x84 => Tag::ReportId,
+            0x94 => Tag::ReportCount,
+            0xA4 => Tag::Push,
+            0xB4 => Tag::Pop,
+            0x08 => Tag::Usage,
+            0x18 => Tag::UsageMinimum,
+            0x28 => Tag::UsageMaximum,
+            0x38 => Tag::DesignatorIndex,
+            0x48 => Tag::DesignatorMinimum,
+            0x58 => Tag::DesignatorMaximum,
+            0x78 => Tag::StringIndex,
+            0x88 => Tag::StringMinimum,
+            0x98 => Tag::StringMaximum,
+            0xA8 => Tag::Delimiter,
+            _ => return None,
+        };
+        Some(tag)
+    }
+
+    pub fn item_type(self) -> ItemType {
+        item_type_of(self as u8)
+    }
+}
+
+fn item_type_of(prefix: u8) -> ItemType {
+    match (prefix >> 2) & 0x03 {
+        0 => ItemType::Main,
+        1 => ItemType::Global,
+        2 => ItemType::Local,
+        _ => ItemType::Reserved,
+    }
+}
+
+/// A usage, made up of a usage page and a usage ID
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Usage {
+    pub page: u16,
+    pub id: u16,
+}
+
+impl Usage {
+    pub const fn new(page: u16, id: u16) -> Self {
+        Self { page, id }
+    }
+
+    /// Split an extended (32 bit) usage into usage page and usage ID
+    pub const fn from_extended(usage: u32) -> Self {
+        Self {
+            page: (usage >> 16) as u16,
+            id: usage as u16,
+        }
+    }
+
+    /// The extended (32 bit) representation of this usage
+    pub const fn extended(self) -> u32 {
+        (self.page as u32) << 16 | self.id as u32
+    }
+}
+
+/// An inclusive range of usages on a single usage page
+///
+/// A single Usage item is represented as a range where `minimum == maximum`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct UsageRange {
+    pub page: u16,
+    pub minimum: u16,
+    pub maximum: u16,
+}
+
+impl UsageRange {
+    /// Number of usages in the range
+    pub fn len(&self) -> usize {
+        (self.maximum as usize + 1).saturating_sub(self.minimum as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether this range was declared by a single Usage item
+    pub fn is_single(&self) -> bool {
+        self.minimum == self.maximum
+    }
+
+    pub fn contains(&self, usage: Usage) -> bool {
+        usage.page == self.page && (self.minimum..=self.maximum).contains(&usage.id)
+    }
+
+    /// Get the n-th usage of the range
+    pub fn get(&self, index: usize) -> Option<Usage> {
+        (index < self.len()).then(|| Usage::new(self.page, self.minimum + index as u16))
+    }
+}
+
+/// The kind of report a field belongs to
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ReportType {
+    Input,
+    Output,
+    Feature,
+}
+
+impl ReportType {
+    pub const fn values() -> [Self; 3] {
+        [Self::Input, Self::Output, Self::Feature]
+    }
+}
+
+/// The type of a collection, from the data of the Collection item
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CollectionType {
+    Physical,
+    Application,
+    Logical,
+    Report,
+    NamedArray,
+    UsageSwitch,
+    UsageModifier,
+    Reserved(u8),
+    VendorDefined(u8),
+}
+
+impl From<u8> for CollectionType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => Self::Physical,
+            0x01 => Self::Application,
+            0x02 => Self::Logical,
+            0x03 => Self::Report,
+            0x04 => Self::NamedArray,
+            0x05 => Self::UsageSwitch,
+            0x06 => Self::UsageModifier,
+            0x80..=0xFF => Self::VendorDefined(value),
+            _ => Self::Reserved(value),
+        }
+    }
+}
+
+impl From<CollectionType> for u8 {
+    fn from(value: CollectionType) -> Self {
+        match value {
+            CollectionType::Physical => 0x00,
+            CollectionType::Application => 0x01,
+            CollectionType::Logical => 0x02,
+            CollectionType::Report => 0x03,
+            CollectionType::NamedArray => 0x04,
+            CollectionType::UsageSwitch => 0x05,
+            CollectionType::UsageModifier => 0x06,
+            CollectionType::Reserved(v) | CollectionType::VendorDefined(v) => v,
+        }
+    }
+}
+
+/// The data bits of an Input, Output or Feature item (USB HID spec 1.11 chapter 6.2.2.5)
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct MainItemFlags(pub u32);
+
+impl MainItemFlags {
+    /// Data, Array, Absolute and all other flags cleared
+    pub const DATA: Self = Self(0);
+    pub const CONSTANT: Self = Self(1 << 0);
+    pub const VARIABLE: Self = Self(1 << 1);
+    pub const RELATIVE: Self = Self(1 << 2);
+    pub const WRAP: Self = Self(1 << 3);
+    pub const NONLINEAR: Self = Self(1 << 4);
+    pub const NO_PREFERRED_STATE: Self = Self(1 << 5);
+    pub const NULL_STATE: Self = Self(1 << 6);
+    pub const VOLATILE: Self = Self(1 << 7);
+    pub const BUFFERED_BYTES: Self = Self(1 << 8);
+
+    /// Constant (as opposed to Data)
+    pub fn is_constant(self) -> bool {
+        self.0 & (1 << 0) != 0
+    }
+    /// Variable (as opposed to Array)
+    pub fn is_variable(self) -> bool {
+        self.0 & (1 << 1) != 0
+    }
+    pub fn is_array(self) -> bool {
+        !self.is_variable()
+    }
+    /// Relative (as opposed to Absolute)
+    pub fn is_relative(self) -> bool {
+        self.0 & (1 << 2) != 0
+    }
+    pub fn wraps(self) -> bool {
+        self.0 & (1 << 3) != 0
+    }
+    pub fn is_nonlinear(self) -> bool {
+        self.0 & (1 << 4) != 0
+    }
+    pub fn has_no_preferred_state(self) -> bool {
+        self.0 & (1 << 5) != 0
+    }
+    pub fn has_null_state(self) -> bool {
+        self.0 & (1 << 6) != 0
+    }
+    /// Only meaningful for Output and Feature items
+    pub fn is_volatile(self) -> bool {
+        self.0 & (1 << 7) != 0
+    }
+    pub fn is_buffered_bytes(self) -> bool {
+        self.0 & (1 << 8) != 0
+    }
+}
+
+impl BitOr for MainItemFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for MainItemFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// The measuring system of a [`Unit`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UnitSystem {
+    None,
+    SiLinear,
+    SiRotation,
+    EnglishLinear,
+    EnglishRotation,
+    Reserved(u8),
+    VendorDefined,
+}
+
+/// The raw value of a Unit item (USB HID spec 1.11 chapter 6.2.2.7)
+///
+/// Each nibble above the system nibble holds the signed exponent of one base unit.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Unit(pub u32);
+
+impl Unit {
+    pub fn system(self) -> UnitSystem {
+        match self.nibble(0) {
+            0x0 => UnitSystem::None,
+            0x1 => UnitSystem::SiLinear,
+            0x2 => UnitSystem::SiRotation,
+            0x3 => UnitSystem::EnglishLinear,
+            0x4 => UnitSystem::EnglishRotation,
+            0xF => UnitSystem::VendorDefined,
+            n => UnitSystem::Reserved(n),
+        }
+    }
+
+    /// Whether the unit has no system or no base unit with a non zero exponent
+    pub fn is_none(self) -> bool {
+        self.system() == UnitSystem::None || self.0 & 0xFFFF_FFF0 == 0
+    }
+
+    /// Exponent of the length unit (cm or inch)
+    pub fn length(self) -> i8 {
+        self.exponent(1)
+    }
+    /// Exponent of the mass unit (gram or slug)
+    pub fn mass(self) -> i8 {
+        self.exponent(2)
+    }
+    /// Exponent of the time unit (seconds)
+    pub fn time(self) -> i8 {
+        self.exponent(3)
+    }
+    /// Exponent of the temperature unit (Kelvin or Fahrenheit)
+    pub fn temperature(self) -> i8 {
+        self.exponent(4)
+    }
+    /// Exponent of the current unit (Ampere)
+    pub fn current(self) -> i8 {
+        self.exponent(5)
+    }
+    /// Exponent of the luminous intensity unit (Candela)
+    pub fn luminous_intensity(self) -> i8 {
+        self.exponent(6)
+    }
+
+    fn nibble(self, index: u32) -> u8 {
+        ((self.0 >> (index * 4)) & 0x0F) as u8
+    }
+
+    fn exponent(self, index: u32) -> i8 {
+        nibble_to_i8(self.nibble(index))
+    }
+}
+
+/// Sign extend a 4 bit two's complement value
+fn nibble_to_i8(nibble: u8) -> i8 {
+    ((nibble << 4) as i8) >> 4
+}
+
+/// A collection of the report descriptor
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Collection {
+    pub(crate) offset: usize,
+    pub(crate) parent: Option<usize>,
+    pub(crate) collection_type: CollectionType,
+    pub(crate) usage: Usage,
+    pub(crate) aliases: Vec<Usage>,
+}
+
+impl Collection {
+    /// Byte offset of the Collection item in the descriptor
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Index of the enclosing collection in [`ReportDescriptor::collections`]
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    pub fn collection_type(&self) -> CollectionType {
+        self.collection_type
+    }
+
+    /// The preferred usage of the collection
+    pub fn usage(&self) -> Usage {
+        self.usage
+    }
+
+    /// Alternative usages declared for the collection inside a delimiter set
+    pub fn aliases(&self) -> &[Usage] {
+        &self.aliases
+    }
+}
+
+/// A single Input, Output or Feature item together with the global and
+/// local state that was in effect for it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Field {
+    pub(crate) offset: usize,
+    pub(crate) report_type: ReportType,
+    pub(crate) report_id: u8,
+    pub(crate) flags: MainItemFlags,
+    pub(crate) bit_offset: usize,
+    pub(crate) report_size: u32,
+    pub(crate) report_count: u32,
+    pub(crate) logical_minimum: i32,
+    pub(crate) logical_maximum: i32,
+    pub(crate) physical_minimum: i32,
+    pub(crate) physical_maximum: i32,
+    /// The maxima as the sign extended item data, before they are read as
+    /// unsigned. The Windows HID parser stores these.
+    pub(crate) signed_logical_maximum: i32,
+    pub(crate) signed_physical_maximum: i32,
+    pub(crate) unit: Unit,
+    pub(crate) unit_exponent: i8,
+    pub(crate) usages: Vec<UsageRange>,
+    pub(crate) aliases: Vec<(usize, UsageRange)>,
+    pub(crate) designators: Option<RangeInclusive<u32>>,
+    pub(crate) strings: Option<RangeInclusive<u32>>,
+    pub(crate) collection: Option<usize>,
+}
+
+impl Field {
+    /// Byte offset of the main item in the descriptor
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn report_type(&self) -> ReportType {
+        self.report_type
+    }
+
+    /// The report ID, or 0 if the descriptor does not use report IDs
+    pub fn report_id(&self) -> u8 {
+        self.report_id
+    }
+
+    pub fn flags(&self) -> MainItemFlags {
+        self.flags
+    }
+
+    /// Position of the first bit of the field in the report, not counting
+    /// the report ID byte
+    pub fn bit_offset(&self) -> usize {
+        self.bit_offset
+    }
+
+    /// Size of a single control in bits
+    pub fn report_size(&self) -> u32 {
+        self.report_size
+    }
+
+    /// Number of controls in the field
+    pub fn report_count(&self) -> u32 {
+        self.report_count
+    }
+
+    /// Total size of the field in bits
+    pub fn bit_size(&self) -> usize {
+        self.report_size as usize * self.report_count as usize
+    }
+
+    pub fn logical_minimum(&self) -> i32 {
+        self.logical_minimum
+    }
+
+    pub fn logical_maximum(&self) -> i32 {
+        self.logical_maximum
+    }
+
+    /// The physical minimum, or the logical minimum if no physical range is defined
+    pub fn physical_minimum(&self) -> i32 {
+        if self.has_physical_range() {
+            self.physical_minimum
+        } else {
+            self.logical_minimum
+        }
+    }
+
+    /// The physical maximum, or the logical maximum if no physical range is defined
+    pub fn physical_maximum(&self) -> i32 {
+        if self.has_physical_range() {
+            self.physical_maximum
+        } else {
+            self.logical_maximum
+        }
+    }
+
+    /// If both Physical Minimum and Physical Maximum are 0, the logical limits
+    /// are taken as physical limits (USB HID spec 1.11 chapter 6.2.2.7)
+    pub fn has_physical_range(&self) -> bool {
+        self.physical_minimum != 0 || self.physical_maximum != 0
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    pub fn unit_exponent(&self) -> i8 {
+        self.unit_exponent
+    }
+
+    /// The usages and usage ranges declared for the field, in descriptor order
+    pub fn usages(&self) -> &[UsageRange] {
+        &self.usages
+    }
+
+    /// Alternative usages declared inside a delimiter set for the usage at `index`
+    pub fn aliases(&self, index: usize) -> impl Iterator<Item = UsageRange> + '_ {
+        self.aliases
+            .iter()
+            .filter(move |(i, _)| *i == index)
+            .map(|(_, range)| *range)
+    }
+
+    /// Designator indices declared for the field
+    pub fn designators(&self) -> Option<RangeInclusive<u32>> {
+        self.designators.clone()
+    }
+
+    /// String indices declared for the field
+    pub fn strings(&self) -> Option<RangeInclusive<u32>> {
+        self.strings.clone()
+    }
+
+    /// Index of the innermost enclosing collection in [`ReportDescriptor::collections`]
+    pub fn collection(&self) -> Option<usize> {
+        self.collection
+    }
+
+    /// Total number of usages, with all ranges expanded
+    pub fn usage_count(&self) -> usize {
+        self.usages.iter().map(UsageRange::len).sum()
+    }
+
+    /// Get the n-th usage, with all ranges expanded
+    pub fn usage_at(&self, mut index: usize) -> Option<Usage> {
+        for range in &self.usages {
+            match range.get(index) {
+                Some(usage) => return Some(usage),
+                None => index -= range.len(),
+            }
+        }
+        None
+    }
+
+    /// Position of `usage` among all usages, with all ranges expanded
+    pub fn usage_index(&self, usage: Usage) -> Option<usize> {
+        let mut index = 0;
+        for range in &self.usages {
+            if range.contains(usage) {
+                return Some(index + (usage.id - range.minimum) as usize);
+            }
+            index += range.len();
+        }
+        None
+    }
+
+    /// The usage of the control at `index` of a variable field
+    ///
+    /// If there are fewer usages than controls, the last usage applies to
+    /// all remaining controls (USB HID spec 1.11 chapter 6.2.2.8).
+    pub fn control_usage(&self, index: usize) -> Option<Usage> {
+        self.usage_at(index).or_else(|| {
+            self.usage_count()
+                .checked_sub(1)
+                .and_then(|last| self.usage_at(last))
+        })
+    }
+}
+
+/// All fields of a single report
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Report {
+    pub(crate) report_type: ReportType,
+    pub(crate) report_id: u8,
+    pub(crate) bit_length: usize,
+    pub(crate) fields: Vec<Field>,
+}
+
+impl Report {
+    pub fn report_type(&self) -> ReportType {
+        self.report_type
+    }
+
+    /// The report ID, or 0 if the descriptor does not use report IDs
+    pub fn report_id(&self) -> u8 {
+        self.report_id
+    }
+
+    /// Length of the report data in bits, not counting the report ID byte
+    pub fn bit_length(&self) -> usize {
+        self.bit_length
+    }
+
+    /// Length of the report data in bytes, not counting the report ID byte
+    pub fn byte_length(&self) -> usize {
+        self.bit_length.div_ceil(8)
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+}
+
+/// A parsed report descriptor
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ReportDescriptor {
+    pub(crate) collections: Vec<Collection>,
+    pub(crate) reports: Vec<Report>,
+}
+
+impl ReportDescriptor {
+    /// Parse a report descriptor from its raw bytes
+    pub fn parse(bytes: &[u8]) -> Result<Self, DescriptorError> {
+        parser::parse(bytes)
+    }
+
+    /// All collections in the order they are opened in the descriptor
+    pub fn collections(&self) -> &[Collection] {
+        &self.collections
+    }
+
+    /// All reports in the order they first appear in the descriptor
+    pub fn reports(&self) -> &[Report] {
+        &self.reports
+    }
+
+    /// Find a report by type and ID. Use 0 if the descriptor does not use report IDs.
+    pub fn report(&self, report_type: ReportType, report_id: u8) -> Option<&Report> {
+        self.reports
+            .iter()
+            .find(|r| r.report_type == report_type && r.report_id == report_id)
+    }
+
+    /// Iterate over all fields of all reports
+    pub fn fields(&self) -> impl Iterator<Item = &Field> {
+        self.reports.iter().flat_map(|r| r.fields.iter())
+    }
+
+    /// Whether the reports are prefixed with a report ID
+    pub fn uses_report_ids(&self) -> bool {
+        self.reports.iter().any(|r| r.report_id != 0)
+    }
+
+    /// The top level collections of the descriptor
+    pub fn top_level_collections(&self) -> impl Iterator<Item = &Collection> {
+        self.collections.iter().filter(|c| c.parent.is_none())
+    }
+}
+
+impl TryFrom<&[u8]> for ReportDescriptor {
+    type Error = DescriptorError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+/// The reason a report descriptor could not be parsed
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DescriptorErrorKind {
+    /// The descriptor ends in the middle of an item
+    UnexpectedEnd,
+    /// An End Collection item without a matching Collection item
+    UnbalancedEndCollection,
+    /// The descriptor ends while a collection is still open
+    UnclosedCollection,
+    /// A Pop item without a matching Push item
+    PopWithoutPush,
+    /// A Report ID item with the reserved value 0
+    InvalidReportId,
+    /// Nested, unbalanced or unknown Delimiter items
+    InvalidDelimiter,
+    /// Usage Minimum and Usage Maximum are on different usage pages
+    UsagePageMismatch,
+    /// The report grows beyond the size that can be represented
+    ReportTooLarge,
+}
+
+impl Display for DescriptorErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::UnexpectedEnd => "unexpected end of descriptor",
+            Self::UnbalancedEndCollection => "End Collection without Collection",
+            Self::UnclosedCollection => "unclosed collection",
+            Self::PopWithoutPush => "Pop without Push",
+            Self::InvalidReportId => "invalid report ID",
+            Self::InvalidDelimiter => "invalid delimiter",
+            Self::UsagePageMismatch => "usage range spans multiple usage pages",
+            Self::ReportTooLarge => "report too large",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Error returned when a report descriptor is malformed
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DescriptorError {
+    offset: usize,
+    kind: DescriptorErrorKind,
+}
+
+impl DescriptorError {
+    pub(crate) fn new(offset: usize, kind: DescriptorErrorKind) -> Self {
+        Self { offset, kind }
+    }
+
+    /// Byte offset of the offending item in the descriptor
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn kind(&self) -> DescriptorErrorKind {
+        self.kind
+    }
+}
+
+impl Display for DescriptorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)
+    }
+}
+
+impl Error for DescriptorError {}
+
+/// Error returned when a report does not match its descriptor
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReportError {
+    /// The descriptor does not define a report of this type and ID
+    UnknownReport {
+        report_type: ReportType,
+        report_id: u8,
+    },
+    /// The report data is shorter than described
+    InvalidLength { expected: usize, actual: usize },
+    /// The report has no data control with this usage
+    UnknownUsage { usage: Usage },
+    /// The value is outside of the logical range of the control
+    ValueOutOfRange { usage: Usage, value: i64 },
+    /// All controls of the array field are already in use
+    ArrayFull { usage: Usage },
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownReport {
+                report_type,
+                report_id,
+            } => write!(f, "unknown {report_type:?} report {report_id}"),
+            Self::InvalidLength { expected, actual } => write!(
+                f,
+                "invalid report length: expected {expected} bytes, got {actual}"
+            ),
+            Self::UnknownUsage { usage } => write!(
+                f,
+                "no control for usage {:#06X}:{:#06X}",
+                usage.page, usage.id
+            ),
+            Self::ValueOutOfRange { usage, value } => write!(
+                f,
+                "value {value} out of range for usage {:#06X}:{:#06X}",
+                usage.page, usage.id
+            ),
+            Self::ArrayFull { usage } => write!(
+                f,
+                "no free array control for usage {:#06X}:{:#06X}",
+                usage.page, usage.id
+            ),
+        }
+    }
+}
+
+impl Error for ReportError {}
@@ -0,0 +1,321 @@
+use super::{CollectionType, MainItemFlags, Tag, Unit, Usage};
+
+/// Assembles a report descriptor from its items
+///
+/// Each method appends one short item (USB HID spec 1.11 chapter 6.2.2.2)
+/// with the smallest data size that holds its value. Items with data use at
+/// least one byte, like most descriptor tools do. Logical and physical
+/// extents are encoded as signed values, all other items as unsigned values.
+///
+/// The [`hid_descriptor!`](crate::hid_descriptor) macro provides the same
+/// items in a more compact notation.
+///
+/// ```
+/// use hidapi::descriptor::{Builder, CollectionType, MainItemFlags, ReportDescriptor};
+///
+/// // A three button mouse with relative X and Y axes
+/// let bytes = Builder::new()
+///     .usage_page(0x01)
+///     .usage(0x02)
+///     .collection(CollectionType::Application, |b| {
+///         b.usage(0x01)
+///             .collection(CollectionType::Physical, |b| {
+///                 b.usage_page(0x09)
+///                     .usage_minimum(1)
+///                     .usage_maximum(3)
+///                     .logical_minimum(0)
+///                     .logical_maximum(1)
+///                     .report_size(1)
+///                     .report_count(3)
+///                     .input(MainItemFlags::VARIABLE)
+///                     .report_count(5)
+///                     .input(MainItemFlags::CONSTANT | MainItemFlags::VARIABLE)
+///                     .usage_page(0x01)
+///                     .usage(0x30)
+///                     .usage(0x31)
+///                     .logical_minimum(-127)
+///                     .logical_maximum(127)
+///                     .report_size(8)
+///                     .report_count(2)
+///                     .input(MainItemFlags::VARIABLE | MainItemFlags::RELATIVE)
+///             })
+///     })
+///     .build();
+///
+/// let descriptor = ReportDescriptor::parse(&bytes).unwrap();
+/// assert_eq!(descriptor.reports()[0].byte_length(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    bytes: Vec<u8>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The encoded descriptor
+    pub fn build(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Append an item with raw data
+    ///
+    /// The data of signed items is the two's complement of the value. Push,
+    /// Pop and End Collection items are written without data.
+    pub fn item(mut self, tag: Tag, data: u32) -> Self {
+        let data = if is_signed(tag) {
+            data as i32 as i64
+        } else {
+            data as i64
+        };
+        write_item(&mut self.bytes, tag, data).expect("32 bit item data");
+        self
+    }
+
+    pub fn input(self, flags: MainItemFlags) -> Self {
+        self.item(Tag::Input, flags.0)
+    }
+
+    pub fn output(self, flags: MainItemFlags) -> Self {
+        self.item(Tag::Output, flags.0)
+    }
+
+    pub fn feature(self, flags: MainItemFlags) -> Self {
+        self.item(Tag::Feature, flags.0)
+    }
+
+    /// Append a Collection item, the items added by `items` and the matching
+    /// End Collection item
+    pub fn collection(
+        self,
+        collection_type: CollectionType,
+        items: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        items(self.item(Tag::Collection, u8::from(collection_type) as u32))
+            .item(Tag::EndCollection, 0)
+    }
+
+    pub fn usage_page(self, usage_page: u16) -> Self {
+        self.item(Tag::UsagePage, usage_page as u32)
+    }
+
+    pub fn logical_minimum(self, value: i32) -> Self {
+        self.item(Tag::LogicalMinimum, value as u32)
+    }
+
+    pub fn logical_maximum(self, value: i32) -> Self {
+        self.item(Tag::LogicalMaximum, value as u32)
+    }
+
+    pub fn physical_minimum(self, value: i32) -> Self {
+        self.item(Tag::PhysicalMinimum, value as u32)
+    }
+
+    pub fn physical_maximum(self, value: i32) -> Self {
+        self.item(Tag::PhysicalMaximum, value as u32)
+    }
+
+    /// Append a Unit Exponent item, encoded as a nibble like the examples of
+    /// the specification
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exponent is not within `-8..=7`.
+    pub fn unit_exponent(self, exponent: i8) -> Self {
+        assert!((-8..=7).contains(&exponent), "unit exponent out of range");
+        self.item(Tag::UnitExponent, (exponent as u32) & 0x0F)
+    }
+
+    pub fn unit(self, unit: Unit) -> Self {
+        self.item(Tag::Unit, unit.0)
+    }
+
+    pub fn report_size(self, bits: u32) -> Self {
+        self.item(Tag::ReportSize, bits)
+    }
+
+    pub fn report_id(self, report_id: u8) -> Self {
+        self.item(Tag::ReportId, report_id as u32)
+    }
+
+    pub fn report_count(self, count: u32) -> Self {
+        self.item(Tag::ReportCount, count)
+    }
+
+    pub fn push(self) -> Self {
+        self.item(Tag::Push, 0)
+    }
+
+    pub fn pop(self) -> Self {
+        self.item(Tag::Pop, 0)
+    }
+
+    /// Append a Usage item with a usage ID on the current usage page
+    pub fn usage(self, id: u16) -> Self {
+        self.item(Tag::Usage, id as u32)
+    }
+
+    /// Append a Usage item with the usage page included in its data
+    pub fn extended_usage(self, usage: Usage) -> Self {
+        self.item(Tag::Usage, usage.extended())
+    }
+
+    pub fn usage_minimum(self, id: u16) -> Self {
+        self.item(Tag::UsageMinimum, id as u32)
+    }
+
+    pub fn usage_maximum(self, id: u16) -> Self {
+        self.item(Tag::UsageMaximum, id as u32)
+    }
+
+    pub fn designator_index(self, index: u32) -> Self {
+        self.item(Tag::DesignatorIndex, index)
+    }
+
+    pub fn designator_minimum(self, index: u32) -> Self {
+        self.item(Tag::DesignatorMinimum, index)
+    }
+
+    pub fn designator_maximum(self, index: u32) -> Self {
+        self.item(Tag::DesignatorMaximum, index)
+    }
+
+    pub fn string_index(self, index: u32) -> Self {
+        self.item(Tag::StringIndex, index)
+    }
+
+    pub fn string_minimum(self, index: u32) -> Self {
+        self.item(Tag::StringMinimum, index)
+    }
+
+    pub fn string_maximum(self, index: u32) -> Self {
+        self.item(Tag::StringMaximum, index)
+    }
+
+    /// Append the usages added by `items` as a set of aliases, enclosed in
+    /// Delimiter items
+    pub fn delimited(self, items: impl FnOnce(Self) -> Self) -> Self {
+        items(self.item(Tag::Delimiter, 1)).item(Tag::Delimiter, 0)
+    }
+}
+
+fn is_signed(tag: Tag) -> bool {
+    matches!(
+        tag,
+        Tag::LogicalMinimum | Tag::LogicalMaximum | Tag::PhysicalMinimum | Tag::PhysicalMaximum
+    )
+}
+
+/// Append a short item with the smallest data size that holds `data`, or
+/// return `None` if it does not fit into 32 bits
+pub(crate) fn write_item(bytes: &mut Vec<u8>, tag: Tag, data: i64) -> Option<()> {
+    let prefix = tag as u8;
+    if matches!(tag, Tag::EndCollection | Tag::Push | Tag::Pop) {
+        bytes.push(prefix);
+    } else if is_signed(tag) {
+        if let Ok(data) = i8::try_from(data) {
+            bytes.push(prefix | 0x01);
+            bytes.extend(data.to_le_bytes());
+        } else if let Ok(data) = i16::try_from(data) {
+            bytes.push(prefix | 0x02);
+            bytes.extend(data.to_le_bytes());
+        } else {
+            bytes.push(prefix | 0x03);
+            bytes.extend(i32::try_from(data).ok()?.to_le_bytes());
+        }
+    } else if let Ok(data) = u8::try_from(data) {
+        bytes.push(prefix | 0x01);
+        bytes.extend(data.to_le_bytes());
+    } else if let Ok(data) = u16::try_from(data) {
+        bytes.push(prefix | 0x02);
+        bytes.extend(data.to_le_bytes());
+    } else {
+        bytes.push(prefix | 0x03);
+        bytes.extend(u32::try_from(data).ok()?.to_le_bytes());
+    }
+    Some(())
+}
+
+/// Assemble a report descriptor from a list of items
+///
+/// Every item is the name of a [`Builder`] method with its arguments, and
+/// the items are separated by commas. Collections and delimited sets of
+/// aliases take their items in braces. The flags of Input, Output and Feature
+/// items are the names of [`MainItemFlags`](crate::descriptor::MainItemFlags)
+/// constants, separated by `|`. The macro evaluates to the bytes of the
+/// descriptor.
+///
+/// ```
+/// use hidapi::hid_descriptor;
+///
+/// // A three button mouse with relative X and Y axes
+/// let bytes = hid_descriptor! {
+///     usage_page(0x01),
+///     usage(0x02),
+///     collection(Application) {
+///         usage(0x01),
+///         collection(Physical) {
+///             usage_page(0x09),
+///             usage_minimum(1),
+///             usage_maximum(3),
+///             logical_minimum(0),
+///             logical_maximum(1),
+///             report_size(1),
+///             report_count(3),
+///             input(VARIABLE),
+///             report_count(5),
+///             input(CONSTANT | VARIABLE),
+///             usage_page(0x01),
+///             usage(0x30),
+///             usage(0x31),
+///             logical_minimum(-127),
+///             logical_maximum(127),
+///             report_size(8),
+///             report_count(2),
+///             input(VARIABLE | RELATIVE),
+///         },
+///     },
+/// };
+///
+/// assert_eq!(bytes[..6], [0x05, 0x01, 0x09, 0x02, 0xA1, 0x01]);
+/// ```
+#[macro_export]
+macro_rules! hid_descriptor {
+    (@items $b:ident; $($name:ident $args:tt $({ $($inner:tt)* })?),* $(,)?) => {
+        $( $b = $crate::hid_descriptor!(@item $b; $name $args $({ $($inner)* })?); )*
+    };
+    (@item $b:ident; collection($kind:ident) { $($inner:tt)* }) => {
+        $b.collection($crate::descriptor::CollectionType::$kind, |mut $b| {
+            $crate::hid_descriptor!(@items $b; $($inner)*);
+            $b
+        })
+    };
+    (@item $b:ident; delimited() { $($inner:tt)* }) => {
+        $b.delimited(|mut $b| {
+            $crate::hid_descriptor!(@items $b; $($inner)*);
+            $b
+        })
+    };
+    (@item $b:ident; input($($flag:ident)|*)) => {
+        $b.input($crate::hid_descriptor!(@flags $($flag)|*))
+    };
+    (@item $b:ident; output($($flag:ident)|*)) => {
+        $b.output($crate::hid_descriptor!(@flags $($flag)|*))
+    };
+    (@item $b:ident; feature($($flag:ident)|*)) => {
+        $b.feature($crate::hid_descriptor!(@flags $($flag)|*))
+    };
+    (@item $b:ident; $name:ident($($arg:expr),* $(,)?)) => {
+        $b.$name($($arg),*)
+    };
+    (@flags $($flag:ident)|*) => {
+        $crate::descriptor::MainItemFlags::DATA $(| $crate::descriptor::MainItemFlags::$flag)*
+    };
+    ($($items:tt)*) => {{
+        let mut builder = $crate::descriptor::Builder::new();
+        $crate::hid_descriptor!(@items builder; $($items)*);
+        builder.build()
+    }};
+}
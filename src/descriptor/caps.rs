@@ -0,0 +1,238 @@
+use super::{Field, MainItemFlags, ReportDescriptor, ReportType, Unit, Usage, UsageRange};
+use std::ops::RangeInclusive;
+
+/// Summary of the reports of a device, modeled after the Win32 `HIDP_CAPS`
+/// structure
+///
+/// Report lengths follow the Windows convention: they include the report ID
+/// byte, even if the descriptor does not use report IDs, and are 0 if the
+/// device has no report of a type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    usage: Usage,
+    report_byte_lengths: [usize; 3],
+    report_ids: Vec<u8>,
+    number_link_collection_nodes: usize,
+    button_caps: Vec<ButtonCaps>,
+    value_caps: Vec<ValueCaps>,
+}
+
+impl Capabilities {
+    /// The usage of the first top level collection
+    pub fn usage(&self) -> Usage {
+        self.usage
+    }
+
+    /// Length of the longest report of a type, including the report ID byte
+    pub fn report_byte_length(&self, report_type: ReportType) -> usize {
+        self.report_byte_lengths[report_type as usize]
+    }
+
+    /// The length of a buffer that can hold any input report
+    pub fn input_report_byte_length(&self) -> usize {
+        self.report_byte_length(ReportType::Input)
+    }
+
+    /// The length of a buffer that can hold any output report
+    pub fn output_report_byte_length(&self) -> usize {
+        self.report_byte_length(ReportType::Output)
+    }
+
+    /// The length of a buffer that can hold any feature report
+    pub fn feature_report_byte_length(&self) -> usize {
+        self.report_byte_length(ReportType::Feature)
+    }
+
+    /// The report IDs used by the device in ascending order, empty if the
+    /// descriptor does not use report IDs
+    pub fn report_ids(&self) -> &[u8] {
+        &self.report_ids
+    }
+
+    /// Number of collections in the descriptor
+    pub fn number_link_collection_nodes(&self) -> usize {
+        self.number_link_collection_nodes
+    }
+
+    /// Button capabilities of all report types
+    pub fn button_caps(&self) -> &[ButtonCaps] {
+        &self.button_caps
+    }
+
+    /// Value capabilities of all report types
+    pub fn value_caps(&self) -> &[ValueCaps] {
+        &self.value_caps
+    }
+
+    /// Number of button capabilities of a report type
+    pub fn number_button_caps(&self, report_type: ReportType) -> usize {
+        self.button_caps
+            .iter()
+            .filter(|c| c.report_type == report_type)
+            .count()
+    }
+
+    /// Number of value capabilities of a report type
+    pub fn number_value_caps(&self, report_type: ReportType) -> usize {
+        self.value_caps
+            .iter()
+            .filter(|c| c.report_type == report_type)
+            .count()
+    }
+}
+
+/// A usage or usage range of a field that reports on/off states, modeled
+/// after the Win32 `HIDP_BUTTON_CAPS` structure
+///
+/// Array fields and variable fields with a report size of 1 are buttons.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ButtonCaps {
+    pub report_type: ReportType,
+    /// The report ID, or 0 if the descriptor does not use report IDs
+    pub report_id: u8,
+    pub usages: UsageRange,
+    /// Whether the usages were declared as alternative inside a delimiter set
+    pub is_alias: bool,
+    /// Index of the enclosing collection in [`ReportDescriptor::collections`]
+    pub collection: Option<usize>,
+    pub flags: MainItemFlags,
+    /// Position of the field in the report, not counting the report ID byte
+    pub bit_offset: usize,
+    pub report_count: u32,
+    pub logical_minimum: i32,
+    pub logical_maximum: i32,
+    pub strings: Option<RangeInclusive<u32>>,
+    pub designators: Option<RangeInclusive<u32>>,
+}
+
+/// A usage or usage range of a field that reports values, modeled after the
+/// Win32 `HIDP_VALUE_CAPS` structure
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ValueCaps {
+    pub report_type: ReportType,
+    /// The report ID, or 0 if the descriptor does not use report IDs
+    pub report_id: u8,
+    pub usages: UsageRange,
+    /// Whether the usages were declared as alternative inside a delimiter set
+    pub is_alias: bool,
+    /// Index of the enclosing collection in [`ReportDescriptor::collections`]
+    pub collection: Option<usize>,
+    pub flags: MainItemFlags,
+    /// Position of the first control in the report, not counting the report ID byte
+    pub bit_offset: usize,
+    pub bit_size: u32,
+    pub report_count: u32,
+    pub logical_minimum: i32,
+    pub logical_maximum: i32,
+    pub physical_minimum: i32,
+    pub physical_maximum: i32,
+    pub unit: Unit,
+    pub unit_exponent: i8,
+    pub strings: Option<RangeInclusive<u32>>,
+    pub designators: Option<RangeInclusive<u32>>,
+}
+
+impl ReportDescriptor {
+    /// Compute the capabilities of the device, as `HidP_GetCaps` would on Windows
+    pub fn capabilities(&self) -> Capabilities {
+        let mut report_byte_lengths = [0; 3];
+        let mut report_ids = Vec::new();
+        let mut button_caps = Vec::new();
+        let mut value_caps = Vec::new();
+
+        for report in &self.reports {
+            let length = &mut report_byte_lengths[report.report_type as usize];
+            *length = (*length).max(report.byte_length() + 1);
+            if report.report_id != 0 {
+                report_ids.push(report.report_id);
+            }
+
+            for field in &report.fields {
+                for (usages, is_alias, bit_offset, report_count) in field_caps(field) {
+                    if field.flags.is_array() || field.report_size == 1 {
+                        button_caps.push(ButtonCaps {
+                            report_type: field.report_type,
+                            report_id: field.report_id,
+                            usages,
+                            is_alias,
+                            collection: field.collection,
+                            flags: field.flags,
+                            bit_offset,
+                            report_count,
+                            logical_minimum: field.logical_minimum,
+                            logical_maximum: field.logical_maximum,
+                            strings: field.strings.clone(),
+                            designators: field.designators.clone(),
+                        });
+                    } else {
+                        value_caps.push(ValueCaps {
+                            report_type: field.report_type,
+                            report_id: field.report_id,
+                            usages,
+                            is_alias,
+                            collection: field.collection,
+                            flags: field.flags,
+                            bit_offset,
+                            bit_size: field.report_size,
+                            report_count,
+                            logical_minimum: field.logical_minimum,
+                            logical_maximum: field.logical_maximum,
+                            physical_minimum: field.physical_minimum,
+                            physical_maximum: field.physical_maximum,
+                            unit: field.unit,
+                            unit_exponent: field.unit_exponent,
+                            strings: field.strings.clone(),
+                            designators: field.designators.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        report_ids.sort_unstable();
+        report_ids.dedup();
+
+        Capabilities {
+            usage: self
+                .top_level_collections()
+                .next()
+                .map_or(Usage::new(0, 0), |c| c.usage),
+            report_byte_lengths,
+            report_ids,
+            number_link_collection_nodes: self.collections.len(),
+            button_caps,
+            value_caps,
+        }
+    }
+}
+
+/// Split a field into one capability per usage or usage range
+///
+/// Each capability of a variable field covers as many controls as it has
+/// usages, the last one covers all remaining controls. Every capability of
+/// an array field covers all controls. Aliases share the controls of their
+/// preferred usage.
+fn field_caps(field: &Field) -> Vec<(UsageRange, bool, usize, u32)> {
+    let mut caps = Vec::new();
+    let mut control = 0;
+    for (index, usages) in field.usages.iter().enumerate() {
+        let (bit_offset, report_count) = if field.flags.is_array() {
+            (field.bit_offset, field.report_count)
+        } else {
+            let remaining = field.report_count.saturating_sub(control);
+            let count = if index + 1 == field.usages.len() {
+                remaining
+            } else {
+                remaining.min(usages.len() as u32)
+            };
+            let bit_offset = field.bit_offset + (control * field.report_size) as usize;
+            control += count;
+            (bit_offset, count)
+        };
+
+        caps.push((*usages, false, bit_offset, report_count));
+        for alias in field.aliases(index) {
+            caps.push((alias, true, bit_offset, report_count));
+        }
+    }
+    caps
+}
@@ -0,0 +1,230 @@
+use super::{Field, Report, ReportDescriptor, ReportError, ReportType, Usage};
+
+/// The value of a single control of a variable field
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UsageValue {
+    usage: Usage,
+    field: usize,
+    index: usize,
+    logical: i64,
+    physical: Option<f64>,
+}
+
+impl UsageValue {
+    pub fn usage(&self) -> Usage {
+        self.usage
+    }
+
+    /// Index of the field in [`Report::fields`]
+    pub fn field(&self) -> usize {
+        self.field
+    }
+
+    /// Index of the control within its field
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The logical value, sign extended if the logical minimum is negative
+    pub fn logical(&self) -> i64 {
+        self.logical
+    }
+
+    /// The value scaled to the physical range and the unit exponent
+    ///
+    /// `None` if the value lies outside of the logical range, which signals
+    /// the null state of the control.
+    pub fn physical(&self) -> Option<f64> {
+        self.physical
+    }
+}
+
+/// All values of a decoded report
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReportValues {
+    report_id: u8,
+    values: Vec<UsageValue>,
+    active_usages: Vec<Usage>,
+}
+
+impl ReportValues {
+    /// The report ID, or 0 if the descriptor does not use report IDs
+    pub fn report_id(&self) -> u8 {
+        self.report_id
+    }
+
+    /// The values of all controls of variable fields
+    pub fn values(&self) -> &[UsageValue] {
+        &self.values
+    }
+
+    /// The first value reported for `usage`
+    pub fn value(&self, usage: Usage) -> Option<&UsageValue> {
+        self.values.iter().find(|v| v.usage == usage)
+    }
+
+    /// The usages reported by array fields, e.g. the keys pressed on a keyboard
+    pub fn active_usages(&self) -> &[Usage] {
+        &self.active_usages
+    }
+
+    /// Whether `usage` is reported by an array field or has a non zero value
+    pub fn is_active(&self, usage: Usage) -> bool {
+        self.active_usages.contains(&usage) || self.value(usage).is_some_and(|v| v.logical != 0)
+    }
+}
+
+impl Report {
+    /// Decode the report data, without the report ID byte
+    pub fn decode(&self, data: &[u8]) -> Result<ReportValues, ReportError> {
+        if data.len() < self.byte_length() {
+            return Err(ReportError::InvalidLength {
+                expected: self.byte_length(),
+                actual: data.len(),
+            });
+        }
+
+        let mut result = ReportValues {
+            report_id: self.report_id,
+            ..Default::default()
+        };
+        for (field_index, field) in self.fields.iter().enumerate() {
+            // Padding, and controls too large to be a single value
+            if field.usages.is_empty() || field.report_size == 0 || field.report_size > 32 {
+                continue;
+            }
+
+            for index in 0..field.report_count as usize {
+                let raw = read_bits(
+                    data,
+                    field.bit_offset + index * field.report_size as usize,
+                    field.report_size,
+                );
+                let logical = field.logical_value(raw);
+
+                if field.flags.is_variable() {
+                    if let Some(usage) = field.control_usage(index) {
+                        result.values.push(UsageValue {
+                            usage,
+                            field: field_index,
+                            index,
+                            logical,
+                            physical: field.physical_value(logical),
+                        });
+                    }
+                } else if let Some(usage) = field.array_usage(logical) {
+                    result.active_usages.push(usage);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl ReportDescriptor {
+    /// Decode a report
+    ///
+    /// Input reports are expected as returned by
+    /// [`HidDevice::read`](crate::HidDevice::read): the first byte is the
+    /// report ID only if the descriptor uses report IDs. Output and Feature
+    /// reports always start with the report ID, which is 0 if the descriptor
+    /// does not use report IDs, as for
+    /// [`HidDevice::get_feature_report`](crate::HidDevice::get_feature_report).
+    pub fn decode(
+        &self,
+        report_type: ReportType,
+        data: &[u8],
+    ) -> Result<ReportValues, ReportError> {
+        let (report_id, payload) = self.split_report_id(report_type, data)?;
+        self.report(report_type, report_id)
+            .ok_or(ReportError::UnknownReport {
+                report_type,
+                report_id,
+            })?
+            .decode(payload)
+    }
+
+    /// Decode an input report as returned by [`HidDevice::read`](crate::HidDevice::read)
+    pub fn decode_input(&self, data: &[u8]) -> Result<ReportValues, ReportError> {
+        self.decode(ReportType::Input, data)
+    }
+
+    fn split_report_id<'a>(
+        &self,
+        report_type: ReportType,
+        data: &'a [u8],
+    ) -> Result<(u8, &'a [u8]), ReportError> {
+        if report_type != ReportType::Input || self.uses_report_ids() {
+            match data.split_first() {
+                Some((id, payload)) => Ok((*id, payload)),
+                None => Err(ReportError::InvalidLength {
+                    expected: 1,
+                    actual: 0,
+                }),
+            }
+        } else {
+            Ok((0, data))
+        }
+    }
+}
+
+impl Field {
+    /// Interpret raw report bits as logical value
+    ///
+    /// The value is sign extended if the logical minimum is negative.
+    pub fn logical_value(&self, raw: u32) -> i64 {
+        let size = self.report_size.min(32);
+        if self.logical_minimum < 0 && size > 0 {
+            let shift = 64 - size;
+            ((raw as i64) << shift) >> shift
+        } else {
+            raw as i64
+        }
+    }
+
+    /// Scale a logical value to the physical range and unit exponent
+    ///
+    /// Returns `None` for values outside of the logical range.
+    pub fn physical_value(&self, logical: i64) -> Option<f64> {
+        let logical_minimum = self.logical_minimum as i64;
+        let logical_maximum = self.logical_maximum as i64;
+        if logical < logical_minimum || logical > logical_maximum {
+            return None;
+        }
+
+        let physical_minimum = self.physical_minimum() as f64;
+        let physical_maximum = self.physical_maximum() as f64;
+        let value = if logical_maximum == logical_minimum {
+            physical_minimum
+        } else {
+            physical_minimum
+                + (logical - logical_minimum) as f64 * (physical_maximum - physical_minimum)
+                    / (logical_maximum - logical_minimum) as f64
+        };
+        Some(value * 10f64.powi(self.unit_exponent as i32))
+    }
+
+    /// The usage selected by the value of an array control
+    ///
+    /// Values outside of the logical range and usage ID 0 ("no event") select
+    /// no usage.
+    pub fn array_usage(&self, logical: i64) -> Option<Usage> {
+        let index = logical.checked_sub(self.logical_minimum as i64)?;
+        if index < 0 || logical > self.logical_maximum as i64 {
+            return None;
+        }
+        self.usage_at(index as usize).filter(|usage| usage.id != 0)
+    }
+}
+
+/// Read up to 32 bits starting at an arbitrary bit position, least significant bit first
+pub(crate) fn read_bits(data: &[u8], bit_offset: usize, bit_size: u32) -> u32 {
+    let mut value = 0u64;
+    let first_byte = bit_offset / 8;
+    let last_byte = (bit_offset + bit_size as usize).div_ceil(8);
+    for (i, byte) in data[first_byte..last_byte].iter().enumerate() {
+        value |= (*byte as u64) << (i * 8);
+    }
+    let value = value >> (bit_offset % 8);
+    (value & ((1u64 << bit_size) - 1)) as u32
+}
@@ -0,0 +1,282 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::parser::unit_exponent;
+use super::{items, CollectionType, Item, MainItemFlags, Tag, Unit, UnitSystem, Usage};
+use crate::usage;
+
+/// Width of the column with the bytes of an item, fits the longest short item
+const BYTES_WIDTH: usize = 30;
+/// Width of the column with the decoded item, before the offset
+const TEXT_WIDTH: usize = 48;
+
+/// Render a report descriptor in the format of the HID Descriptor Tool
+///
+/// Every item is printed on its own line, with its bytes as C array
+/// elements, and a comment with the decoded item and its byte offset. Items
+/// within collections are indented. The output of a descriptor that ends in
+/// the middle of an item ends with the remaining bytes and the error.
+///
+/// ```
+/// use hidapi::descriptor::disassemble;
+///
+/// let bytes = [0x05, 0x01, 0x09, 0x06, 0xA1, 0x01, 0x75, 0x08, 0x81, 0x03, 0xC0];
+/// let text = disassemble(&bytes).to_string();
+/// assert_eq!(
+///     text.lines().nth(3).unwrap(),
+///     "0x75, 0x08,                   //   Report Size (8)                                6",
+/// );
+/// ```
+///
+/// The full output of the example is
+///
+/// ```text
+/// 0x05, 0x01,                   // Usage Page (Generic Desktop)                     0
+/// 0x09, 0x06,                   // Usage (Keyboard)                                 2
+/// 0xA1, 0x01,                   // Collection (Application)                         4
+/// 0x75, 0x08,                   //   Report Size (8)                                6
+/// 0x81, 0x03,                   //   Input (Const,Var,Abs,No Wrap,Linear,Preferred State,No Null Position) 8
+/// 0xC0,                         // End Collection                                   10
+/// ```
+pub fn disassemble(bytes: &[u8]) -> Disassembly<'_> {
+    Disassembly { bytes }
+}
+
+/// A report descriptor that is displayed as its items, see [`disassemble`]
+#[derive(Debug, Clone, Copy)]
+pub struct Disassembly<'a> {
+    bytes: &'a [u8],
+}
+
+impl Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut depth = 0usize;
+        let mut usage_page = 0u16;
+        let mut usage_pages = Vec::new();
+
+        for item in items(self.bytes) {
+            let item = match item {
+                Ok(item) => item,
+                Err(e) => {
+                    let rest = &self.bytes[e.offset()..];
+                    return write_line(f, rest.iter().copied(), 0, &e.kind(), e.offset());
+                }
+            };
+
+            let text = match item.tag() {
+                Some(Tag::UsagePage) => {
+                    usage_page = item.unsigned() as u16;
+                    format!("Usage Page ({})", PageName(usage_page))
+                }
+                Some(Tag::Usage) => format!("Usage ({})", usage_text(&item, usage_page)),
+                Some(Tag::UsageMinimum) => {
+                    format!("Usage Minimum ({})", usage_text(&item, usage_page))
+                }
+                Some(Tag::UsageMaximum) => {
+                    format!("Usage Maximum ({})", usage_text(&item, usage_page))
+                }
+                Some(Tag::Collection) => {
+                    let collection_type = CollectionType::from(item.unsigned() as u8);
+                    format!("Collection ({})", collection_name(collection_type))
+                }
+                Some(Tag::EndCollection) => {
+                    depth = depth.saturating_sub(1);
+                    "End Collection".to_owned()
+                }
+                Some(tag @ (Tag::Input | Tag::Output | Tag::Feature)) => {
+                    let flags = main_item_flags(MainItemFlags(item.unsigned()), tag != Tag::Input);
+                    format!("{} ({})", tag_name(tag), flags.join(","))
+                }
+                Some(Tag::Push) => {
+                    usage_pages.push(usage_page);
+                    "Push".to_owned()
+                }
+                Some(Tag::Pop) => {
+                    usage_page = usage_pages.pop().unwrap_or(usage_page);
+                    "Pop".to_owned()
+                }
+                Some(Tag::Unit) => format!("Unit ({})", unit_text(Unit(item.unsigned()))),
+                Some(Tag::UnitExponent) => format!("Unit Exponent ({})", unit_exponent(&item)),
+                Some(Tag::Delimiter) => match item.unsigned() {
+                    0 => "Delimiter (Close)".to_owned(),
+                    1 => "Delimiter (Open)".to_owned(),
+                    n => format!("Delimiter ({n})"),
+                },
+                Some(
+                    tag @ (Tag::LogicalMinimum
+                    | Tag::LogicalMaximum
+                    | Tag::PhysicalMinimum
+                    | Tag::PhysicalMaximum),
+                ) => format!("{} ({})", tag_name(tag), item.signed()),
+                Some(tag) => format!("{} ({})", tag_name(tag), item.unsigned()),
+                None if item.is_long() => {
+                    format!("Long Item (0x{:02X})", item.long_item_tag().unwrap_or(0))
+                }
+                None => format!("Reserved (0x{:02X})", item.prefix()),
+            };
+
+            write_line(f, item.bytes(), depth, &text, item.offset())?;
+            if item.tag() == Some(Tag::Collection) {
+                depth += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_line(
+    f: &mut Formatter<'_>,
+    bytes: impl Iterator<Item = u8>,
+    depth: usize,
+    text: &dyn Display,
+    offset: usize,
+) -> fmt::Result {
+    let bytes = bytes.map(|b| format!("0x{b:02X}, ")).collect::<String>();
+    let text = format!("{:indent$}{text}", "", indent = depth * 2);
+    writeln!(
+        f,
+        "{:BYTES_WIDTH$}// {text:TEXT_WIDTH$} {offset}",
+        bytes.trim_end()
+    )
+}
+
+fn tag_name(tag: Tag) -> &'static str {
+    match tag {
+        Tag::Input => "Input",
+        Tag::Output => "Output",
+        Tag::Feature => "Feature",
+        Tag::Collection => "Collection",
+        Tag::EndCollection => "End Collection",
+        Tag::UsagePage => "Usage Page",
+        Tag::LogicalMinimum => "Logical Minimum",
+        Tag::LogicalMaximum => "Logical Maximum",
+        Tag::PhysicalMinimum => "Physical Minimum",
+        Tag::PhysicalMaximum => "Physical Maximum",
+        Tag::UnitExponent => "Unit Exponent",
+        Tag::Unit => "Unit",
+        Tag::ReportSize => "Report Size",
+        Tag::ReportId => "Report ID",
+        Tag::ReportCount => "Report Count",
+        Tag::Push => "Push",
+        Tag::Pop => "Pop",
+        Tag::Usage => "Usage",
+        Tag::UsageMinimum => "Usage Minimum",
+        Tag::UsageMaximum => "Usage Maximum",
+        Tag::DesignatorIndex => "Designator Index",
+        Tag::DesignatorMinimum => "Designator Minimum",
+        Tag::DesignatorMaximum => "Designator Maximum",
+        Tag::StringIndex => "String Index",
+        Tag::StringMinimum => "String Minimum",
+        Tag::StringMaximum => "String Maximum",
+        Tag::Delimiter => "Delimiter",
+    }
+}
+
+fn collection_name(collection_type: CollectionType) -> String {
+    match collection_type {
+        CollectionType::Physical => "Physical".to_owned(),
+        CollectionType::Application => "Application".to_owned(),
+        CollectionType::Logical => "Logical".to_owned(),
+        CollectionType::Report => "Report".to_owned(),
+        CollectionType::NamedArray => "Named Array".to_owned(),
+        CollectionType::UsageSwitch => "Usage Switch".to_owned(),
+        CollectionType::UsageModifier => "Usage Modifier".to_owned(),
+        CollectionType::Reserved(n) => format!("Reserved 0x{n:02X}"),
+        CollectionType::VendorDefined(n) => format!("Vendor Defined 0x{n:02X}"),
+    }
+}
+
+fn main_item_flags(flags: MainItemFlags, has_volatile: bool) -> Vec<&'static str> {
+    let pick = |set: bool, yes, no| if set { yes } else { no };
+    let mut names = vec![
+        pick(flags.is_constant(), "Const", "Data"),
+        pick(flags.is_variable(), "Var", "Array"),
+        pick(flags.is_relative(), "Rel", "Abs"),
+        pick(flags.wraps(), "Wrap", "No Wrap"),
+        pick(flags.is_nonlinear(), "Non-linear", "Linear"),
+        pick(
+            flags.has_no_preferred_state(),
+            "No Preferred State",
+            "Preferred State",
+        ),
+        pick(flags.has_null_state(), "Null State", "No Null Position"),
+    ];
+    if has_volatile {
+        names.push(pick(flags.is_volatile(), "Volatile", "Non-volatile"));
+    }
+    if flags.is_buffered_bytes() {
+        names.push("Buffered Bytes");
+    }
+    names
+}
+
+/// The usage of a Usage, Usage Minimum or Usage Maximum item
+fn usage_text(item: &Item, usage_page: u16) -> String {
+    if item.data().len() == 4 {
+        let usage = Usage::from_extended(item.unsigned());
+        format!("{}: {}", PageName(usage.page), UsageName(usage))
+    } else {
+        UsageName(Usage::new(usage_page, item.unsigned() as u16)).to_string()
+    }
+}
+
+/// "System: SI Linear, Length: Centimeter, Time: Seconds^-1"
+fn unit_text(unit: Unit) -> String {
+    let system = match unit.system() {
+        UnitSystem::None => return "None".to_owned(),
+        UnitSystem::SiLinear => "SI Linear",
+        UnitSystem::SiRotation => "SI Rotation",
+        UnitSystem::EnglishLinear => "English Linear",
+        UnitSystem::EnglishRotation => "English Rotation",
+        UnitSystem::VendorDefined => return format!("Vendor Defined 0x{:08X}", unit.0),
+        UnitSystem::Reserved(_) => return format!("Reserved 0x{:08X}", unit.0),
+    };
+    let (length, mass, temperature) = match unit.system() {
+        UnitSystem::SiLinear => ("Centimeter", "Gram", "Kelvin"),
+        UnitSystem::SiRotation => ("Radians", "Gram", "Kelvin"),
+        UnitSystem::EnglishLinear => ("Inch", "Slug", "Fahrenheit"),
+        _ => ("Degrees", "Slug", "Fahrenheit"),
+    };
+
+    let mut text = format!("System: {system}");
+    let base_units = [
+        ("Length", length, unit.length()),
+        ("Mass", mass, unit.mass()),
+        ("Time", "Seconds", unit.time()),
+        ("Temperature", temperature, unit.temperature()),
+        ("Current", "Ampere", unit.current()),
+        ("Luminous Intensity", "Candela", unit.luminous_intensity()),
+    ];
+    for (quantity, name, exponent) in base_units {
+        match exponent {
+            0 => {}
+            1 => text += &format!(", {quantity}: {name}"),
+            _ => text += &format!(", {quantity}: {name}^{exponent}"),
+        }
+    }
+    text
+}
+
+/// The name of a usage page, or its number
+struct PageName(u16);
+
+impl Display for PageName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match usage::page_name(self.0) {
+            Some(name) => f.write_str(name),
+            None if self.0 >= 0xFF00 => write!(f, "Vendor Defined 0x{:04X}", self.0),
+            None => write!(f, "0x{:04X}", self.0),
+        }
+    }
+}
+
+/// The name of a usage, or its number
+struct UsageName(Usage);
+
+impl Display for UsageName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match usage::name(self.0) {
+            Some(name) => f.write_str(&name),
+            None => write!(f, "0x{:02X}", self.0.id),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use super::{Report, ReportDescriptor, ReportError, ReportType, Usage};
+
+/// Builds the bytes of an Output or Feature report from usage values
+///
+/// The buffer always starts with the report ID, which is 0 if the descriptor
+/// does not use report IDs. It can be passed unchanged to
+/// [`HidDevice::write`](crate::HidDevice::write) or
+/// [`HidDevice::send_feature_report`](crate::HidDevice::send_feature_report).
+/// All controls that are not set are 0.
+#[derive(Debug, Clone)]
+pub struct ReportBuilder<'a> {
+    report: &'a Report,
+    data: Vec<u8>,
+    array_counts: Vec<u32>,
+}
+
+impl<'a> ReportBuilder<'a> {
+    pub fn new(report: &'a Report) -> Self {
+        let mut data = vec![0; report.byte_length() + 1];
+        data[0] = report.report_id;
+        Self {
+            report,
+            data,
+            array_counts: vec![0; report.fields.len()],
+        }
+    }
+
+    pub fn report(&self) -> &'a Report {
+        self.report
+    }
+
+    /// Set the control of a variable field with the given usage
+    ///
+    /// The value must lie within the logical range of the control.
+    pub fn set(&mut self, usage: Usage, value: i64) -> Result<&mut Self, ReportError> {
+        let (field, index) = self
+            .report
+            .fields
+            .iter()
+            .filter(|f| f.flags.is_variable() && !f.flags.is_constant())
+            .find_map(|f| {
+                (0..f.report_count as usize)
+                    .find(|&i| f.control_usage(i) == Some(usage))
+                    .map(|i| (f, i))
+            })
+            .ok_or(ReportError::UnknownUsage { usage })?;
+
+        if value < field.logical_minimum as i64 || value > field.logical_maximum as i64 {
+            return Err(ReportError::ValueOutOfRange { usage, value });
+        }
+        write_bits(
+            &mut self.data[1..],
+            field.bit_offset + index * field.report_size as usize,
+            field.report_size,
+            value,
+        );
+        Ok(self)
+    }
+
+    /// Report a usage of an array field as active, e.g. a key press
+    ///
+    /// Each call occupies the next free control of the array.
+    pub fn set_active(&mut self, usage: Usage) -> Result<&mut Self, ReportError> {
+        let (field_index, field, usage_index) = self
+            .report
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.flags.is_array() && !f.flags.is_constant())
+            .find_map(|(i, f)| f.usage_index(usage).map(|u| (i, f, u)))
+            .ok_or(ReportError::UnknownUsage { usage })?;
+
+        let value = field.logical_minimum as i64 + usage_index as i64;
+        if value > field.logical_maximum as i64 {
+            return Err(ReportError::ValueOutOfRange { usage, value });
+        }
+        let index = self.array_counts[field_index];
+        if index >= field.report_count {
+            return Err(ReportError::ArrayFull { usage });
+        }
+        write_bits(
+            &mut self.data[1..],
+            field.bit_offset + (index * field.report_size) as usize,
+            field.report_size,
+            value,
+        );
+        self.array_counts[field_index] += 1;
+        Ok(self)
+    }
+
+    /// The report as built so far, including the report ID
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the builder and return the report, including the report ID
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl Report {
+    /// Start building this report from usage values
+    pub fn builder(&self) -> ReportBuilder<'_> {
+        ReportBuilder::new(self)
+    }
+}
+
+impl ReportDescriptor {
+    /// Start building the report of the given type and ID
+    ///
+    /// Use report ID 0 if the descriptor does not use report IDs.
+    pub fn builder(
+        &self,
+        report_type: ReportType,
+        report_id: u8,
+    ) -> Result<ReportBuilder<'_>, ReportError> {
+        self.report(report_type, report_id)
+            .map(Report::builder)
+            .ok_or(ReportError::UnknownReport {
+                report_type,
+                report_id,
+            })
+    }
+}
+
+/// Write the lowest `bit_size` bits of `value` at an arbitrary bit position, least significant bit first
+pub(crate) fn write_bits(data: &mut [u8], bit_offset: usize, bit_size: u32, value: i64) {
+    for bit in 0..bit_size as usize {
+        let position = bit_offset + bit;
+        let mask = 1 << (position % 8);
+        if (value >> bit.min(63)) & 1 != 0 {
+            data[position / 8] |= mask;
+        } else {
+            data[position / 8] &= !mask;
+        }
+    }
+}
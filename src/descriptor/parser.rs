@@ -0,0 +1,499 @@
+use super::{
+    item_type_of, nibble_to_i8, Collection, CollectionType, DescriptorError, DescriptorErrorKind,
+    Field, ItemType, MainItemFlags, Report, ReportDescriptor, ReportType, Tag, Unit, Usage,
+    UsageRange,
+};
+use std::ops::RangeInclusive;
+
+/// Prefix of a long item (USB HID spec 1.11 chapter 6.2.2.3)
+const LONG_ITEM_PREFIX: u8 = 0xFE;
+
+/// Iterate over the items of a report descriptor
+///
+/// The iterator stops after the first error.
+pub fn items(bytes: &[u8]) -> Items<'_> {
+    Items { bytes, offset: 0 }
+}
+
+/// Iterator over the items of a report descriptor, see [`items`]
+#[derive(Debug, Clone)]
+pub struct Items<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Result<Item<'a>, DescriptorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        let prefix = *self.bytes.get(offset)?;
+
+        // Long items carry their data size in the second byte and their tag in the third
+        let (header_len, data_len) = if prefix == LONG_ITEM_PREFIX {
+            match self.bytes.get(offset + 1) {
+                Some(size) => (3, *size as usize),
+                None => (3, 0),
+            }
+        } else {
+            (1, [0, 1, 2, 4][(prefix & 0x03) as usize])
+        };
+
+        let end = offset + header_len + data_len;
+        if end > self.bytes.len() {
+            self.offset = self.bytes.len();
+            return Some(Err(DescriptorError::new(
+                offset,
+                DescriptorErrorKind::UnexpectedEnd,
+            )));
+        }
+
+        self.offset = end;
+        Some(Ok(Item {
+            offset,
+            prefix,
+            header: &self.bytes[offset..offset + header_len],
+            data: &self.bytes[offset + header_len..end],
+        }))
+    }
+}
+
+/// A single item of a report descriptor
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Item<'a> {
+    offset: usize,
+    prefix: u8,
+    header: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> Item<'a> {
+    /// Byte offset of the item in the descriptor
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The first byte of the item
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// The tag of a short item, `None` for long items and reserved tags
+    pub fn tag(&self) -> Option<Tag> {
+        if self.is_long() {
+            None
+        } else {
+            Tag::from_prefix(self.prefix)
+        }
+    }
+
+    pub fn item_type(&self) -> ItemType {
+        item_type_of(self.prefix)
+    }
+
+    pub fn is_long(&self) -> bool {
+        self.prefix == LONG_ITEM_PREFIX
+    }
+
+    /// The tag of a long item
+    pub fn long_item_tag(&self) -> Option<u8> {
+        self.is_long().then(|| self.header[2])
+    }
+
+    /// The data bytes of the item
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The full encoding of the item, including its prefix
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + 'a {
+        self.header.iter().chain(self.data).copied()
+    }
+
+    /// Total length of the item in bytes
+    pub fn len(&self) -> usize {
+        self.header.len() + self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The item data as unsigned little endian value
+    pub fn unsigned(&self) -> u32 {
+        let mut bytes = [0u8; 4];
+        let len = self.data.len().min(4);
+        bytes[..len].copy_from_slice(&self.data[..len]);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// The item data as signed two's complement little endian value
+    pub fn signed(&self) -> i32 {
+        match self.data.len() {
+            0 => 0,
+            1 => self.data[0] as i8 as i32,
+            2 => i16::from_le_bytes([self.data[0], self.data[1]]) as i32,
+            _ => self.unsigned() as i32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct GlobalState {
+    usage_page: u16,
+    logical_minimum: i32,
+    logical_maximum: Bound,
+    physical_minimum: i32,
+    physical_maximum: Bound,
+    unit_exponent: i8,
+    unit: u32,
+    report_size: u32,
+    report_id: u8,
+    report_count: u32,
+}
+
+/// A maximum value as found in the descriptor.
+///
+/// Many descriptors encode e.g. a Logical Maximum of 255 as the single byte
+/// 0xFF. Following the Linux kernel, such values are read as unsigned if the
+/// minimum is not negative.
+#[derive(Debug, Copy, Clone, Default)]
+struct Bound {
+    signed: i32,
+    unsigned: u32,
+}
+
+impl Bound {
+    fn from_item(item: &Item) -> Self {
+        Self {
+            signed: item.signed(),
+            unsigned: item.unsigned(),
+        }
+    }
+
+    fn resolve(self, minimum: i32) -> i32 {
+        if minimum >= 0 && self.signed < minimum {
+            i32::try_from(self.unsigned).unwrap_or(self.signed)
+        } else {
+            self.signed
+        }
+    }
+}
+
+/// A usage or usage range, before the usage page is applied.
+///
+/// Usages with less than four data bytes use the usage page in effect when
+/// the main item is processed (USB HID spec 1.11 chapter 6.2.2.8).
+#[derive(Debug, Copy, Clone)]
+struct RawUsage {
+    value: u32,
+    extended: bool,
+}
+
+impl RawUsage {
+    fn from_item(item: &Item) -> Self {
+        Self {
+            value: item.unsigned(),
+            extended: item.data().len() == 4,
+        }
+    }
+
+    fn page(self, usage_page: u16) -> u16 {
+        if self.extended {
+            (self.value >> 16) as u16
+        } else {
+            usage_page
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct PendingUsage {
+    offset: usize,
+    minimum: RawUsage,
+    maximum: RawUsage,
+    /// Index of the preferred usage if this is an alias inside a delimiter set
+    alias_of: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct LocalState {
+    usages: Vec<PendingUsage>,
+    usage_minimum: Option<(usize, RawUsage)>,
+    usage_maximum: Option<RawUsage>,
+    designator_index: Option<u32>,
+    designator_minimum: Option<u32>,
+    designator_maximum: Option<u32>,
+    string_index: Option<u32>,
+    string_minimum: Option<u32>,
+    string_maximum: Option<u32>,
+    delimiter: Option<DelimiterSet>,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct DelimiterSet {
+    preferred: Option<usize>,
+}
+
+impl LocalState {
+    fn push_usage(&mut self, offset: usize, minimum: RawUsage, maximum: RawUsage) {
+        let index = self.usages.iter().filter(|u| u.alias_of.is_none()).count();
+        let alias_of = match &mut self.delimiter {
+            Some(DelimiterSet {
+                preferred: Some(preferred),
+            }) => Some(*preferred),
+            Some(set) => {
+                set.preferred = Some(index);
+                None
+            }
+            None => None,
+        };
+        self.usages.push(PendingUsage {
+            offset,
+            minimum,
+            maximum,
+            alias_of,
+        });
+    }
+
+    /// Record a Usage Minimum or Maximum and complete the range once both are known
+    fn push_usage_bound(&mut self, item: &Item, tag: Tag) {
+        let usage = RawUsage::from_item(item);
+        match tag {
+            Tag::UsageMinimum => self.usage_minimum = Some((item.offset(), usage)),
+            _ => self.usage_maximum = Some(usage),
+        }
+        if let (Some((offset, minimum)), Some(maximum)) = (self.usage_minimum, self.usage_maximum) {
+            self.usage_minimum = None;
+            self.usage_maximum = None;
+            self.push_usage(offset, minimum, maximum);
+        }
+    }
+
+    /// Apply the usage page and split the usages into preferred usages and aliases
+    #[allow(clippy::type_complexity)]
+    fn resolve_usages(
+        &self,
+        usage_page: u16,
+    ) -> Result<(Vec<UsageRange>, Vec<(usize, UsageRange)>), DescriptorError> {
+        let mut usages = Vec::new();
+        let mut aliases = Vec::new();
+        for pending in &self.usages {
+            let page = match (pending.minimum.extended, pending.maximum.extended) {
+                (true, true) if pending.minimum.page(0) != pending.maximum.page(0) => {
+                    return Err(DescriptorError::new(
+                        pending.offset,
+                        DescriptorErrorKind::UsagePageMismatch,
+                    ));
+                }
+                (true, _) => pending.minimum.page(usage_page),
+                (false, _) => pending.maximum.page(usage_page),
+            };
+            let range = UsageRange {
+                page,
+                minimum: pending.minimum.value as u16,
+                maximum: pending.maximum.value as u16,
+            };
+            match pending.alias_of {
+                Some(index) => aliases.push((index, range)),
+                None => usages.push(range),
+            }
+        }
+        Ok((usages, aliases))
+    }
+
+    fn designators(&self) -> Option<RangeInclusive<u32>> {
+        range_of(
+            self.designator_index,
+            self.designator_minimum,
+            self.designator_maximum,
+        )
+    }
+
+    fn strings(&self) -> Option<RangeInclusive<u32>> {
+        range_of(self.string_index, self.string_minimum, self.string_maximum)
+    }
+}
+
+fn range_of(
+    index: Option<u32>,
+    minimum: Option<u32>,
+    maximum: Option<u32>,
+) -> Option<RangeInclusive<u32>> {
+    match (minimum, maximum) {
+        (Some(min), Some(max)) => Some(min..=max),
+        _ => index.map(|i| i..=i),
+    }
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<ReportDescriptor, DescriptorError> {
+    let mut global = GlobalState::default();
+    let mut global_stack: Vec<GlobalState> = Vec::new();
+    let mut local = LocalState::default();
+    let mut collection_stack: Vec<usize> = Vec::new();
+    let mut descriptor = ReportDescriptor::default();
+
+    for item in items(bytes) {
+        let item = item?;
+        let offset = item.offset();
+        let tag = match item.tag() {
+            Some(tag) => tag,
+            // Long items and reserved tags carry no layout information
+            None => continue,
+        };
+
+        match tag {
+            Tag::Input | Tag::Output | Tag::Feature => {
+                let report_type = match tag {
+                    Tag::Input => ReportType::Input,
+                    Tag::Output => ReportType::Output,
+                    _ => ReportType::Feature,
+                };
+                let (usages, aliases) = local.resolve_usages(global.usage_page)?;
+                let report =
+                    match descriptor.reports.iter_mut().position(|r| {
+                        r.report_type == report_type && r.report_id == global.report_id
+                    }) {
+                        Some(index) => &mut descriptor.reports[index],
+                        None => {
+                            descriptor.reports.push(Report {
+                                report_type,
+                                report_id: global.report_id,
+                                bit_length: 0,
+                                fields: Vec::new(),
+                            });
+                            descriptor.reports.last_mut().unwrap()
+                        }
+                    };
+                let bit_offset = report.bit_length;
+                report.bit_length = (global.report_size as usize)
+                    .checked_mul(global.report_count as usize)
+                    .and_then(|bits| bits.checked_add(bit_offset))
+                    .filter(|bits| *bits <= u32::MAX as usize)
+                    .ok_or(DescriptorError::new(
+                        offset,
+                        DescriptorErrorKind::ReportTooLarge,
+                    ))?;
+                report.fields.push(Field {
+                    offset,
+                    report_type,
+                    report_id: global.report_id,
+                    flags: MainItemFlags(item.unsigned()),
+                    bit_offset,
+                    report_size: global.report_size,
+                    report_count: global.report_count,
+                    logical_minimum: global.logical_minimum,
+                    logical_maximum: global.logical_maximum.resolve(global.logical_minimum),
+                    physical_minimum: global.physical_minimum,
+                    physical_maximum: global.physical_maximum.resolve(global.physical_minimum),
+                    signed_logical_maximum: global.logical_maximum.signed,
+                    signed_physical_maximum: global.physical_maximum.signed,
+                    unit: Unit(global.unit),
+                    unit_exponent: global.unit_exponent,
+                    usages,
+                    aliases,
+                    designators: local.designators(),
+                    strings: local.strings(),
+                    collection: collection_stack.last().copied(),
+                });
+                local = LocalState::default();
+            }
+            Tag::Collection => {
+                let (usages, aliases) = local.resolve_usages(global.usage_page)?;
+                let usage = usages
+                    .first()
+                    .map(|range| Usage::new(range.page, range.minimum))
+                    .unwrap_or(Usage::new(global.usage_page, 0));
+                descriptor.collections.push(Collection {
+                    offset,
+                    parent: collection_stack.last().copied(),
+                    collection_type: CollectionType::from(item.unsigned() as u8),
+                    usage,
+                    aliases: aliases
+                        .into_iter()
+                        .map(|(_, range)| Usage::new(range.page, range.minimum))
+                        .collect(),
+                });
+                collection_stack.push(descriptor.collections.len() - 1);
+                local = LocalState::default();
+            }
+            Tag::EndCollection => {
+                if collection_stack.pop().is_none() {
+                    return Err(DescriptorError::new(
+                        offset,
+                        DescriptorErrorKind::UnbalancedEndCollection,
+                    ));
+                }
+                local = LocalState::default();
+            }
+            Tag::UsagePage => global.usage_page = item.unsigned() as u16,
+            Tag::LogicalMinimum => global.logical_minimum = item.signed(),
+            Tag::LogicalMaximum => global.logical_maximum = Bound::from_item(&item),
+            Tag::PhysicalMinimum => global.physical_minimum = item.signed(),
+            Tag::PhysicalMaximum => global.physical_maximum = Bound::from_item(&item),
+            Tag::UnitExponent => global.unit_exponent = unit_exponent(&item),
+            Tag::Unit => global.unit = item.unsigned(),
+            Tag::ReportSize => global.report_size = item.unsigned(),
+            Tag::ReportCount => global.report_count = item.unsigned(),
+            Tag::ReportId => {
+                global.report_id = match item.unsigned() {
+                    id @ 1..=255 => id as u8,
+                    _ => {
+                        return Err(DescriptorError::new(
+                            offset,
+                            DescriptorErrorKind::InvalidReportId,
+                        ))
+                    }
+                }
+            }
+            Tag::Push => global_stack.push(global.clone()),
+            Tag::Pop => {
+                global = global_stack.pop().ok_or(DescriptorError::new(
+                    offset,
+                    DescriptorErrorKind::PopWithoutPush,
+                ))?;
+            }
+            Tag::Usage => {
+                let usage = RawUsage::from_item(&item);
+                local.push_usage(offset, usage, usage);
+            }
+            Tag::UsageMinimum | Tag::UsageMaximum => local.push_usage_bound(&item, tag),
+            Tag::DesignatorIndex => local.designator_index = Some(item.unsigned()),
+            Tag::DesignatorMinimum => local.designator_minimum = Some(item.unsigned()),
+            Tag::DesignatorMaximum => local.designator_maximum = Some(item.unsigned()),
+            Tag::StringIndex => local.string_index = Some(item.unsigned()),
+            Tag::StringMinimum => local.string_minimum = Some(item.unsigned()),
+            Tag::StringMaximum => local.string_maximum = Some(item.unsigned()),
+            Tag::Delimiter => {
+                local.delimiter = match (item.unsigned(), local.delimiter) {
+                    (1, None) => Some(DelimiterSet::default()),
+                    (0, Some(_)) => None,
+                    _ => {
+                        return Err(DescriptorError::new(
+                            offset,
+                            DescriptorErrorKind::InvalidDelimiter,
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(index) = collection_stack.pop() {
+        return Err(DescriptorError::new(
+            descriptor.collections[index].offset,
+            DescriptorErrorKind::UnclosedCollection,
+        ));
+    }
+
+    Ok(descriptor)
+}
+
+/// The Unit Exponent is meant to be a 4 bit two's complement value, but
+/// some devices store it as a full signed integer instead.
+pub(super) fn unit_exponent(item: &Item) -> i8 {
+    match item.unsigned() {
+        v @ 0..=0x0F => nibble_to_i8(v as u8),
+        _ => item.signed() as i8,
+    }
+}
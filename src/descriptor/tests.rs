@@ -0,0 +1,746 @@
+use crate::descriptor::{
+    disassemble, items, validate, Builder, CollectionType, DescriptorErrorKind, Lint,
+    MainItemFlags, ReportDescriptor, ReportError, ReportType, Severity, Tag, Usage, UsageRange,
+};
+use std::fs::{read_dir, read_to_string};
+
+#[test]
+fn test_items() {
+    let data = include_bytes!("../../tests/assets/mouse1.data");
+    let items = items(data).collect::<Result<Vec<_>, _>>().unwrap();
+
+    let tags = items.iter().map(|i| i.tag().unwrap()).collect::<Vec<_>>();
+    assert_eq!(
+        tags,
+        vec![
+            Tag::UsagePage,
+            Tag::Usage,
+            Tag::Collection,
+            Tag::ReportId,
+            Tag::UsageMinimum,
+            Tag::UsageMaximum,
+            Tag::LogicalMinimum,
+            Tag::LogicalMaximum,
+            Tag::ReportSize,
+            Tag::ReportCount,
+            Tag::Input,
+            Tag::EndCollection,
+        ]
+    );
+    assert_eq!(items[0].unsigned(), 0xFFBC);
+    assert_eq!(items[7].offset(), 0x10);
+    assert_eq!(items[7].signed(), 255);
+}
+
+#[test]
+fn test_long_item() {
+    let data = [0xFE, 0x02, 0x10, 0xAA, 0xBB, 0xC0];
+    let items = items(&data).collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_long());
+    assert_eq!(items[0].long_item_tag(), Some(0x10));
+    assert_eq!(items[0].data(), &[0xAA, 0xBB]);
+    assert_eq!(items[0].tag(), None);
+    assert_eq!(items[1].tag(), Some(Tag::EndCollection));
+}
+
+#[test]
+fn test_truncated_item() {
+    let err = ReportDescriptor::parse(&[0x05, 0x01, 0x26, 0xFF]).unwrap_err();
+    assert_eq!(err.kind(), DescriptorErrorKind::UnexpectedEnd);
+    assert_eq!(err.offset(), 2);
+}
+
+#[test]
+fn test_mouse1() {
+    let data = include_bytes!("../../tests/assets/mouse1.data");
+    let desc = ReportDescriptor::parse(data).unwrap();
+
+    assert_eq!(desc.collections().len(), 1);
+    assert_eq!(desc.collections()[0].usage(), Usage::new(0xFFBC, 0x88));
+    assert_eq!(
+        desc.collections()[0].collection_type(),
+        CollectionType::Application
+    );
+
+    let report = desc.report(ReportType::Input, 4).unwrap();
+    assert_eq!(report.byte_length(), 1);
+    let field = &report.fields()[0];
+    assert!(field.flags().is_array());
+    assert_eq!(field.logical_maximum(), 255);
+    assert_eq!(
+        field.usages(),
+        &[UsageRange {
+            page: 0xFFBC,
+            minimum: 0,
+            maximum: 0xFF
+        }]
+    );
+    assert_eq!(field.usage_count(), 256);
+}
+
+#[test]
+fn test_mouse2() {
+    let data = include_bytes!("../../tests/assets/mouse2.data");
+    let desc = ReportDescriptor::parse(data).unwrap();
+
+    let collections = desc
+        .collections()
+        .iter()
+        .map(|c| (c.usage(), c.parent()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        collections,
+        vec![
+            (Usage::new(0x01, 0x02), None),
+            (Usage::new(0x01, 0x01), Some(0)),
+            (Usage::new(0x01, 0x80), None),
+            (Usage::new(0x0C, 0x01), None),
+            (Usage::new(0xFF00, 0x0E), None),
+        ]
+    );
+    assert!(desc.uses_report_ids());
+
+    let lengths = desc
+        .reports()
+        .iter()
+        .map(|r| (r.report_type(), r.report_id(), r.bit_length()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lengths,
+        vec![
+            (ReportType::Input, 1, 56),
+            (ReportType::Input, 2, 8),
+            (ReportType::Input, 3, 16),
+            (ReportType::Output, 0xBA, 248),
+            (ReportType::Input, 0xBA, 248),
+        ]
+    );
+
+    let pointer = desc.report(ReportType::Input, 1).unwrap();
+    let offsets = pointer
+        .fields()
+        .iter()
+        .map(|f| (f.bit_offset(), f.report_size(), f.report_count()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        offsets,
+        vec![(0, 1, 5), (5, 3, 1), (8, 16, 2), (40, 8, 1), (48, 8, 1)]
+    );
+
+    let xy = &pointer.fields()[2];
+    assert_eq!(xy.logical_minimum(), -2047);
+    assert_eq!(xy.logical_maximum(), 2047);
+    assert_eq!(xy.control_usage(0), Some(Usage::new(0x01, 0x30)));
+    assert_eq!(xy.control_usage(1), Some(Usage::new(0x01, 0x31)));
+    assert_eq!(xy.collection(), Some(1));
+
+    let pan = &pointer.fields()[4];
+    assert_eq!(pan.control_usage(0), Some(Usage::new(0x0C, 0x238)));
+}
+
+#[test]
+fn test_usage_page_applied_at_main_item() {
+    #[rustfmt::skip]
+    let data = [
+        0x09, 0x30,                   // Usage (0x30)
+        0x05, 0x01,                   // Usage Page (Generic Desktop)
+        0x0B, 0x01, 0x00, 0x09, 0x00, // Usage (Button 1, extended)
+        0x75, 0x08, 0x95, 0x02,       // Report Size (8), Report Count (2)
+        0x81, 0x02,                   // Input (Data, Var, Abs)
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+    let field = desc.fields().next().unwrap();
+
+    assert_eq!(field.control_usage(0), Some(Usage::new(0x01, 0x30)));
+    assert_eq!(field.control_usage(1), Some(Usage::new(0x09, 0x01)));
+}
+
+#[test]
+fn test_push_pop() {
+    #[rustfmt::skip]
+    let data = [
+        0x05, 0x01, 0x75, 0x08, 0x95, 0x01, // Usage Page, Report Size (8), Report Count (1)
+        0xA4,                               // Push
+        0x05, 0x09, 0x75, 0x01,             // Usage Page (Button), Report Size (1)
+        0x09, 0x01, 0x81, 0x02,             // Usage, Input
+        0xB4,                               // Pop
+        0x09, 0x30, 0x81, 0x02,             // Usage, Input
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+    let fields = desc.fields().collect::<Vec<_>>();
+
+    assert_eq!(fields[0].report_size(), 1);
+    assert_eq!(fields[0].usages()[0].page, 0x09);
+    assert_eq!(fields[1].report_size(), 8);
+    assert_eq!(fields[1].usages()[0].page, 0x01);
+    assert_eq!(fields[1].bit_offset(), 1);
+
+    let err = ReportDescriptor::parse(&[0xB4]).unwrap_err();
+    assert_eq!(err.kind(), DescriptorErrorKind::PopWithoutPush);
+}
+
+#[test]
+fn test_delimiter() {
+    #[rustfmt::skip]
+    let data = [
+        0x05, 0x01,
+        0xA9, 0x01,             // Delimiter (Open)
+        0x09, 0x30, 0x09, 0x33, // Usage (X), Usage (Rx)
+        0xA9, 0x00,             // Delimiter (Close)
+        0x09, 0x31,             // Usage (Y)
+        0x75, 0x08, 0x95, 0x02, 0x81, 0x02,
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+    let field = desc.fields().next().unwrap();
+
+    assert_eq!(field.usage_count(), 2);
+    assert_eq!(field.control_usage(0), Some(Usage::new(0x01, 0x30)));
+    assert_eq!(field.control_usage(1), Some(Usage::new(0x01, 0x31)));
+    let aliases = field.aliases(0).collect::<Vec<_>>();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].minimum, 0x33);
+
+    let err = ReportDescriptor::parse(&[0xA9, 0x01, 0xA9, 0x01]).unwrap_err();
+    assert_eq!(err.kind(), DescriptorErrorKind::InvalidDelimiter);
+}
+
+#[test]
+fn test_unbalanced_collections() {
+    let err = ReportDescriptor::parse(&[0xA1, 0x01, 0xC0, 0xC0]).unwrap_err();
+    assert_eq!(err.kind(), DescriptorErrorKind::UnbalancedEndCollection);
+    assert_eq!(err.offset(), 3);
+
+    let err = ReportDescriptor::parse(&[0xA1, 0x01, 0xA1, 0x00, 0xC0]).unwrap_err();
+    assert_eq!(err.kind(), DescriptorErrorKind::UnclosedCollection);
+    assert_eq!(err.offset(), 0);
+}
+
+#[test]
+fn test_unit() {
+    // Unit (SI Lin: cm), Unit Exponent (-2), Physical Minimum/Maximum
+    #[rustfmt::skip]
+    let data = [
+        0x65, 0x11, 0x55, 0x0E, 0x35, 0x00, 0x46, 0x10, 0x27,
+        0x75, 0x10, 0x95, 0x01, 0x81, 0x02,
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+    let field = desc.fields().next().unwrap();
+
+    assert_eq!(field.unit().length(), 1);
+    assert_eq!(field.unit_exponent(), -2);
+    assert_eq!(field.physical_maximum(), 10000);
+}
+
+#[test]
+fn test_decode_numbered() {
+    let data = include_bytes!("../../tests/assets/mouse2.data");
+    let desc = ReportDescriptor::parse(data).unwrap();
+
+    // Buttons 1 and 3, X = -2, Y = 300, wheel = -1, pan = 1
+    let values = desc
+        .decode_input(&[0x01, 0b101, 0xFE, 0xFF, 0x2C, 0x01, 0xFF, 0x01])
+        .unwrap();
+    assert_eq!(values.report_id(), 1);
+    assert!(values.is_active(Usage::new(0x09, 1)));
+    assert!(!values.is_active(Usage::new(0x09, 2)));
+    assert!(values.is_active(Usage::new(0x09, 3)));
+    assert_eq!(values.value(Usage::new(0x01, 0x30)).unwrap().logical(), -2);
+    assert_eq!(values.value(Usage::new(0x01, 0x31)).unwrap().logical(), 300);
+    assert_eq!(values.value(Usage::new(0x01, 0x38)).unwrap().logical(), -1);
+    assert_eq!(values.value(Usage::new(0x0C, 0x238)).unwrap().logical(), 1);
+
+    let err = desc.decode_input(&[0x01, 0x00]).unwrap_err();
+    assert_eq!(
+        err,
+        ReportError::InvalidLength {
+            expected: 7,
+            actual: 1
+        }
+    );
+    let err = desc.decode_input(&[0x07, 0x00]).unwrap_err();
+    assert_eq!(
+        err,
+        ReportError::UnknownReport {
+            report_type: ReportType::Input,
+            report_id: 7
+        }
+    );
+}
+
+#[test]
+fn test_decode_unnumbered() {
+    #[rustfmt::skip]
+    let data = [
+        0x05, 0x07,             // Usage Page (Keyboard)
+        0x19, 0x00, 0x29, 0x65, // Usage Minimum (0), Usage Maximum (0x65)
+        0x15, 0x00, 0x25, 0x65, // Logical Minimum (0), Logical Maximum (0x65)
+        0x75, 0x08, 0x95, 0x03, // Report Size (8), Report Count (3)
+        0x81, 0x00,             // Input (Data, Array, Abs)
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+    assert!(!desc.uses_report_ids());
+
+    let values = desc.decode_input(&[0x04, 0x00, 0x05]).unwrap();
+    assert_eq!(values.report_id(), 0);
+    assert!(values.values().is_empty());
+    assert_eq!(
+        values.active_usages(),
+        &[Usage::new(0x07, 0x04), Usage::new(0x07, 0x05)]
+    );
+
+    // Feature and Output reports always carry the report ID byte
+    let err = desc.decode(ReportType::Feature, &[0x00]).unwrap_err();
+    assert!(matches!(err, ReportError::UnknownReport { .. }));
+}
+
+#[test]
+fn test_decode_bit_fields() {
+    #[rustfmt::skip]
+    let data = [
+        0x05, 0x01,
+        0x09, 0x30, 0x15, 0x00, 0x25, 0x0F, 0x75, 0x04, 0x95, 0x01, 0x81, 0x02, // X: 4 bits
+        0x09, 0x31, 0x26, 0xFF, 0x0F, 0x75, 0x0C, 0x81, 0x02,                   // Y: 12 bits
+        0x09, 0x32, 0x16, 0x00, 0xFE, 0x26, 0xFF, 0x01, 0x75, 0x0A, 0x81, 0x02, // Z: 10 bits, signed
+        0x75, 0x06, 0x81, 0x03,                                                 // Padding
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+
+    // X = 0xA, Y = 0x5BC, Z = -3
+    let values = desc.decode_input(&[0xCA, 0x5B, 0xFD, 0x03]).unwrap();
+    let logical = values
+        .values()
+        .iter()
+        .map(|v| v.logical())
+        .collect::<Vec<_>>();
+    assert_eq!(logical, vec![0xA, 0x5BC, -3]);
+}
+
+#[test]
+fn test_decode_physical() {
+    #[rustfmt::skip]
+    let data = [
+        0x05, 0x01, 0x09, 0x30,
+        0x15, 0x00, 0x26, 0xFF, 0x00, // Logical Minimum (0), Logical Maximum (255)
+        0x35, 0x00, 0x46, 0xF6, 0x09, // Physical Minimum (0), Physical Maximum (2550)
+        0x65, 0x11, 0x55, 0x0E,       // Unit (cm), Unit Exponent (-2)
+        0x75, 0x08, 0x95, 0x01, 0x81, 0x42, // Input (Data, Var, Abs, Null)
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+
+    let value = desc.decode_input(&[100]).unwrap().values()[0];
+    assert_eq!(value.logical(), 100);
+    assert!((value.physical().unwrap() - 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_encode_unnumbered() {
+    #[rustfmt::skip]
+    let data = [
+        0x05, 0x08, 0x19, 0x01, 0x29, 0x05,       // Usage Page (LED), Usage Minimum/Maximum (1, 5)
+        0x15, 0x00, 0x25, 0x01, 0x75, 0x01, 0x95, 0x05,
+        0x91, 0x02,                               // Output (Data, Var, Abs)
+        0x95, 0x01, 0x75, 0x03, 0x91, 0x01,       // Output (Const), padding
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+
+    let mut builder = desc.builder(ReportType::Output, 0).unwrap();
+    builder
+        .set(Usage::new(0x08, 1), 1)
+        .unwrap()
+        .set(Usage::new(0x08, 3), 1)
+        .unwrap();
+    assert_eq!(builder.as_bytes(), &[0x00, 0b101]);
+
+    let err = builder.set(Usage::new(0x08, 2), 2).unwrap_err();
+    assert_eq!(
+        err,
+        ReportError::ValueOutOfRange {
+            usage: Usage::new(0x08, 2),
+            value: 2
+        }
+    );
+    let err = builder.set(Usage::new(0x08, 6), 1).unwrap_err();
+    assert!(matches!(err, ReportError::UnknownUsage { .. }));
+
+    let values = desc.decode(ReportType::Output, &builder.finish()).unwrap();
+    assert!(values.is_active(Usage::new(0x08, 3)));
+}
+
+#[test]
+fn test_encode_numbered() {
+    #[rustfmt::skip]
+    let data = [
+        0x06, 0x00, 0xFF, 0x85, 0x05,                   // Usage Page (Vendor), Report ID (5)
+        0x09, 0x01, 0x15, 0x81, 0x25, 0x7F, 0x75, 0x0C, 0x95, 0x01,
+        0xB1, 0x02,                                     // Feature (Data, Var, Abs), 12 bits
+        0x19, 0x10, 0x29, 0x1F, 0x15, 0x10, 0x25, 0x1F, 0x75, 0x06, 0x95, 0x02,
+        0xB1, 0x00,                                     // Feature (Data, Array, Abs)
+    ];
+    let desc = ReportDescriptor::parse(&data).unwrap();
+    let usage = Usage::new(0xFF00, 0x01);
+
+    let mut builder = desc.builder(ReportType::Feature, 5).unwrap();
+    builder.set(usage, -2).unwrap();
+    builder.set_active(Usage::new(0xFF00, 0x12)).unwrap();
+    builder.set_active(Usage::new(0xFF00, 0x1F)).unwrap();
+    let err = builder.set_active(Usage::new(0xFF00, 0x10)).unwrap_err();
+    assert!(matches!(err, ReportError::ArrayFull { .. }));
+    let err = builder.set(usage, -128).unwrap_err();
+    assert!(matches!(err, ReportError::ValueOutOfRange { .. }));
+
+    let report = builder.finish();
+    assert_eq!(report, vec![0x05, 0xFE, 0x2F, 0x7D]);
+
+    let values = desc.decode(ReportType::Feature, &report).unwrap();
+    assert_eq!(values.value(usage).unwrap().logical(), -2);
+    assert_eq!(
+        values.active_usages(),
+        &[Usage::new(0xFF00, 0x12), Usage::new(0xFF00, 0x1F)]
+    );
+
+    assert!(desc.builder(ReportType::Output, 5).is_err());
+}
+
+/// The Windows preparsed data store the length of the longest report of each
+/// type, including the report ID byte, the number of capabilities and the
+/// number of link collections. The capabilities computed from the
+/// reconstructed descriptors must match.
+///
+/// The Xbox controller (045E_02FF) is skipped: its driver reports two more
+/// bytes than its descriptor describes.
+#[test]
+fn test_pp_data_capabilities() {
+    let mut tested = 0;
+    for entry in read_dir("./tests/pp_data").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "expected")
+            && !path.ends_with("045E_02FF_0005_0001.expected")
+        {
+            let descriptor = decode_hex(&read_to_string(&path).unwrap());
+            let pp_data = decode_hex(&read_to_string(path.with_extension("pp_data")).unwrap());
+            let desc = ReportDescriptor::parse(&descriptor)
+                .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+
+            let caps = desc.capabilities();
+            assert_eq!(
+                caps.number_link_collection_nodes(),
+                read_u16(&pp_data, 42) as usize,
+                "{}",
+                path.display()
+            );
+            for (index, report_type) in ReportType::values().into_iter().enumerate() {
+                let caps_info = 16 + index * 8;
+                assert_eq!(
+                    caps.report_byte_length(report_type),
+                    read_u16(&pp_data, caps_info + 6) as usize,
+                    "{} {:?}",
+                    path.display(),
+                    report_type
+                );
+                assert_eq!(
+                    caps.number_button_caps(report_type) + caps.number_value_caps(report_type),
+                    (read_u16(&pp_data, caps_info + 4) - read_u16(&pp_data, caps_info)) as usize,
+                    "{} {:?}",
+                    path.display(),
+                    report_type
+                );
+            }
+            tested += 1;
+        }
+    }
+    assert_eq!(tested, 23);
+}
+
+#[test]
+fn test_disassemble() {
+    let data = include_bytes!("../../tests/assets/mouse2.data");
+    let text = disassemble(data).to_string();
+    let lines = text.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), items(data).count());
+    assert_eq!(
+        lines[6],
+        "0x05, 0x09,                   //     Usage Page (Button)                          12"
+    );
+    assert_eq!(
+        lines[20],
+        "0x16, 0x01, 0xF8,             //     Logical Minimum (-2047)                      40"
+    );
+    assert!(lines[32].contains("Usage (AC Pan)"));
+    assert!(lines[36].starts_with("0xC0,                         // End Collection "));
+    assert!(lines
+        .iter()
+        .any(|l| l.contains("Usage Page (Vendor Defined 0xFF00)")));
+
+    let unit = [0x66, 0x11, 0xF0, 0x55, 0x0E];
+    let text = disassemble(&unit).to_string();
+    assert!(text.contains("Unit (System: SI Linear, Length: Centimeter, Time: Seconds^-1)"));
+    assert!(text.contains("Unit Exponent (-2)"));
+
+    let text = disassemble(&[0x05, 0x01, 0x26, 0xFF]).to_string();
+    assert_eq!(
+        text.lines().last().unwrap(),
+        "0x26, 0xFF,                   // unexpected end of descriptor                     2"
+    );
+}
+
+/// The byte columns of the disassembly are the descriptor as C array
+#[test]
+fn test_disassemble_round_trip() {
+    for entry in read_dir("./tests/pp_data").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "expected") {
+            let descriptor = decode_hex(&read_to_string(&path).unwrap());
+            let text = disassemble(&descriptor).to_string();
+            let bytes = text
+                .lines()
+                .map(|line| line.split("//").next().unwrap())
+                .collect::<Vec<_>>()
+                .join("\n");
+            assert_eq!(decode_hex(&bytes), descriptor, "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn test_builder_item_sizes() {
+    let bytes = Builder::new()
+        .logical_minimum(-129)
+        .logical_maximum(255)
+        .physical_maximum(-1)
+        .usage_maximum(0x380)
+        .extended_usage(Usage::new(0x0C, 0xE9))
+        .unit_exponent(-2)
+        .report_count(70000)
+        .push()
+        .pop()
+        .delimited(|b| b.usage(0x30).usage(0x31))
+        .build();
+    assert_eq!(
+        bytes,
+        [
+            0x16, 0x7F, 0xFF, 0x26, 0xFF, 0x00, 0x45, 0xFF, 0x2A, 0x80, 0x03, 0x0B, 0xE9, 0x00,
+            0x0C, 0x00, 0x55, 0x0E, 0x97, 0x70, 0x11, 0x01, 0x00, 0xA4, 0xB4, 0xA9, 0x01, 0x09,
+            0x30, 0x09, 0x31, 0xA9, 0x00,
+        ]
+    );
+}
+
+#[test]
+fn test_hid_descriptor_macro() {
+    let bytes = crate::hid_descriptor! {
+        usage_page(0x01),
+        usage(0x02),
+        collection(Application) {
+            usage(0x01),
+            collection(Physical) {
+                usage_page(0x09),
+                usage_minimum(1),
+                usage_maximum(3),
+                logical_minimum(0),
+                logical_maximum(1),
+                report_size(1),
+                report_count(3),
+                input(VARIABLE),
+                report_count(5),
+                input(CONSTANT | VARIABLE),
+                usage_page(0x01),
+                usage(0x30),
+                usage(0x31),
+                logical_minimum(-127),
+                logical_maximum(127),
+                report_size(8),
+                report_count(2),
+                input(VARIABLE | RELATIVE),
+            },
+        },
+        report_id(2),
+        delimited() {
+            usage(0x30),
+            usage(0x31),
+        },
+        output(),
+        feature(CONSTANT),
+    };
+    assert_eq!(
+        bytes[..48],
+        [
+            0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, 0x09, 0x01, 0xA1, 0x00, 0x05, 0x09, 0x19, 0x01,
+            0x29, 0x03, 0x15, 0x00, 0x25, 0x01, 0x75, 0x01, 0x95, 0x03, 0x81, 0x02, 0x95, 0x05,
+            0x81, 0x03, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x15, 0x81, 0x25, 0x7F, 0x75, 0x08,
+            0x95, 0x02, 0x81, 0x06, 0xC0, 0xC0,
+        ]
+    );
+    assert_eq!(
+        bytes[48..],
+        [0x85, 0x02, 0xA9, 0x01, 0x09, 0x30, 0x09, 0x31, 0xA9, 0x00, 0x91, 0x00, 0xB1, 0x01]
+    );
+
+    let descriptor = ReportDescriptor::parse(&bytes).unwrap();
+    let flags = descriptor.report(ReportType::Feature, 2).unwrap().fields()[0].flags();
+    assert_eq!(flags, MainItemFlags::CONSTANT);
+}
+
+/// Encoding the items of the reconstructed descriptors again gives the same
+/// bytes, since the reconstruction also uses the smallest item sizes
+#[test]
+fn test_builder_round_trip() {
+    for entry in read_dir("./tests/pp_data").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "expected") {
+            let descriptor = decode_hex(&read_to_string(&path).unwrap());
+            let bytes = items(&descriptor)
+                .map(|item| item.unwrap())
+                .fold(Builder::new(), |b, item| {
+                    let tag = item.tag().unwrap();
+                    match tag {
+                        Tag::LogicalMinimum
+                        | Tag::LogicalMaximum
+                        | Tag::PhysicalMinimum
+                        | Tag::PhysicalMaximum => b.item(tag, item.signed() as u32),
+                        _ => b.item(tag, item.unsigned()),
+                    }
+                })
+                .build();
+            assert_eq!(bytes, descriptor, "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn test_validate_errors() {
+    let bytes = Builder::new()
+        .usage(0x01)
+        .usage_page(0x01)
+        .collection(CollectionType::Application, |b| {
+            b.report_id(0)
+                .logical_minimum(5)
+                .logical_maximum(1)
+                .usage_minimum(5)
+                .usage_maximum(1)
+                .input(MainItemFlags::VARIABLE)
+                .report_id(1)
+                .report_size(8)
+                .report_count(1)
+                .logical_maximum(10)
+                .usage_minimum(1)
+                .input(MainItemFlags::DATA)
+                .pop()
+        })
+        .item(Tag::EndCollection, 0)
+        .build();
+    let diagnostics = validate(&bytes)
+        .into_iter()
+        .map(|d| (d.offset(), d.lint()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [
+            (0, Lint::UsageWithoutPage),
+            (6, Lint::InvalidReportId(0)),
+            (10, Lint::LogicalRangeInverted(5, 1)),
+            (14, Lint::UsageRangeInverted),
+            (16, Lint::MissingReportSize),
+            (16, Lint::MissingReportCount),
+            (16, Lint::MixedReportIds),
+            (26, Lint::UnmatchedUsageRange),
+            (30, Lint::PopWithoutPush),
+            (32, Lint::UnbalancedEndCollection),
+        ]
+    );
+    assert!(validate(&bytes[..31])
+        .iter()
+        .any(|d| d.lint() == Lint::UnclosedCollection && d.offset() == 4));
+
+    let diagnostics = validate(&[0x05, 0x01, 0x26, 0xFF]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].lint(), Lint::UnexpectedEnd);
+    assert_eq!(diagnostics[0].offset(), 2);
+}
+
+#[test]
+fn test_validate_warnings() {
+    let bytes = Builder::new()
+        .usage_page(0x01)
+        .report_size(8)
+        .report_count(1)
+        .input(MainItemFlags::CONSTANT)
+        .collection(CollectionType::Physical, |b| {
+            b.report_size(40)
+                .input(MainItemFlags::VARIABLE)
+                .item(Tag::UnitExponent, 0xFE)
+                .logical_maximum(-1)
+                .report_size(1)
+                .input(MainItemFlags::VARIABLE)
+                .push()
+        })
+        .collection(CollectionType::Application, |b| {
+            b.input(MainItemFlags::VARIABLE)
+        })
+        .build();
+    let diagnostics = validate(&bytes);
+    assert!(diagnostics
+        .iter()
+        .all(|d| d.severity() == Severity::Warning));
+    let diagnostics = diagnostics
+        .into_iter()
+        .map(|d| (d.offset(), d.lint()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diagnostics,
+        [
+            (6, Lint::MainItemOutsideCollection),
+            (6, Lint::UnalignedReport(ReportType::Input, 0)),
+            (8, Lint::TopLevelNotApplication),
+            (12, Lint::WideValue(40)),
+            (12, Lint::ReportInMultipleCollections(ReportType::Input, 0)),
+            (14, Lint::UnitExponentNotNibble(0xFE)),
+            (16, Lint::UnsignedMaximum(255)),
+            (22, Lint::PushWithoutPop),
+            (26, Lint::ReportInMultipleCollections(ReportType::Input, 0)),
+        ]
+    );
+}
+
+/// The reconstructed descriptors are valid. The Xbox controller encodes its
+/// maximum values of 255 in a single byte.
+#[test]
+fn test_validate_reconstructed() {
+    for entry in read_dir("./tests/pp_data").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "expected") {
+            let descriptor = decode_hex(&read_to_string(&path).unwrap());
+            let lints = validate(&descriptor)
+                .into_iter()
+                .map(|d| d.lint())
+                .collect::<Vec<_>>();
+            if path.ends_with("045E_02FF_0005_0001.expected") {
+                assert_eq!(lints, [Lint::UnsignedMaximum(255); 4]);
+            } else {
+                assert_eq!(lints, [], "{}", path.display());
+            }
+        }
+    }
+    assert_eq!(
+        validate(include_bytes!("../../tests/assets/mouse2.data")),
+        []
+    );
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    hex.lines()
+        .flat_map(|line| {
+            line.split(',')
+                .map(|hex| hex.trim())
+                .filter(|hex| !hex.is_empty())
+                .map(|hex| hex.strip_prefix("0x").unwrap())
+                .map(|hex| u8::from_str_radix(hex, 16).unwrap())
+        })
+        .collect()
+}
@@ -0,0 +1,545 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use super::{items, CollectionType, Item, MainItemFlags, ReportType, Tag};
+use crate::MAX_REPORT_DESCRIPTOR_SIZE;
+
+/// Largest report the Linux kernel accepts, `HID_MAX_BUFFER_SIZE` minus the
+/// report ID
+const MAX_REPORT_BYTES: u64 = 16384 - 1;
+
+/// Check a report descriptor for violations of the HID specification and
+/// for constructs that operating systems handle differently
+///
+/// Unlike [`ReportDescriptor::parse`](super::ReportDescriptor::parse), the
+/// validation does not stop at the first problem. Only an item that ends
+/// early stops it. The diagnostics are sorted by their offset.
+///
+/// ```
+/// use hidapi::descriptor::{validate, Lint, Severity};
+///
+/// // Collection (Application), Report ID (0), End Collection, End Collection
+/// let diagnostics = validate(&[0xA1, 0x01, 0x85, 0x00, 0xC0, 0xC0]);
+/// assert_eq!(diagnostics.len(), 2);
+/// assert_eq!(diagnostics[0].lint(), Lint::InvalidReportId(0));
+/// assert_eq!(diagnostics[1].offset(), 5);
+/// assert_eq!(diagnostics[1].severity(), Severity::Error);
+/// assert_eq!(
+///     diagnostics[1].to_string(),
+///     "error at offset 5: End Collection without Collection"
+/// );
+/// ```
+pub fn validate(bytes: &[u8]) -> Vec<Diagnostic> {
+    let mut validator = Validator::default();
+    if bytes.len() > MAX_REPORT_DESCRIPTOR_SIZE {
+        validator.report(0, Lint::DescriptorTooLarge(bytes.len()));
+    }
+    for item in items(bytes) {
+        match item {
+            Ok(item) => validator.item(&item),
+            Err(e) => {
+                validator.report(e.offset(), Lint::UnexpectedEnd);
+                break;
+            }
+        }
+    }
+    validator.finish()
+}
+
+/// How serious a [`Lint`] is
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// The descriptor violates the specification, and parsers reject it or
+    /// disagree on its meaning
+    Error,
+    /// The descriptor is valid, but some operating systems or parsers
+    /// handle it in surprising ways
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A problem found by [`validate`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Lint {
+    /// The descriptor ends in the middle of an item
+    UnexpectedEnd,
+    /// The descriptor is longer than `MAX_REPORT_DESCRIPTOR_SIZE`, which is
+    /// also the limit of the Linux kernel
+    DescriptorTooLarge(usize),
+    /// An End Collection item without a matching Collection item
+    UnbalancedEndCollection,
+    /// A Collection item without a matching End Collection item
+    UnclosedCollection,
+    /// A Pop item without a matching Push item
+    PopWithoutPush,
+    /// A Push item without a matching Pop item
+    PushWithoutPop,
+    /// A Report ID item outside of `1..=255`
+    InvalidReportId(u32),
+    /// A main item without report ID in a descriptor that uses report IDs
+    MixedReportIds,
+    /// A main item before the first Report Size item
+    MissingReportSize,
+    /// A main item before the first Report Count item
+    MissingReportCount,
+    /// The report grows beyond what the Linux kernel accepts
+    ReportTooLarge(ReportType, u8),
+    /// Logical Minimum is greater than Logical Maximum
+    LogicalRangeInverted(i32, i32),
+    /// Physical Minimum is greater than Physical Maximum
+    PhysicalRangeInverted(i32, i32),
+    /// A usage without usage page in its data, before the first Usage Page item
+    UsageWithoutPage,
+    /// A Usage Minimum without Usage Maximum, or the other way around
+    UnmatchedUsageRange,
+    /// Usage Minimum is greater than Usage Maximum
+    UsageRangeInverted,
+    /// Nested, unbalanced or unknown Delimiter items
+    InvalidDelimiter,
+    /// An item with a reserved tag, which parsers skip
+    ReservedItem,
+    /// A long item, which parsers skip
+    LongItem,
+    /// A main item outside of any collection, which Windows ignores
+    MainItemOutsideCollection,
+    /// A top level collection that is not an Application collection.
+    /// Windows creates a device for each top level collection, and expects
+    /// them to be Application collections.
+    TopLevelNotApplication,
+    /// The report has fields in multiple top level collections. Windows
+    /// gives each top level collection its own device, so no device receives
+    /// the whole report.
+    ReportInMultipleCollections(ReportType, u8),
+    /// A Logical or Physical Maximum that is only greater than the minimum
+    /// when read as unsigned value, like 255 encoded as the single byte 0xFF.
+    /// Linux reads such values as unsigned, Windows as signed.
+    UnsignedMaximum(u32),
+    /// A variable field with more than 32 bits per value, which Windows only
+    /// returns with `HidP_GetUsageValueArray`
+    WideValue(u32),
+    /// A Unit Exponent that is not a 4 bit nibble, which Windows reads as a
+    /// different exponent than Linux
+    UnitExponentNotNibble(u32),
+    /// A report that does not end on a byte boundary, which operating
+    /// systems pad with zero bits
+    UnalignedReport(ReportType, u8),
+}
+
+impl Lint {
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::UnexpectedEnd
+            | Self::DescriptorTooLarge(_)
+            | Self::UnbalancedEndCollection
+            | Self::UnclosedCollection
+            | Self::PopWithoutPush
+            | Self::InvalidReportId(_)
+            | Self::MixedReportIds
+            | Self::MissingReportSize
+            | Self::MissingReportCount
+            | Self::ReportTooLarge(..)
+            | Self::LogicalRangeInverted(..)
+            | Self::PhysicalRangeInverted(..)
+            | Self::UsageWithoutPage
+            | Self::UnmatchedUsageRange
+            | Self::UsageRangeInverted
+            | Self::InvalidDelimiter => Severity::Error,
+            Self::PushWithoutPop
+            | Self::ReservedItem
+            | Self::LongItem
+            | Self::MainItemOutsideCollection
+            | Self::TopLevelNotApplication
+            | Self::ReportInMultipleCollections(..)
+            | Self::UnsignedMaximum(_)
+            | Self::WideValue(_)
+            | Self::UnitExponentNotNibble(_)
+            | Self::UnalignedReport(..) => Severity::Warning,
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of descriptor"),
+            Self::DescriptorTooLarge(length) => write!(
+                f,
+                "descriptor of {length} bytes is longer than {MAX_REPORT_DESCRIPTOR_SIZE} bytes"
+            ),
+            Self::UnbalancedEndCollection => f.write_str("End Collection without Collection"),
+            Self::UnclosedCollection => f.write_str("Collection without End Collection"),
+            Self::PopWithoutPush => f.write_str("Pop without Push"),
+            Self::PushWithoutPop => f.write_str("Push without Pop"),
+            Self::InvalidReportId(id) => write!(f, "invalid report ID {id}"),
+            Self::MixedReportIds => f.write_str("main item without report ID"),
+            Self::MissingReportSize => f.write_str("main item without Report Size"),
+            Self::MissingReportCount => f.write_str("main item without Report Count"),
+            Self::ReportTooLarge(report_type, id) => {
+                write!(f, "{report_type:?} report {id} is too large")
+            }
+            Self::LogicalRangeInverted(minimum, maximum) => write!(
+                f,
+                "Logical Minimum {minimum} is greater than Logical Maximum {maximum}"
+            ),
+            Self::PhysicalRangeInverted(minimum, maximum) => write!(
+                f,
+                "Physical Minimum {minimum} is greater than Physical Maximum {maximum}"
+            ),
+            Self::UsageWithoutPage => f.write_str("usage without Usage Page"),
+            Self::UnmatchedUsageRange => {
+                f.write_str("Usage Minimum and Usage Maximum must be used together")
+            }
+            Self::UsageRangeInverted => f.write_str("Usage Minimum is greater than Usage Maximum"),
+            Self::InvalidDelimiter => f.write_str("invalid delimiter"),
+            Self::ReservedItem => f.write_str("item with reserved tag"),
+            Self::LongItem => f.write_str("long item"),
+            Self::MainItemOutsideCollection => {
+                f.write_str("main item outside of a collection is ignored by Windows")
+            }
+            Self::TopLevelNotApplication => {
+                f.write_str("top level collection is not an Application collection")
+            }
+            Self::ReportInMultipleCollections(report_type, id) => write!(
+                f,
+                "{report_type:?} report {id} spans multiple top level collections"
+            ),
+            Self::UnsignedMaximum(maximum) => write!(
+                f,
+                "maximum {maximum} is encoded as negative value, which Windows uses"
+            ),
+            Self::WideValue(bits) => write!(
+                f,
+                "variable field of {bits} bits needs HidP_GetUsageValueArray on Windows"
+            ),
+            Self::UnitExponentNotNibble(value) => write!(
+                f,
+                "Unit Exponent 0x{value:X} is not a nibble, Windows and Linux disagree on it"
+            ),
+            Self::UnalignedReport(report_type, id) => write!(
+                f,
+                "{report_type:?} report {id} does not end on a byte boundary"
+            ),
+        }
+    }
+}
+
+/// A problem of a descriptor and the offset of the item it was found at
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    offset: usize,
+    lint: Lint,
+}
+
+impl Diagnostic {
+    /// Byte offset of the offending item in the descriptor
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn lint(&self) -> Lint {
+        self.lint
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.lint.severity()
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {}: {}",
+            self.severity(),
+            self.offset,
+            self.lint
+        )
+    }
+}
+
+/// Compare a minimum with the signed and unsigned reading of a maximum,
+/// like the parser does
+fn check_range(
+    minimum: i32,
+    (signed, unsigned): (i32, u32),
+    inverted: fn(i32, i32) -> Lint,
+) -> Option<Lint> {
+    if minimum <= signed {
+        None
+    } else if minimum >= 0 && i32::try_from(unsigned).is_ok_and(|u| u >= minimum) {
+        Some(Lint::UnsignedMaximum(unsigned))
+    } else {
+        Some(inverted(minimum, signed))
+    }
+}
+
+/// A global value and the offset of the item that set it
+type Global<T> = Option<(usize, T)>;
+
+#[derive(Debug, Clone, Default)]
+struct GlobalState {
+    usage_page: Option<u16>,
+    logical_minimum: Global<i32>,
+    logical_maximum: Global<(i32, u32)>,
+    physical_minimum: Global<i32>,
+    physical_maximum: Global<(i32, u32)>,
+    report_size: Option<u32>,
+    report_count: Option<u32>,
+    report_id: u8,
+}
+
+#[derive(Debug, Default)]
+struct LocalState {
+    usage_minimum: Global<RawUsage>,
+    usage_maximum: Global<RawUsage>,
+    delimiter_open: bool,
+}
+
+/// The data of a usage item, and whether it includes the usage page
+type RawUsage = (u32, bool);
+
+#[derive(Debug, Default)]
+struct ReportState {
+    bits: u64,
+    first_offset: usize,
+    top_level_collection: usize,
+}
+
+#[derive(Debug, Default)]
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+    global: GlobalState,
+    global_stack: Vec<(usize, GlobalState)>,
+    local: LocalState,
+    collections: Vec<usize>,
+    top_level_collections: usize,
+    reports: BTreeMap<(ReportType, u8), ReportState>,
+    first_report_id: Option<usize>,
+    first_unnumbered: Option<usize>,
+}
+
+impl Validator {
+    /// Add a diagnostic, unless the same problem at the same offset was
+    /// already found for an earlier main item
+    fn report(&mut self, offset: usize, lint: Lint) {
+        let diagnostic = Diagnostic { offset, lint };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn item(&mut self, item: &Item) {
+        let offset = item.offset();
+        let tag = match item.tag() {
+            Some(tag) => tag,
+            None if item.is_long() => return self.report(offset, Lint::LongItem),
+            None => return self.report(offset, Lint::ReservedItem),
+        };
+
+        match tag {
+            Tag::Input | Tag::Output | Tag::Feature => {
+                let report_type = match tag {
+                    Tag::Input => ReportType::Input,
+                    Tag::Output => ReportType::Output,
+                    _ => ReportType::Feature,
+                };
+                self.main_item(offset, report_type, MainItemFlags(item.unsigned()));
+                self.end_local_items();
+            }
+            Tag::Collection => {
+                if self.collections.is_empty() {
+                    self.top_level_collections += 1;
+                    if CollectionType::from(item.unsigned() as u8) != CollectionType::Application {
+                        self.report(offset, Lint::TopLevelNotApplication);
+                    }
+                }
+                self.collections.push(offset);
+                self.end_local_items();
+            }
+            Tag::EndCollection => {
+                if self.collections.pop().is_none() {
+                    self.report(offset, Lint::UnbalancedEndCollection);
+                }
+                self.end_local_items();
+            }
+            Tag::UsagePage => self.global.usage_page = Some(item.unsigned() as u16),
+            Tag::LogicalMinimum => self.global.logical_minimum = Some((offset, item.signed())),
+            Tag::LogicalMaximum => {
+                self.global.logical_maximum = Some((offset, (item.signed(), item.unsigned())))
+            }
+            Tag::PhysicalMinimum => self.global.physical_minimum = Some((offset, item.signed())),
+            Tag::PhysicalMaximum => {
+                self.global.physical_maximum = Some((offset, (item.signed(), item.unsigned())))
+            }
+            Tag::UnitExponent => {
+                if item.unsigned() > 0x0F {
+                    self.report(offset, Lint::UnitExponentNotNibble(item.unsigned()));
+                }
+            }
+            Tag::ReportSize => self.global.report_size = Some(item.unsigned()),
+            Tag::ReportCount => self.global.report_count = Some(item.unsigned()),
+            Tag::ReportId => match item.unsigned() {
+                id @ 1..=255 => {
+                    self.global.report_id = id as u8;
+                    self.first_report_id.get_or_insert(offset);
+                }
+                id => self.report(offset, Lint::InvalidReportId(id)),
+            },
+            Tag::Push => self.global_stack.push((offset, self.global.clone())),
+            Tag::Pop => match self.global_stack.pop() {
+                Some((_, global)) => self.global = global,
+                None => self.report(offset, Lint::PopWithoutPush),
+            },
+            Tag::Usage | Tag::UsageMinimum | Tag::UsageMaximum => {
+                let extended = item.data().len() == 4;
+                if !extended && self.global.usage_page.is_none() {
+                    self.report(offset, Lint::UsageWithoutPage);
+                }
+                let usage = Some((offset, (item.unsigned(), extended)));
+                match tag {
+                    Tag::UsageMinimum => self.local.usage_minimum = usage,
+                    Tag::UsageMaximum => self.local.usage_maximum = usage,
+                    _ => {}
+                }
+                self.check_usage_range();
+            }
+            Tag::Delimiter => match (item.unsigned(), self.local.delimiter_open) {
+                (1, false) => self.local.delimiter_open = true,
+                (0, true) => self.local.delimiter_open = false,
+                _ => self.report(offset, Lint::InvalidDelimiter),
+            },
+            Tag::Unit
+            | Tag::DesignatorIndex
+            | Tag::DesignatorMinimum
+            | Tag::DesignatorMaximum
+            | Tag::StringIndex
+            | Tag::StringMinimum
+            | Tag::StringMaximum => {}
+        }
+    }
+
+    fn main_item(&mut self, offset: usize, report_type: ReportType, flags: MainItemFlags) {
+        if self.collections.is_empty() {
+            self.report(offset, Lint::MainItemOutsideCollection);
+        }
+        if self.global.report_id == 0 {
+            self.first_unnumbered.get_or_insert(offset);
+        }
+        self.check_ranges();
+
+        let report_size = self.global.report_size.unwrap_or_else(|| {
+            self.report(offset, Lint::MissingReportSize);
+            0
+        });
+        let report_count = self.global.report_count.unwrap_or_else(|| {
+            self.report(offset, Lint::MissingReportCount);
+            0
+        });
+        if flags.is_variable() && !flags.is_constant() && report_size > 32 {
+            self.report(offset, Lint::WideValue(report_size));
+        }
+
+        let report_id = self.global.report_id;
+        let top_level_collection = self.top_level_collections;
+        let report = self
+            .reports
+            .entry((report_type, report_id))
+            .or_insert(ReportState {
+                bits: 0,
+                first_offset: offset,
+                top_level_collection,
+            });
+        let fits = report.bits <= MAX_REPORT_BYTES * 8;
+        report.bits += report_size as u64 * report_count as u64;
+        let too_large = fits && report.bits > MAX_REPORT_BYTES * 8;
+        let split = report.top_level_collection != top_level_collection;
+        report.top_level_collection = top_level_collection;
+
+        if too_large {
+            self.report(offset, Lint::ReportTooLarge(report_type, report_id));
+        }
+        if split {
+            self.report(
+                offset,
+                Lint::ReportInMultipleCollections(report_type, report_id),
+            );
+        }
+    }
+
+    /// Check the logical and physical extents that apply to a main item
+    fn check_ranges(&mut self) {
+        let GlobalState {
+            logical_minimum,
+            logical_maximum,
+            physical_minimum,
+            physical_maximum,
+            ..
+        } = self.global;
+        let minimum = logical_minimum.map_or(0, |(_, minimum)| minimum);
+        if let Some((offset, maximum)) = logical_maximum {
+            if let Some(lint) = check_range(minimum, maximum, Lint::LogicalRangeInverted) {
+                self.report(offset, lint);
+            }
+        }
+        let minimum = physical_minimum.map_or(0, |(_, minimum)| minimum);
+        if let Some((offset, maximum)) = physical_maximum {
+            if let Some(lint) = check_range(minimum, maximum, Lint::PhysicalRangeInverted) {
+                self.report(offset, lint);
+            }
+        }
+    }
+
+    fn check_usage_range(&mut self) {
+        if let (Some((_, minimum)), Some((offset, maximum))) =
+            (self.local.usage_minimum, self.local.usage_maximum)
+        {
+            // An extended usage and a usage ID are on different pages
+            if minimum.1 == maximum.1 && minimum.0 > maximum.0 {
+                self.report(offset, Lint::UsageRangeInverted);
+            }
+            self.local.usage_minimum = None;
+            self.local.usage_maximum = None;
+        }
+    }
+
+    /// Local items only apply to the next main item
+    fn end_local_items(&mut self) {
+        let unmatched = self.local.usage_minimum.or(self.local.usage_maximum);
+        if let Some((offset, _)) = unmatched {
+            self.report(offset, Lint::UnmatchedUsageRange);
+        }
+        self.local = LocalState::default();
+    }
+
+    fn finish(mut self) -> Vec<Diagnostic> {
+        if let Some(offset) = self.collections.last() {
+            self.report(*offset, Lint::UnclosedCollection);
+        }
+        if let Some((offset, _)) = self.global_stack.last() {
+            self.report(*offset, Lint::PushWithoutPop);
+        }
+        if let (Some(_), Some(unnumbered)) = (self.first_report_id, self.first_unnumbered) {
+            self.report(unnumbered, Lint::MixedReportIds);
+        }
+        let unaligned = self
+            .reports
+            .iter()
+            .filter(|(_, report)| report.bits % 8 != 0)
+            .map(|(&(report_type, id), report)| (report.first_offset, report_type, id))
+            .collect::<Vec<_>>();
+        for (offset, report_type, id) in unaligned {
+            self.report(offset, Lint::UnalignedReport(report_type, id));
+        }
+
+        self.diagnostics.sort_by_key(|d| d.offset);
+        self.diagnostics
+    }
+}
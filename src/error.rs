@@ -8,6 +8,8 @@ use libc::wchar_t;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result};
 
+use crate::descriptor::{DescriptorError, ReportError};
+use crate::preparsed::PreparsedDataError;
 use crate::DeviceInfo;
 
 #[derive(Debug)]
@@ -75,7 +77,31 @@ impl From<std::io::Error> for HidError {
     }
 }
 
-#[cfg(all(feature = "linux-native", target_os = "linux"))]
+impl From<DescriptorError> for HidError {
+    fn from(e: DescriptorError) -> Self {
+        Self::HidApiError {
+            message: format!("invalid report descriptor: {e}"),
+        }
+    }
+}
+
+impl From<PreparsedDataError> for HidError {
+    fn from(e: PreparsedDataError) -> Self {
+        Self::HidApiError {
+            message: format!("invalid preparsed data: {e}"),
+        }
+    }
+}
+
+impl From<ReportError> for HidError {
+    fn from(e: ReportError) -> Self {
+        Self::HidApiError {
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
 impl From<nix::errno::Errno> for HidError {
     fn from(e: nix::errno::Errno) -> Self {
         Self::IoError { error: e.into() }
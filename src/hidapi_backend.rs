@@ -7,7 +7,7 @@ use std::{
 
 use libc::{c_int, size_t, wchar_t};
 
-use crate::{ffi, DeviceInfo, HidDeviceBackendBase, HidError, HidResult, WcharString};
+use crate::{ffi, DeviceBackend, DeviceInfo, HidError, HidResult, WcharString};
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -141,6 +141,7 @@ pub unsafe fn conv_hid_device_info(src: *mut ffi::HidDeviceInfo) -> HidResult<De
         usage: (*src).usage,
         interface_number: (*src).interface_number,
         bus_type: (*src).bus_type,
+        physical_path: None,
     })
 }
 
@@ -186,7 +187,7 @@ impl HidDevice {
     }
 }
 
-impl HidDeviceBackendBase for HidDevice {
+impl DeviceBackend for HidDevice {
     fn check_error(&self) -> HidResult<HidError> {
         Ok(HidError::HidApiError {
             message: unsafe {
@@ -251,6 +252,15 @@ impl HidDeviceBackendBase for HidDevice {
         self.check_size(res)
     }
 
+    // hid_send_output_report is only available since hidapi 0.15, so
+    // send_output_report keeps the default to link with older libraries
+    fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let res = unsafe {
+            ffi::hid_get_input_report(self._hid_device, buf.as_mut_ptr(), buf.len() as size_t)
+        };
+        self.check_size(res)
+    }
+
     fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
         let res = unsafe {
             ffi::hid_set_nonblocking(self._hid_device, if blocking { 0i32 } else { 1i32 })
@@ -337,4 +347,19 @@ impl HidDeviceBackendBase for HidDevice {
         };
         self.check_size(res)
     }
+
+    #[cfg(target_os = "windows")]
+    fn get_container_id(&self) -> HidResult<windows_sys::core::GUID> {
+        HidDevice::get_container_id(self)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn get_location_id(&self) -> HidResult<u32> {
+        HidDevice::get_location_id(self)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn is_open_exclusive(&self) -> HidResult<bool> {
+        HidDevice::is_open_exclusive(self)
+    }
 }
@@ -1,10 +1,10 @@
 //! The extra beahviour for macOS
 
 use super::HidDevice;
-use crate::{ffi, HidDeviceBackendBase, HidDeviceBackendMacos, HidResult};
+use crate::{ffi, DeviceBackend, HidResult};
 
-impl HidDeviceBackendMacos for HidDevice {
-    fn get_location_id(&self) -> HidResult<u32> {
+impl HidDevice {
+    pub(super) fn get_location_id(&self) -> HidResult<u32> {
         let mut location_id: u32 = 0;
 
         let res = unsafe {
@@ -21,7 +21,7 @@ impl HidDeviceBackendMacos for HidDevice {
         }
     }
 
-    fn is_open_exclusive(&self) -> HidResult<bool> {
+    pub(super) fn is_open_exclusive(&self) -> HidResult<bool> {
         let res = unsafe { ffi::macos::hid_darwin_is_device_open_exclusive(self._hid_device) };
 
         if res == -1 {
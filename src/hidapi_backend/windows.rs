@@ -4,10 +4,10 @@ use std::ptr::addr_of_mut;
 use windows_sys::core::GUID;
 
 use super::HidDevice;
-use crate::{ffi, HidDeviceBackendBase, HidDeviceBackendWindows, HidResult};
+use crate::{ffi, DeviceBackend, HidResult};
 
-impl HidDeviceBackendWindows for HidDevice {
-    fn get_container_id(&self) -> HidResult<GUID> {
+impl HidDevice {
+    pub(super) fn get_container_id(&self) -> HidResult<GUID> {
         let mut container_id: GUID = unsafe { std::mem::zeroed() };
 
         let res = unsafe {
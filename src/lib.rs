@@ -37,6 +37,25 @@
 //!
 //! For more usage examples, please take a look at the `examples/` directory.
 //!
+//! # Report descriptors
+//!
+//! The [`descriptor`] module parses the report descriptor returned by
+//! [`HidDevice::get_report_descriptor`] into collections, reports and fields.
+//! It is pure Rust and available with every backend.
+//! [`HidDevice::report_descriptor`] fetches and parses the descriptor in one
+//! step, and [`HidDevice::capabilities`] summarizes it like `HidP_GetCaps`
+//! does on Windows.
+//!
+//! The [`preparsed`] module reconstructs report descriptors from the
+//! preparsed data that Windows provides instead. The `windows-native` backend uses it
+//! for [`HidDevice::get_report_descriptor`], and it can be used on any
+//! platform to analyse preparsed data captured on Windows. It also compiles
+//! report descriptors into preparsed data.
+//!
+//! The [`usage`] module names the usage pages and usages of the HID Usage
+//! Tables. The [`codegen`] module generates report types from a descriptor
+//! in build scripts.
+//!
 //! # Feature flags
 //!
 //! - `linux-static-libusb`: uses statically linked `libusb` backend on Linux
@@ -44,16 +63,39 @@
 //! - `linux-shared-libusb`: uses dynamically linked `libusb` backend on Linux
 //! - `linux-shared-hidraw`: uses dynamically linked `hidraw` backend on Linux
 //! - `linux-native`: talks to hidraw directly without using the `hidapi` C library
+//! - `linux-native-sysfs`: the `linux-native` backend without libudev, finds devices by reading sysfs
 //! - `illumos-static-libusb`: uses statically linked `libusb` backend on Illumos (default)
 //! - `illumos-shared-libusb`: uses statically linked `hidraw` backend on Illumos
 //! - `macos-shared-device`: enables shared access to HID devices on MacOS
 //! - `windows-native`: talks to hid.dll directly without using the `hidapi` C library
+//! - `tokio`: adds `AsyncHidDevice` for devices with a file descriptor, like the ones of `linux-native`
+//! - `futures`: adds the runtime agnostic `stream` module
+//! - `mock`: adds the `mock` module with virtual devices for tests
+//! - `uhid`: adds the `uhid` module with kernel HID devices for tests on Linux
+//! - `record`: adds the `record` module to record the traffic of a device and replay it
+//! - `derive`: adds the `report` module with `#[derive(HidReport)]` for typed reports
 //!
 //! ## Linux backends
 //!
 //! On linux the libusb backends do not support [`DeviceInfo::usage()`] and [`DeviceInfo::usage_page()`].
 //! The hidraw backend has support for them, but it might be buggy in older kernel versions.
 //!
+//! The `linux-native` backend can watch for devices being connected and
+//! disconnected with `HidApi::monitor()`.
+//!
+//! `linux-native` finds devices with libudev, and reads sysfs directly where
+//! udev is not available, e.g. in containers. The `backend::LinuxSysfs`
+//! backend always reads sysfs, also from another root directory. It is the
+//! only backend of `linux-native-sysfs`, which does not link libudev, e.g.
+//! for static musl builds, and cannot monitor devices. A device
+//! that is already open, e.g. a hidraw node passed into a sandbox, gets its
+//! `get_device_info()` from hidraw ioctls. Only the USB strings, release
+//! number and interface number still come from sysfs.
+//!
+//! `linux-native` can be enabled together with one of the other Linux
+//! backends. `HidApi::new()` then uses `linux-native`, and
+//! [`HidApi::with_backend()`] selects one of the [`backend`]s at runtime.
+//!
 //! ## MacOS Shared device access
 //!
 //! Since `hidapi` 0.12 it is possible to open MacOS devices with shared access, so that multiple
@@ -61,8 +103,35 @@
 //! an opt-in that can be enabled with the `macos-shared-device` feature flag.
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+macro_rules! ensure {
+    ($cond:expr, $result:expr) => {
+        if !($cond) {
+            return $result;
+        }
+    };
+}
+
+pub mod backend;
+pub mod codegen;
+pub mod descriptor;
 mod error;
 mod ffi;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod preparsed;
+#[cfg(feature = "record")]
+pub mod record;
+#[cfg(feature = "derive")]
+pub mod report;
+#[cfg(feature = "futures")]
+pub mod stream;
+#[cfg(all(feature = "uhid", target_os = "linux"))]
+pub mod uhid;
+pub mod usage;
+
+// The code generated by `#[derive(HidReport)]` refers to `::hidapi`
+#[cfg(all(test, feature = "derive"))]
+extern crate self as hidapi;
 
 use cfg_if::cfg_if;
 use libc::wchar_t;
@@ -70,56 +139,56 @@ use std::ffi::CStr;
 use std::ffi::CString;
 use std::fmt;
 use std::fmt::Debug;
+#[cfg(unix)]
+use std::os::fd::BorrowedFd;
 use std::sync::Mutex;
 
+pub use backend::{Backend, DeviceBackend};
 pub use error::HidError;
 
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+mod linux_native;
+#[cfg(all(feature = "linux-native", target_os = "linux"))]
+pub use linux_native::DeviceMonitor;
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux", feature = "tokio"))]
+mod async_device;
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux", feature = "tokio"))]
+pub use async_device::AsyncHidDevice;
+#[cfg(hidapi)]
+mod hidapi_backend;
+#[cfg(all(feature = "windows-native", target_os = "windows"))]
+mod windows_native;
+
+// The backend used by `HidApi::new()`
 cfg_if! {
     if #[cfg(all(feature = "linux-native", target_os = "linux"))] {
-        //#[cfg_attr(docsrs, doc(cfg(all(feature = "linux-native", target_os = "linux"))))]
-        mod linux_native;
-        use linux_native::HidApiBackend;
+        fn default_backend() -> Box<dyn Backend> {
+            Box::new(backend::LinuxNative)
+        }
+    } else if #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))] {
+        fn default_backend() -> Box<dyn Backend> {
+            Box::new(backend::LinuxSysfs::new())
+        }
     } else if #[cfg(all(feature = "windows-native", target_os = "windows"))] {
-        //#[cfg_attr(docsrs, doc(cfg(all(feature = "windows-native", target_os = "windows"))))]
-        mod windows_native;
-        use windows_native::HidApiBackend;
+        fn default_backend() -> Box<dyn Backend> {
+            Box::new(backend::WindowsNative)
+        }
     } else if #[cfg(hidapi)] {
-        mod hidapi;
-        use hidapi::HidApiBackend;
+        fn default_backend() -> Box<dyn Backend> {
+            Box::new(backend::Hidapi::initialized())
+        }
     } else {
         compile_error!("No backend selected");
     }
 }
 
-// Automatically implement the top trait
 cfg_if! {
     if #[cfg(target_os = "windows")] {
         #[cfg_attr(docsrs, doc(cfg(target_os = "windows")))]
         mod windows;
-        use windows::GUID;
-        /// A trait with the extra methods that are available on Windows
-        trait HidDeviceBackendWindows {
-            /// Get the container ID for a HID device
-            fn get_container_id(&self) -> HidResult<GUID>;
-        }
-        trait HidDeviceBackend: HidDeviceBackendBase + HidDeviceBackendWindows + Send {}
-        impl<T> HidDeviceBackend for T where T: HidDeviceBackendBase + HidDeviceBackendWindows + Send {}
     } else if #[cfg(target_os = "macos")] {
         #[cfg_attr(docsrs, doc(cfg(target_os = "macos")))]
         mod macos;
-        /// A trait with the extra methods that are available on macOS
-        trait HidDeviceBackendMacos {
-            /// Get the location ID for a [`HidDevice`] device.
-            fn get_location_id(&self) -> HidResult<u32>;
-
-            /// Check if the device was opened in exclusive mode.
-            fn is_open_exclusive(&self) -> HidResult<bool>;
-        }
-        trait HidDeviceBackend: HidDeviceBackendBase + HidDeviceBackendMacos + Send {}
-        impl<T> HidDeviceBackend for T where T: HidDeviceBackendBase + HidDeviceBackendMacos + Send {}
-    } else {
-        trait HidDeviceBackend: HidDeviceBackendBase + Send {}
-        impl<T> HidDeviceBackend for T where T: HidDeviceBackendBase + Send {}
     }
 }
 
@@ -180,6 +249,7 @@ fn lazy_init(do_enumerate: bool) -> HidResult<()> {
 ///
 /// Each instance has its own device list cache.
 pub struct HidApi {
+    backend: Box<dyn Backend>,
     device_list: Vec<DeviceInfo>,
 }
 
@@ -196,6 +266,21 @@ impl HidApi {
         lazy_init(true)?;
 
         let mut api = HidApi {
+            backend: default_backend(),
+            device_list: Vec::with_capacity(8),
+        };
+        api.add_devices(0, 0)?;
+        Ok(api)
+    }
+
+    /// Create a context that enumerates and opens devices with the given
+    /// backend instead of the one selected by the feature flags.
+    ///
+    /// Will also initialize the currently available device list. See the
+    /// [`backend`] module for the available backends.
+    pub fn with_backend(backend: impl Backend + 'static) -> HidResult<Self> {
+        let mut api = HidApi {
+            backend: Box::new(backend),
             device_list: Vec::with_capacity(8),
         };
         api.add_devices(0, 0)?;
@@ -214,6 +299,7 @@ impl HidApi {
         lazy_init(false)?;
 
         Ok(HidApi {
+            backend: default_backend(),
             device_list: Vec::new(),
         })
     }
@@ -227,6 +313,17 @@ impl HidApi {
         Ok(())
     }
 
+    /// Refresh the devices list like `refresh_devices()`, and report how it
+    /// changed.
+    ///
+    /// Entries are matched by their path, usage page and usage. If enumerating
+    /// the devices fails, the devices list is left unchanged.
+    pub fn refresh_devices_diff(&mut self) -> HidResult<DeviceListDiff> {
+        let devices = self.backend.enumerate(0, 0)?;
+        let old_devices = std::mem::replace(&mut self.device_list, devices);
+        Ok(DeviceListDiff::new(&old_devices, &self.device_list))
+    }
+
     /// Reset devices list. Intended to be used with the `add_devices` method.
     pub fn reset_devices(&mut self) -> HidResult<()> {
         self.device_list.clear();
@@ -237,7 +334,7 @@ impl HidApi {
     /// 0 indicates no filter.
     pub fn add_devices(&mut self, vid: u16, pid: u16) -> HidResult<()> {
         self.device_list
-            .append(&mut HidApiBackend::get_hid_device_info_vector(vid, pid)?);
+            .append(&mut self.backend.enumerate(vid, pid)?);
         Ok(())
     }
 
@@ -253,23 +350,20 @@ impl HidApi {
     /// first one found in the internal device list will be used. There are however
     /// no guarantees, which device this will be.
     pub fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
-        let dev = HidApiBackend::open(vid, pid)?;
-        Ok(HidDevice::from_backend(Box::new(dev)))
+        self.backend.open(vid, pid)
     }
 
     /// Open a HID device using a Vendor ID (VID), Product ID (PID) and
     /// a serial number.
     pub fn open_serial(&self, vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
-        let dev = HidApiBackend::open_serial(vid, pid, sn)?;
-        Ok(HidDevice::from_backend(Box::new(dev)))
+        self.backend.open_serial(vid, pid, sn)
     }
 
     /// The path name be determined by inspecting the device list available with [HidApi::devices()](struct.HidApi.html#method.devices)
     ///
     /// Alternatively a platform-specific path name can be used (eg: /dev/hidraw0 on Linux).
     pub fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
-        let dev = HidApiBackend::open_path(device_path)?;
-        Ok(HidDevice::from_backend(Box::new(dev)))
+        self.backend.open_path(device_path)
     }
 
     /// Open a HID device using libusb_wrap_sys_device.
@@ -278,16 +372,29 @@ impl HidApi {
         let device = unsafe { ffi::hid_libusb_wrap_sys_device(sys_dev, interface_num) };
 
         if device.is_null() {
-            match HidApiBackend::check_error() {
+            match hidapi_backend::HidApiBackend::check_error() {
                 Ok(err) => Err(err),
                 Err(e) => Err(e),
             }
         } else {
-            let dev = hidapi::HidDevice::from_raw(device);
+            let dev = hidapi_backend::HidDevice::from_raw(device);
             Ok(HidDevice::from_backend(Box::new(dev)))
         }
     }
 
+    /// Watch for HID devices being connected and disconnected.
+    ///
+    /// Only changes after this call are reported, the devices that are
+    /// already connected are available through `refresh_devices()`. This
+    /// avoids polling the system by repeatedly re-enumerating all devices.
+    ///
+    /// Only the `LinuxNative` backend can watch devices, the others, like
+    /// `LinuxSysfs` and the ones of other crates, fail with "not supported".
+    #[cfg(all(feature = "linux-native", target_os = "linux"))]
+    pub fn monitor(&self) -> HidResult<DeviceMonitor> {
+        self.backend.monitor()
+    }
+
     /// Get the last non-device specific error, which happened in the underlying hidapi C library.
     /// To get the last device specific error, use [`HidDevice::check_error`].
     ///
@@ -299,7 +406,7 @@ impl HidApi {
     #[cfg(hidapi)]
     #[deprecated(since = "2.2.3", note = "use the return values from the other methods")]
     pub fn check_error(&self) -> HidResult<HidError> {
-        HidApiBackend::check_error()
+        hidapi_backend::HidApiBackend::check_error()
     }
 }
 
@@ -307,7 +414,10 @@ impl HidApi {
 #[derive(Clone, PartialEq)]
 enum WcharString {
     String(String),
-    #[cfg_attr(all(feature = "linux-native", target_os = "linux"), allow(dead_code))]
+    #[cfg_attr(
+        all(feature = "linux-native-sysfs", target_os = "linux"),
+        allow(dead_code)
+    )]
     Raw(Vec<wchar_t>),
     None,
 }
@@ -321,9 +431,60 @@ impl From<WcharString> for Option<String> {
     }
 }
 
+/// A HID device being connected or disconnected, see `HidApi::monitor()`.
+#[derive(Clone, Debug)]
+pub enum DeviceEvent {
+    /// A device was connected. Like in the device list, a device with several
+    /// top level collections is reported once per usage.
+    Added(DeviceInfo),
+    /// The device with the given path was disconnected.
+    Removed(CString),
+}
+
+/// The changes of the devices list, see `HidApi::refresh_devices_diff()`.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceListDiff {
+    /// Entries that are new in the list
+    pub added: Vec<DeviceInfo>,
+    /// Entries that are no longer in the list, with their old information
+    pub removed: Vec<DeviceInfo>,
+    /// Entries that are still in the list, with their new information
+    pub changed: Vec<DeviceInfo>,
+}
+
+impl DeviceListDiff {
+    fn new(old: &[DeviceInfo], new: &[DeviceInfo]) -> Self {
+        let find = |list: &[DeviceInfo], info: &DeviceInfo| {
+            list.iter()
+                .find(|i| i.identity() == info.identity())
+                .cloned()
+        };
+
+        let mut diff = Self::default();
+        for info in new {
+            match find(old, info) {
+                None => diff.added.push(info.clone()),
+                Some(old_info) if old_info != *info => diff.changed.push(info.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|info| find(new, info).is_none())
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Whether the list did not change at all
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
 /// The underlying HID bus type.
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum BusType {
     Unknown = 0x00,
     Usb = 0x01,
@@ -337,7 +498,7 @@ pub enum BusType {
 /// Note: Methods like `serial_number()` may return None, if the conversion to a
 /// String failed internally. You can however access the raw hid representation of the
 /// string by calling `serial_number_raw()`
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct DeviceInfo {
     path: CString,
     vendor_id: u16,
@@ -352,6 +513,7 @@ pub struct DeviceInfo {
     usage: u16,
     interface_number: i32,
     bus_type: BusType,
+    physical_path: Option<String>,
 }
 
 impl DeviceInfo {
@@ -428,6 +590,12 @@ impl DeviceInfo {
         self.usage
     }
 
+    /// The name of the top level usage, like `Mouse`, see [`usage::name`]
+    #[cfg(not(all(libusb, target_os = "linux")))]
+    pub fn usage_name(&self) -> Option<std::borrow::Cow<'static, str>> {
+        usage::name(descriptor::Usage::new(self.usage_page, self.usage))
+    }
+
     pub fn interface_number(&self) -> i32 {
         self.interface_number
     }
@@ -436,6 +604,14 @@ impl DeviceInfo {
         self.bus_type
     }
 
+    /// Where the device is connected, like `usb-0000:00:14.0-2/input2`
+    ///
+    /// Only the linux-native backend knows it. None for the other backends
+    /// and for devices that do not have one.
+    pub fn physical_path(&self) -> Option<&str> {
+        self.physical_path.as_deref()
+    }
+
     /// Use the information contained in `DeviceInfo` to open
     /// and return a handle to a [HidDevice](struct.HidDevice.html).
     ///
@@ -458,6 +634,79 @@ impl DeviceInfo {
     }
 }
 
+// Construction for custom backends
+impl DeviceInfo {
+    /// Describe a device of a custom [`Backend`]
+    ///
+    /// The other properties are unknown until they are set with the `with_`
+    /// methods.
+    pub fn new(path: CString, vendor_id: u16, product_id: u16) -> Self {
+        Self {
+            path,
+            vendor_id,
+            product_id,
+            serial_number: WcharString::None,
+            release_number: 0,
+            manufacturer_string: WcharString::None,
+            product_string: WcharString::None,
+            usage_page: 0,
+            usage: 0,
+            interface_number: -1,
+            bus_type: BusType::Unknown,
+            physical_path: None,
+        }
+    }
+
+    pub fn with_serial_number(mut self, serial_number: &str) -> Self {
+        self.serial_number = WcharString::String(serial_number.into());
+        self
+    }
+
+    pub fn with_release_number(mut self, release_number: u16) -> Self {
+        self.release_number = release_number;
+        self
+    }
+
+    pub fn with_manufacturer_string(mut self, manufacturer_string: &str) -> Self {
+        self.manufacturer_string = WcharString::String(manufacturer_string.into());
+        self
+    }
+
+    pub fn with_product_string(mut self, product_string: &str) -> Self {
+        self.product_string = WcharString::String(product_string.into());
+        self
+    }
+
+    /// The usage of the top level collection this entry stands for
+    pub fn with_usage(mut self, usage_page: u16, usage: u16) -> Self {
+        self.usage_page = usage_page;
+        self.usage = usage;
+        self
+    }
+
+    pub fn with_interface_number(mut self, interface_number: i32) -> Self {
+        self.interface_number = interface_number;
+        self
+    }
+
+    pub fn with_bus_type(mut self, bus_type: BusType) -> Self {
+        self.bus_type = bus_type;
+        self
+    }
+
+    pub fn with_physical_path(mut self, physical_path: &str) -> Self {
+        self.physical_path = Some(physical_path.into());
+        self
+    }
+}
+
+impl DeviceInfo {
+    /// What identifies an entry of the devices list across refreshes
+    fn identity(&self) -> (&CStr, u16, u16) {
+        (&self.path, self.usage_page, self.usage)
+    }
+}
+
 impl fmt::Debug for DeviceInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HidDeviceInfo")
@@ -467,31 +716,8 @@ impl fmt::Debug for DeviceInfo {
     }
 }
 
-/// Trait which the different backends must implement
-trait HidDeviceBackendBase {
-    #[cfg(hidapi)]
-    fn check_error(&self) -> HidResult<HidError>;
-    fn write(&self, data: &[u8]) -> HidResult<usize>;
-    fn read(&self, buf: &mut [u8]) -> HidResult<usize>;
-    fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize>;
-    fn send_feature_report(&self, data: &[u8]) -> HidResult<()>;
-    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize>;
-    fn set_blocking_mode(&self, blocking: bool) -> HidResult<()>;
-    fn get_device_info(&self) -> HidResult<DeviceInfo>;
-    fn get_manufacturer_string(&self) -> HidResult<Option<String>>;
-    fn get_product_string(&self) -> HidResult<Option<String>>;
-    fn get_serial_number_string(&self) -> HidResult<Option<String>>;
-    fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize>;
-
-    fn get_indexed_string(&self, _index: i32) -> HidResult<Option<String>> {
-        Err(HidError::HidApiError {
-            message: "get_indexed_string: not supported".to_string(),
-        })
-    }
-}
-
 pub struct HidDevice {
-    inner: Box<dyn HidDeviceBackend>,
+    inner: Box<dyn DeviceBackend>,
 }
 
 impl Debug for HidDevice {
@@ -501,9 +727,22 @@ impl Debug for HidDevice {
 }
 
 impl HidDevice {
-    fn from_backend(inner: Box<dyn HidDeviceBackend>) -> Self {
+    /// Wrap a device opened by a custom [`Backend`]
+    pub fn from_backend(inner: Box<dyn DeviceBackend>) -> Self {
         Self { inner }
     }
+
+    /// The file descriptor of the device, if the backend has one
+    ///
+    /// It becomes readable when an Input report is available. The
+    /// `linux-native` backend returns the hidraw file descriptor, which is
+    /// opened in non blocking mode, and the `mock` devices return one that
+    /// is signalled when a report is pushed. The `hidapi` C library backends
+    /// have none, which is why `HidDevice` does not implement `AsFd`.
+    #[cfg(unix)]
+    pub fn as_fd(&self) -> Option<BorrowedFd<'_>> {
+        self.inner.as_fd()
+    }
 }
 
 // Methods that use the backend
@@ -594,6 +833,31 @@ impl HidDevice {
         self.inner.get_feature_report(buf)
     }
 
+    /// Get an input report from a HID device over the Control endpoint as a
+    /// Get_Report transfer, instead of waiting for it on the Interrupt endpoint.
+    ///
+    /// Set the first byte of `buf` to the 'Report ID' of the report to be read,
+    /// or 0x0 for devices which do not use numbered reports. Upon return, the
+    /// first byte will still contain the Report ID, and the report data will
+    /// start in `buf[1]`.
+    ///
+    /// If successful, returns the number of bytes read plus one for the report ID (which is still
+    /// in the first byte).
+    pub fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.inner.get_input_report(buf)
+    }
+
+    /// Send an Output report to the device over the Control endpoint as a
+    /// Set_Report transfer, unlike [`write`](HidDevice::write) which uses the
+    /// Interrupt endpoint if the device has one.
+    ///
+    /// The first byte of `data` must contain the 'Report ID', or 0x0 for
+    /// devices which do not use numbered reports, like for
+    /// [`send_feature_report`](HidDevice::send_feature_report).
+    pub fn send_output_report(&self, data: &[u8]) -> HidResult<()> {
+        self.inner.send_output_report(data)
+    }
+
     /// Set the device handle to be in blocking or in non-blocking mode. In
     /// non-blocking mode calls to `read()` will return immediately with an empty
     /// slice if there is no data to be read. In blocking mode, `read()` will
@@ -631,8 +895,131 @@ impl HidDevice {
         self.inner.get_report_descriptor(buf)
     }
 
+    /// Get the report descriptor of a HID device and parse it
+    pub fn report_descriptor(&self) -> HidResult<descriptor::ReportDescriptor> {
+        let mut buf = [0u8; MAX_REPORT_DESCRIPTOR_SIZE];
+        let len = self.get_report_descriptor(&mut buf)?;
+        Ok(descriptor::ReportDescriptor::parse(&buf[..len])?)
+    }
+
+    /// Get the capabilities of a HID device, computed from its report descriptor
+    ///
+    /// This mirrors `HidP_GetCaps` on Windows and works on every backend. The
+    /// report lengths include the report ID byte and are suitable as buffer
+    /// sizes for [`read`](HidDevice::read) and
+    /// [`get_feature_report`](HidDevice::get_feature_report).
+    pub fn capabilities(&self) -> HidResult<descriptor::Capabilities> {
+        Ok(self.report_descriptor()?.capabilities())
+    }
+
     /// Get [`DeviceInfo`] from a HID device.
     pub fn get_device_info(&self) -> HidResult<DeviceInfo> {
         self.inner.get_device_info()
     }
 }
+
+// Typed reports
+#[cfg(feature = "derive")]
+impl HidDevice {
+    /// Read the next Input report with the ID of `T`
+    ///
+    /// Input reports with other IDs are skipped. In non-blocking mode this
+    /// fails with [`std::io::ErrorKind::WouldBlock`] if no report is
+    /// available.
+    pub fn read_report<T: report::InputReport>(&self) -> HidResult<T> {
+        let mut buf = vec![0u8; T::LENGTH];
+        loop {
+            // Reports without ID are read without the leading 0
+            let start = (T::REPORT_ID == 0) as usize;
+            let len = self.read(&mut buf[start..])?;
+            if len == 0 {
+                return Err(HidError::IoError {
+                    error: std::io::ErrorKind::WouldBlock.into(),
+                });
+            }
+            if buf[0] == T::REPORT_ID {
+                return T::from_bytes(&buf[..start + len]);
+            }
+        }
+    }
+
+    /// Write `report` as an Output report
+    pub fn write_report<T: report::OutputReport>(&self, report: &T) -> HidResult<()> {
+        let bytes = report.to_bytes();
+        let sent = self.write(&bytes)?;
+        if sent < bytes.len() {
+            return Err(HidError::IncompleteSendError {
+                sent,
+                all: bytes.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Get the Feature report with the ID of `T`
+    pub fn get_feature<T: report::FeatureReport>(&self) -> HidResult<T> {
+        let mut buf = vec![0u8; T::LENGTH];
+        buf[0] = T::REPORT_ID;
+        let len = self.get_feature_report(&mut buf)?;
+        T::from_bytes(&buf[..len])
+    }
+
+    /// Send `report` as a Feature report
+    pub fn send_feature<T: report::FeatureReport>(&self, report: &T) -> HidResult<()> {
+        self.send_feature_report(&report.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device_info(path: &str, usage: u16, product: &str) -> DeviceInfo {
+        DeviceInfo {
+            path: CString::new(path).unwrap(),
+            vendor_id: 0x046D,
+            product_id: 0xC534,
+            serial_number: WcharString::None,
+            release_number: 0,
+            manufacturer_string: WcharString::None,
+            product_string: WcharString::String(product.into()),
+            usage_page: 1,
+            usage,
+            interface_number: -1,
+            bus_type: BusType::Usb,
+            physical_path: None,
+        }
+    }
+
+    #[test]
+    fn test_device_list_diff() {
+        let old = [
+            device_info("/dev/hidraw0", 2, "Mouse"),
+            device_info("/dev/hidraw0", 6, "Mouse"),
+            device_info("/dev/hidraw1", 6, "Keyboard"),
+        ];
+        let new = [
+            device_info("/dev/hidraw0", 2, "Mouse"),
+            device_info("/dev/hidraw1", 6, "Keyboard 2"),
+            device_info("/dev/hidraw2", 6, "Keyboard"),
+        ];
+
+        let diff = DeviceListDiff::new(&old, &new);
+        assert_eq!(diff.added, [new[2].clone()]);
+        assert_eq!(diff.removed, [old[1].clone()]);
+        assert_eq!(diff.changed, [new[1].clone()]);
+        assert!(DeviceListDiff::new(&new, &new).is_empty());
+    }
+
+    #[test]
+    #[cfg(not(all(libusb, target_os = "linux")))]
+    fn test_usage_name() {
+        assert_eq!(
+            device_info("/dev/hidraw0", 2, "Mouse")
+                .usage_name()
+                .as_deref(),
+            Some("Mouse")
+        );
+        assert_eq!(device_info("/dev/hidraw0", 3, "").usage_name(), None);
+    }
+}
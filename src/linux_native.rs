@@ -1,6 +1,10 @@
 //! This backend uses libudev to discover devices and then talks to hidraw directly
+//!
+//! Where libudev fails, or without the `linux-native` feature, the devices are
+//! found by reading sysfs, see [`sysfs`].
 
 mod ioctl;
+pub(crate) mod sysfs;
 
 use std::{
     cell::{Cell, Ref, RefCell},
@@ -13,6 +17,12 @@ use std::{
     },
     path::{Path, PathBuf},
 };
+#[cfg(feature = "linux-native")]
+use std::{
+    collections::VecDeque,
+    os::fd::RawFd,
+    time::{Duration, Instant},
+};
 
 use nix::{
     errno::Errno,
@@ -21,8 +31,14 @@ use nix::{
     unistd::{read, write},
 };
 
-use super::{BusType, DeviceInfo, HidDeviceBackendBase, HidError, HidResult, WcharString};
-use ioctl::{hidraw_ioc_get_feature, hidraw_ioc_grdescsize, hidraw_ioc_set_feature};
+#[cfg(feature = "linux-native")]
+use super::DeviceEvent;
+use super::{BusType, DeviceBackend, DeviceInfo, HidError, HidResult, WcharString};
+use ioctl::{
+    hidraw_ioc_get_feature, hidraw_ioc_get_input, hidraw_ioc_grawinfo, hidraw_ioc_grawname,
+    hidraw_ioc_grawphys, hidraw_ioc_grawuniq, hidraw_ioc_grdesc, hidraw_ioc_grdescsize,
+    hidraw_ioc_set_feature, hidraw_ioc_set_output, HidrawDevinfo, HID_MAX_DESCRIPTOR_SIZE,
+};
 
 // Bus values from linux/input.h
 const BUS_USB: u16 = 0x03;
@@ -33,22 +49,27 @@ const BUS_SPI: u16 = 0x1C;
 pub struct HidApiBackend;
 
 impl HidApiBackend {
+    #[cfg(feature = "linux-native")]
     pub fn get_hid_device_info_vector(vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
         // The C version assumes these can't fail, and they should only fail in case
         // of memory allocation issues, at which point maybe we should panic
-        let mut enumerator = match udev::Enumerator::new() {
-            Ok(e) => e,
-            Err(_) => return Ok(Vec::new()),
-        };
-        enumerator.match_subsystem("hidraw").unwrap();
-        let scan = match enumerator.scan_devices() {
-            Ok(s) => s,
-            Err(_) => return Ok(Vec::new()),
+        // Without udev, e.g. in a container, read sysfs instead
+        let devices = match udev::Enumerator::new() {
+            Ok(mut enumerator) => {
+                enumerator.match_subsystem("hidraw").unwrap();
+                match enumerator.scan_devices() {
+                    Ok(scan) => scan
+                        .filter_map(|device| device_to_hid_device_info(&device))
+                        .flatten()
+                        .collect(),
+                    Err(_) => sysfs::enumerate(Path::new(sysfs::SYSFS_ROOT)),
+                }
+            }
+            Err(_) => sysfs::enumerate(Path::new(sysfs::SYSFS_ROOT)),
         };
 
-        let devices = scan
-            .filter_map(|device| device_to_hid_device_info(&device))
-            .flatten()
+        let devices = devices
+            .into_iter()
             .filter(|device| vid == 0 || device.vendor_id == vid)
             .filter(|device| pid == 0 || device.product_id == pid)
             .collect::<Vec<_>>();
@@ -56,10 +77,12 @@ impl HidApiBackend {
         Ok(devices)
     }
 
+    #[cfg(feature = "linux-native")]
     pub fn open(vid: u16, pid: u16) -> HidResult<HidDevice> {
         HidDevice::open(vid, pid, None)
     }
 
+    #[cfg(feature = "linux-native")]
     pub fn open_serial(vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
         HidDevice::open(vid, pid, Some(sn))
     }
@@ -67,44 +90,210 @@ impl HidApiBackend {
     pub fn open_path(device_path: &CStr) -> HidResult<HidDevice> {
         HidDevice::open_path(device_path)
     }
+
+    #[cfg(feature = "linux-native")]
+    pub fn monitor() -> HidResult<DeviceMonitor> {
+        let socket = udev::MonitorBuilder::new()?
+            .match_subsystem("hidraw")?
+            .listen()?;
+        Ok(DeviceMonitor {
+            socket,
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+#[cfg(feature = "linux-native")]
+/// Reports hidraw devices being connected and disconnected, created by
+/// [`HidApi::monitor`](crate::HidApi::monitor)
+///
+/// Iterating blocks until the next event arrives and never ends, a failure of
+/// the udev socket is yielded as an error. To integrate with an event loop,
+/// poll the file descriptor for readability and then fetch the events with
+/// [`DeviceMonitor::next_event`] and a timeout of 0.
+pub struct DeviceMonitor {
+    socket: udev::MonitorSocket,
+    pending: VecDeque<DeviceEvent>,
+}
+
+#[cfg(feature = "linux-native")]
+impl DeviceMonitor {
+    /// Wait for the next event
+    ///
+    /// The timeout is in milliseconds, -1 waits forever and 0 returns
+    /// immediately. Returns `None` if no event arrived in time.
+    pub fn next_event(&mut self, timeout: i32) -> HidResult<Option<DeviceEvent>> {
+        let deadline = u64::try_from(timeout)
+            .ok()
+            .map(|timeout| Instant::now() + Duration::from_millis(timeout));
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            // The socket is non blocking, so drain it before waiting
+            if let Some(device) = self.receive_device()? {
+                self.pending
+                    .extend(uevent_to_device_events(device.action(), &device));
+                continue;
+            }
+
+            // Only wait for what is left of the timeout, a wakeup may not
+            // have produced an event
+            let timeout = match deadline {
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_micros()
+                    .div_ceil(1000) as i32,
+                None => -1,
+            };
+            let pollfd = PollFd::new(&self.socket, PollFlags::POLLIN);
+            match poll(&mut [pollfd], timeout) {
+                Ok(0) => return Ok(None),
+                Ok(_) | Err(Errno::EINTR) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Receive the next uevent from the socket, if there is one
+    ///
+    /// Unlike [`udev::MonitorSocket::iter`], this tells a failure apart from
+    /// an empty socket.
+    fn receive_device(&self) -> HidResult<Option<udev::Device>> {
+        use udev::{ffi, AsRaw, FromRawWithContext};
+
+        let monitor = self.socket.as_raw();
+        Errno::clear();
+        let device = unsafe { ffi::udev_monitor_receive_device(monitor) };
+        if device.is_null() {
+            return match Errno::last() {
+                // Nothing left, or only uevents that did not match the filter
+                Errno::UnknownErrno | Errno::EAGAIN | Errno::EINTR => Ok(None),
+                e => Err(e.into()),
+            };
+        }
+        // The device releases a reference to the udev context when dropped
+        unsafe {
+            let udev = ffi::udev_ref(ffi::udev_monitor_get_udev(monitor));
+            Ok(Some(udev::Device::from_raw_with_context(udev, device)))
+        }
+    }
+}
+
+#[cfg(feature = "linux-native")]
+impl Iterator for DeviceMonitor {
+    type Item = HidResult<DeviceEvent>;
+
+    /// Block until the next event or a failure of the udev socket
+    fn next(&mut self) -> Option<HidResult<DeviceEvent>> {
+        self.next_event(-1).transpose()
+    }
+}
+
+#[cfg(feature = "linux-native")]
+impl AsFd for DeviceMonitor {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
+
+#[cfg(feature = "linux-native")]
+impl AsRawFd for DeviceMonitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+/// The events for a uevent of a hidraw device with the given action
+#[cfg(feature = "linux-native")]
+fn uevent_to_device_events<D: SysDevice>(action: Option<&OsStr>, device: &D) -> Vec<DeviceEvent> {
+    match action.and_then(OsStr::to_str) {
+        Some("add") => device_to_hid_device_info(device)
+            .unwrap_or_default()
+            .into_iter()
+            .map(DeviceEvent::Added)
+            .collect(),
+        // The device is gone from sysfs, only the node name of the event is left
+        Some("remove") => device
+            .devnode()
+            .and_then(|p| CString::new(p.into_os_string().into_vec()).ok())
+            .map(DeviceEvent::Removed)
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The parts of a device in sysfs that make up its [`DeviceInfo`], from
+/// libudev or read from sysfs directly
+pub(crate) trait SysDevice: Sized {
+    fn syspath(&self) -> &Path;
+    fn devnode(&self) -> Option<PathBuf>;
+    /// A property of the `uevent` file
+    fn property(&self, key: &str) -> Option<OsString>;
+    fn attribute(&self, name: &str) -> Option<OsString>;
+    /// The closest parent with the subsystem and, if given, the device type
+    fn parent(&self, subsystem: &str, devtype: Option<&str>) -> Option<Self>;
+}
+
+#[cfg(feature = "linux-native")]
+impl SysDevice for udev::Device {
+    fn syspath(&self) -> &Path {
+        udev::Device::syspath(self)
+    }
+
+    fn devnode(&self) -> Option<PathBuf> {
+        udev::Device::devnode(self).map(Path::to_path_buf)
+    }
+
+    fn property(&self, key: &str) -> Option<OsString> {
+        self.property_value(key).map(OsStr::to_os_string)
+    }
+
+    fn attribute(&self, name: &str) -> Option<OsString> {
+        self.attribute_value(name).map(OsStr::to_os_string)
+    }
+
+    fn parent(&self, subsystem: &str, devtype: Option<&str>) -> Option<Self> {
+        match devtype {
+            Some(devtype) => self.parent_with_subsystem_devtype(subsystem, devtype),
+            None => self.parent_with_subsystem(subsystem),
+        }
+        .ok()
+        .flatten()
+    }
 }
 
-fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo>> {
+pub(crate) fn device_to_hid_device_info<D: SysDevice>(raw_device: &D) -> Option<Vec<DeviceInfo>> {
     let mut infos = Vec::new();
 
     // We're given the hidraw device, but we actually want to go and check out
     // the info for the parent hid device.
-    let device = match raw_device.parent_with_subsystem("hid") {
-        Ok(Some(dev)) => dev,
-        _ => return None,
-    };
+    let device = raw_device.parent("hid", None)?;
 
     let (bus, vid, pid) = match device
-        .property_value("HID_ID")
-        .and_then(|s| s.to_str())
+        .property("HID_ID")
+        .as_deref()
+        .and_then(OsStr::to_str)
         .and_then(parse_hid_vid_pid)
     {
         Some(t) => t,
         None => return None,
     };
-    let bus_type = match bus {
-        BUS_USB => BusType::Usb,
-        BUS_BLUETOOTH => BusType::Bluetooth,
-        BUS_I2C => BusType::I2c,
-        BUS_SPI => BusType::Spi,
-        _ => return None,
-    };
-    let name = match device.property_value("HID_NAME") {
+    let bus_type = bus_type(bus)?;
+    let name = match device.property("HID_NAME") {
         Some(name) => name,
         None => return None,
     };
-    let serial = match device.property_value("HID_UNIQ") {
+    let serial = match device.property("HID_UNIQ") {
         Some(serial) => serial,
         None => return None,
     };
+    let physical_path = device.property("HID_PHYS");
     let path = match raw_device
         .devnode()
-        .map(|p| p.as_os_str().to_os_string().into_vec())
+        .map(|p| p.into_os_string().into_vec())
         .map(CString::new)
     {
         Some(Ok(s)) => s,
@@ -116,7 +305,7 @@ fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo
         path,
         vendor_id: vid,
         product_id: pid,
-        serial_number: osstring_to_string(serial.into()),
+        serial_number: osstring_to_string(serial),
         release_number: 0,
         manufacturer_string: WcharString::None,
         product_string: WcharString::None,
@@ -124,20 +313,19 @@ fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo
         usage: 0,
         interface_number: -1,
         bus_type,
+        physical_path: physical_path.and_then(physical_path_to_string),
     };
 
     // USB has a bunch more information but everything else gets the same empty
     // manufacturer and the product we read from the property above.
     let info = match bus_type {
         BusType::Usb => fill_in_usb(raw_device, info, name),
-        _ => DeviceInfo {
-            manufacturer_string: WcharString::String("".into()),
-            product_string: osstring_to_string(name.into()),
-            ..info
-        },
+        _ => fill_in_other(info, name),
     };
 
-    if let Ok(descriptor) = HidrawReportDescriptor::from_syspath(raw_device.syspath()) {
+    // The same file as `device/report_descriptor` of the hidraw device
+    if let Ok(descriptor) = std::fs::read(device.syspath().join("report_descriptor")) {
+        let descriptor = HidrawReportDescriptor(descriptor);
         let mut usages = descriptor.usages();
 
         // Get the first usage page and usage for our current DeviceInfo
@@ -166,25 +354,35 @@ fn device_to_hid_device_info(raw_device: &udev::Device) -> Option<Vec<DeviceInfo
     Some(infos)
 }
 
+fn bus_type(bus: u16) -> Option<BusType> {
+    match bus {
+        BUS_USB => Some(BusType::Usb),
+        BUS_BLUETOOTH => Some(BusType::Bluetooth),
+        BUS_I2C => Some(BusType::I2c),
+        BUS_SPI => Some(BusType::Spi),
+        _ => None,
+    }
+}
+
+/// Devices without a physical path, like virtual ones, have an empty one
+fn physical_path_to_string(physical_path: OsString) -> Option<String> {
+    match osstring_to_string(physical_path) {
+        WcharString::String(s) if !s.is_empty() => Some(s),
+        _ => None,
+    }
+}
+
 /// Fill in the extra information that's available for a USB device.
-fn fill_in_usb(device: &udev::Device, info: DeviceInfo, name: &OsStr) -> DeviceInfo {
-    let usb_dev = match device.parent_with_subsystem_devtype("usb", "usb_device") {
-        Ok(Some(dev)) => dev,
-        Ok(None) | Err(_) => {
-            return DeviceInfo {
-                manufacturer_string: WcharString::String("".into()),
-                product_string: osstring_to_string(name.into()),
-                ..info
-            }
-        }
+fn fill_in_usb<D: SysDevice>(device: &D, info: DeviceInfo, name: OsString) -> DeviceInfo {
+    let usb_dev = match device.parent("usb", Some("usb_device")) {
+        Some(dev) => dev,
+        None => return fill_in_other(info, name),
     };
     let manufacturer_string = attribute_as_wchar(&usb_dev, "manufacturer");
     let product_string = attribute_as_wchar(&usb_dev, "product");
     let release_number = attribute_as_u16(&usb_dev, "bcdDevice").unwrap_or(0);
     let interface_number = device
-        .parent_with_subsystem_devtype("usb", "usb_interface")
-        .ok()
-        .flatten()
+        .parent("usb", Some("usb_interface"))
         .and_then(|ref dev| attribute_as_i32(dev, "bInterfaceNumber"))
         .unwrap_or(-1);
 
@@ -197,6 +395,15 @@ fn fill_in_usb(device: &udev::Device, info: DeviceInfo, name: &OsStr) -> DeviceI
     }
 }
 
+/// Devices other than USB have no manufacturer and the HID name as product
+fn fill_in_other(info: DeviceInfo, name: OsString) -> DeviceInfo {
+    DeviceInfo {
+        manufacturer_string: WcharString::String("".into()),
+        product_string: osstring_to_string(name),
+        ..info
+    }
+}
+
 #[derive(Default)]
 struct HidrawReportDescriptor(Vec<u8>);
 
@@ -354,9 +561,8 @@ fn hid_report_bytes(cursor: &mut Cursor<&Vec<u8>>, num_bytes: usize) -> HidResul
 }
 
 /// Get the attribute from the device and convert it into a [`WcharString`].
-fn attribute_as_wchar(dev: &udev::Device, attr: &str) -> WcharString {
-    dev.attribute_value(attr)
-        .map(Into::into)
+fn attribute_as_wchar<D: SysDevice>(dev: &D, attr: &str) -> WcharString {
+    dev.attribute(attr)
         .map(osstring_to_string)
         .unwrap_or(WcharString::None)
 }
@@ -364,8 +570,9 @@ fn attribute_as_wchar(dev: &udev::Device, attr: &str) -> WcharString {
 /// Get the attribute from the device and convert it into a i32
 ///
 /// On error or if the attribute is not found, it returns None.
-fn attribute_as_i32(dev: &udev::Device, attr: &str) -> Option<i32> {
-    dev.attribute_value(attr)
+fn attribute_as_i32<D: SysDevice>(dev: &D, attr: &str) -> Option<i32> {
+    dev.attribute(attr)
+        .as_deref()
         .and_then(OsStr::to_str)
         .and_then(|v| i32::from_str_radix(v, 16).ok())
 }
@@ -373,17 +580,21 @@ fn attribute_as_i32(dev: &udev::Device, attr: &str) -> Option<i32> {
 /// Get the attribute from the device and convert it into a u16
 ///
 /// On error or if the attribute is not found, it returns None.
-fn attribute_as_u16(dev: &udev::Device, attr: &str) -> Option<u16> {
-    dev.attribute_value(attr)
+fn attribute_as_u16<D: SysDevice>(dev: &D, attr: &str) -> Option<u16> {
+    dev.attribute(attr)
+        .as_deref()
         .and_then(OsStr::to_str)
         .and_then(|v| u16::from_str_radix(v, 16).ok())
 }
 
 /// Convert a [`OsString`] into a [`WcharString`]
+///
+/// The strings come from the device, e.g. the name of a uhid device, and are
+/// not always UTF-8. Invalid sequences are replaced with U+FFFD.
 fn osstring_to_string(s: OsString) -> WcharString {
     match s.into_string() {
         Ok(s) => WcharString::String(s),
-        Err(_) => panic!("udev strings should always be utf8"),
+        Err(s) => WcharString::String(String::from_utf8_lossy(&s.into_vec()).into_owned()),
     }
 }
 
@@ -405,6 +616,7 @@ fn parse_hid_vid_pid(s: &str) -> Option<(u16, u16, u16)> {
 pub struct HidDevice {
     blocking: Cell<bool>,
     fd: OwnedFd,
+    path: CString,
     info: RefCell<Option<DeviceInfo>>,
 }
 
@@ -412,6 +624,7 @@ unsafe impl Send for HidDevice {}
 
 // API for the library to call us, or for internal uses
 impl HidDevice {
+    #[cfg(feature = "linux-native")]
     pub(crate) fn open(vid: u16, pid: u16, sn: Option<&str>) -> HidResult<Self> {
         for device in HidApiBackend::get_hid_device_info_vector(0, 0)?
             .iter()
@@ -458,10 +671,64 @@ impl HidDevice {
         Ok(Self {
             blocking: Cell::new(true),
             fd,
+            path: device_path.to_owned(),
             info: RefCell::new(None),
         })
     }
 
+    /// The sysfs device of the hidraw node, found through its device number
+    fn sysfs_device(&self) -> HidResult<sysfs::SysfsDevice> {
+        let devnum = fstat(self.fd.as_raw_fd())?.st_rdev;
+        let syspath: PathBuf = format!("/sys/dev/char/{}:{}", major(devnum), minor(devnum)).into();
+        sysfs::SysfsDevice::new(Path::new(sysfs::SYSFS_ROOT), &syspath).ok_or_else(|| {
+            HidError::HidApiError {
+                message: format!("no sysfs device at {}", syspath.display()),
+            }
+        })
+    }
+
+    /// Ask hidraw itself, which works without sysfs
+    ///
+    /// Only the extra information of USB devices is read from sysfs, when it
+    /// is there.
+    fn device_info_from_ioctls(&self) -> HidResult<DeviceInfo> {
+        let fd = self.fd.as_raw_fd();
+        let mut devinfo = HidrawDevinfo::default();
+        unsafe { hidraw_ioc_grawinfo(fd, &mut devinfo) }?;
+        let name = string_ioctl(|buf| unsafe { hidraw_ioc_grawname(fd, buf) })?;
+        let physical_path = string_ioctl(|buf| unsafe { hidraw_ioc_grawphys(fd, buf) })?;
+        let serial = string_ioctl(|buf| unsafe { hidraw_ioc_grawuniq(fd, buf) })?;
+
+        let Some(bus_type) = bus_type(devinfo.bustype as u16) else {
+            return Err(HidError::HidApiError {
+                message: "failed to create device info".into(),
+            });
+        };
+        let (usage_page, usage) = HidrawReportDescriptor(report_descriptor(self.fd.as_fd())?)
+            .usages()
+            .next()
+            .unwrap_or_default();
+
+        let info = DeviceInfo {
+            path: self.path.clone(),
+            vendor_id: devinfo.vendor as u16,
+            product_id: devinfo.product as u16,
+            serial_number: osstring_to_string(serial),
+            release_number: 0,
+            manufacturer_string: WcharString::None,
+            product_string: WcharString::None,
+            usage_page,
+            usage,
+            interface_number: -1,
+            bus_type,
+            physical_path: physical_path_to_string(physical_path),
+        };
+        Ok(match (bus_type, self.sysfs_device()) {
+            (BusType::Usb, Ok(device)) => fill_in_usb(&device, info, name),
+            _ => fill_in_other(info, name),
+        })
+    }
+
     fn info(&self) -> HidResult<Ref<DeviceInfo>> {
         if self.info.borrow().is_none() {
             let info = self.get_device_info()?;
@@ -479,7 +746,7 @@ impl AsFd for HidDevice {
     }
 }
 
-impl HidDeviceBackendBase for HidDevice {
+impl DeviceBackend for HidDevice {
     fn write(&self, data: &[u8]) -> HidResult<usize> {
         if data.is_empty() {
             return Err(HidError::InvalidZeroSizeData);
@@ -520,40 +787,19 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
-        if data.is_empty() {
-            return Err(HidError::InvalidZeroSizeData);
-        }
-
-        let res = match unsafe { hidraw_ioc_set_feature(self.fd.as_raw_fd(), data) } {
-            Ok(n) => n as usize,
-            Err(e) => {
-                return Err(HidError::HidApiError {
-                    message: format!("ioctl (GFEATURE): {e}"),
-                })
-            }
-        };
-
-        if res != data.len() {
-            return Err(HidError::IncompleteSendError {
-                sent: res,
-                all: data.len(),
-            });
-        }
-
-        Ok(())
+        send_feature_report(self.fd.as_fd(), data)
     }
 
     fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
-        let res = match unsafe { hidraw_ioc_get_feature(self.fd.as_raw_fd(), buf) } {
-            Ok(n) => n as usize,
-            Err(e) => {
-                return Err(HidError::HidApiError {
-                    message: format!("ioctl (GFEATURE): {e}"),
-                })
-            }
-        };
+        get_feature_report(self.fd.as_fd(), buf)
+    }
+
+    fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        get_input_report(self.fd.as_fd(), buf)
+    }
 
-        Ok(res)
+    fn send_output_report(&self, data: &[u8]) -> HidResult<()> {
+        send_output_report(self.fd.as_fd(), data)
     }
 
     fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
@@ -577,15 +823,18 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn get_device_info(&self) -> HidResult<DeviceInfo> {
-        // What we have is a descriptor to a file in /dev but we need a syspath
-        // so we get the major/minor from there and generate our syspath
-        let devnum = fstat(self.fd.as_raw_fd())?.st_rdev;
-        let syspath: PathBuf = format!("/sys/dev/char/{}:{}", major(devnum), minor(devnum)).into();
+        if let Ok(info) = self.device_info_from_ioctls() {
+            return Ok(info);
+        }
 
-        // The clone is a bit silly but we can't implement Copy. Maybe it's not
-        // much worse than doing the conversion to Rust from interacting with C.
-        let device = udev::Device::from_syspath(&syspath)?;
-        match device_to_hid_device_info(&device) {
+        // Kernels before 5.6 have no HIDIOCGRAWUNIQ, read everything from
+        // sysfs then. The clone is a bit silly but we can't implement Copy.
+        match self
+            .sysfs_device()
+            .ok()
+            .as_ref()
+            .and_then(device_to_hid_device_info)
+        {
             Some(info) => Ok(info[0].clone()),
             None => Err(HidError::HidApiError {
                 message: "failed to create device info".into(),
@@ -594,25 +843,294 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
-        let devnum = fstat(self.fd.as_raw_fd())?.st_rdev;
-        let syspath: PathBuf = format!("/sys/dev/char/{}:{}", major(devnum), minor(devnum)).into();
-
-        let descriptor = HidrawReportDescriptor::from_syspath(&syspath)?;
+        let descriptor = match report_descriptor(self.fd.as_fd()) {
+            Ok(descriptor) => HidrawReportDescriptor(descriptor),
+            Err(_) => HidrawReportDescriptor::from_syspath(self.sysfs_device()?.syspath())?,
+        };
         let min_size = buf.len().min(descriptor.0.len());
         buf[..min_size].copy_from_slice(&descriptor.0[..min_size]);
         Ok(min_size)
     }
+
+    fn as_fd(&self) -> Option<BorrowedFd<'_>> {
+        Some(self.fd.as_fd())
+    }
+}
+
+/// Read the report descriptor through the hidraw file descriptor
+fn report_descriptor(fd: BorrowedFd<'_>) -> HidResult<Vec<u8>> {
+    let mut size = 0_i32;
+    unsafe { hidraw_ioc_grdescsize(fd.as_raw_fd(), &mut size) }?;
+
+    let mut descriptor = ioctl::HidrawReportDescriptor {
+        size: size as u32,
+        value: [0; HID_MAX_DESCRIPTOR_SIZE],
+    };
+    unsafe { hidraw_ioc_grdesc(fd.as_raw_fd(), &mut descriptor) }?;
+    let size = (descriptor.size as usize).min(HID_MAX_DESCRIPTOR_SIZE);
+    Ok(descriptor.value[..size].to_vec())
+}
+
+/// Call one of the ioctls that copy a NUL terminated string into the buffer
+fn string_ioctl(ioctl: impl FnOnce(&mut [u8]) -> nix::Result<libc::c_int>) -> HidResult<OsString> {
+    // The kernel keeps the name in 128 bytes, the others in 64
+    let mut buf = [0u8; 256];
+    let len = (ioctl(&mut buf)? as usize).min(buf.len());
+    let len = buf[..len].iter().position(|&b| b == 0).unwrap_or(len);
+    Ok(OsString::from_vec(buf[..len].to_vec()))
+}
+
+/// Send a feature report through the hidraw file descriptor
+pub(crate) fn send_feature_report(fd: BorrowedFd<'_>, data: &[u8]) -> HidResult<()> {
+    if data.is_empty() {
+        return Err(HidError::InvalidZeroSizeData);
+    }
+
+    let res = match unsafe { hidraw_ioc_set_feature(fd.as_raw_fd(), data) } {
+        Ok(n) => n as usize,
+        Err(e) => {
+            return Err(HidError::HidApiError {
+                message: format!("ioctl (GFEATURE): {e}"),
+            })
+        }
+    };
+
+    if res != data.len() {
+        return Err(HidError::IncompleteSendError {
+            sent: res,
+            all: data.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Get a feature report through the hidraw file descriptor
+pub(crate) fn get_feature_report(fd: BorrowedFd<'_>, buf: &mut [u8]) -> HidResult<usize> {
+    let res = match unsafe { hidraw_ioc_get_feature(fd.as_raw_fd(), buf) } {
+        Ok(n) => n as usize,
+        Err(e) => {
+            return Err(HidError::HidApiError {
+                message: format!("ioctl (GFEATURE): {e}"),
+            })
+        }
+    };
+
+    Ok(res)
+}
+
+/// Get an input report through the hidraw file descriptor
+pub(crate) fn get_input_report(fd: BorrowedFd<'_>, buf: &mut [u8]) -> HidResult<usize> {
+    match unsafe { hidraw_ioc_get_input(fd.as_raw_fd(), buf) } {
+        Ok(n) => Ok(n as usize),
+        Err(e) => Err(report_ioctl_error("GINPUT", e)),
+    }
+}
+
+/// Send an output report over the control endpoint through the hidraw file
+/// descriptor
+pub(crate) fn send_output_report(fd: BorrowedFd<'_>, data: &[u8]) -> HidResult<()> {
+    if data.is_empty() {
+        return Err(HidError::InvalidZeroSizeData);
+    }
+
+    let res = match unsafe { hidraw_ioc_set_output(fd.as_raw_fd(), data) } {
+        Ok(n) => n as usize,
+        Err(e) => return Err(report_ioctl_error("SOUTPUT", e)),
+    };
+
+    if res != data.len() {
+        return Err(HidError::IncompleteSendError {
+            sent: res,
+            all: data.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// hidraw answers ioctls it does not know with `ENOTTY`, which is the case
+/// for the input and output report ioctls before Linux 5.11
+fn report_ioctl_error(name: &str, e: Errno) -> HidError {
+    let message = match e {
+        Errno::ENOTTY => format!("ioctl ({name}): not supported, needs Linux 5.11 or newer"),
+        e => format!("ioctl ({name}): {e}"),
+    };
+    HidError::HidApiError { message }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::usage::{consumer, generic_desktop, Usage};
 
     #[test]
     fn test_parse_hid_vid_pid() {
         assert_eq!(None, parse_hid_vid_pid("Hello World"));
         assert_eq!(Some((1, 1, 1)), parse_hid_vid_pid("1:1:1"));
         assert_eq!(Some((0x11, 0x17, 0x18)), parse_hid_vid_pid("11:0017:00018"));
+        assert_eq!(
+            Some((0x03, 0x046D, 0xC52B)),
+            parse_hid_vid_pid("0003:0000046D:0000C52B")
+        );
+        assert_eq!(None, parse_hid_vid_pid("0003:0000046D"));
+        assert_eq!(None, parse_hid_vid_pid("0003:0001046D:0000C52B"));
+    }
+
+    #[test]
+    fn test_report_ioctl_error() {
+        // Like hidraw before Linux 5.11, /dev/null does not know the ioctls
+        let null = File::open("/dev/null").unwrap();
+        assert_eq!(
+            get_input_report(null.as_fd(), &mut [0x01, 0x00])
+                .unwrap_err()
+                .to_string(),
+            "hidapi error: ioctl (GINPUT): not supported, needs Linux 5.11 or newer"
+        );
+        assert_eq!(
+            send_output_report(null.as_fd(), &[0x01, 0x00])
+                .unwrap_err()
+                .to_string(),
+            "hidapi error: ioctl (SOUTPUT): not supported, needs Linux 5.11 or newer"
+        );
+        assert_eq!(
+            report_ioctl_error("GINPUT", Errno::EIO).to_string(),
+            "hidapi error: ioctl (GINPUT): EIO: I/O error"
+        );
+    }
+
+    #[test]
+    fn test_non_utf8_strings() {
+        let name = OsString::from_vec(b"Keyboard \xFF\xFE".to_vec());
+        assert_eq!(
+            Option::<String>::from(osstring_to_string(name.clone())).as_deref(),
+            Some("Keyboard \u{FFFD}\u{FFFD}")
+        );
+        let info = fill_in_other(
+            sysfs::enumerate(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sysfs")).remove(0),
+            name,
+        );
+        assert_eq!(info.product_string(), Some("Keyboard \u{FFFD}\u{FFFD}"));
+        assert_eq!(
+            physical_path_to_string(OsString::from_vec(b"usb-\x80".to_vec())).as_deref(),
+            Some("usb-\u{FFFD}")
+        );
+    }
+
+    #[test]
+    fn test_physical_path_to_string() {
+        assert_eq!(
+            physical_path_to_string("usb-0000:00:14.0-2/input2".into()).as_deref(),
+            Some("usb-0000:00:14.0-2/input2")
+        );
+        assert_eq!(physical_path_to_string("".into()), None);
+    }
+
+    #[test]
+    fn test_sysfs_enumerate() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sysfs");
+        let usages = |data: &[u8]| {
+            let descriptor = HidrawReportDescriptor::from_slice(data).unwrap();
+            descriptor.usages().collect::<Vec<_>>()
+        };
+
+        let receiver = DeviceInfo {
+            path: CString::new("/dev/hidraw0").unwrap(),
+            vendor_id: 0x046D,
+            product_id: 0xC52B,
+            serial_number: WcharString::String("".into()),
+            release_number: 0x1211,
+            manufacturer_string: WcharString::String("Logitech".into()),
+            product_string: WcharString::String("USB Receiver".into()),
+            usage_page: 0,
+            usage: 0,
+            interface_number: 2,
+            bus_type: BusType::Usb,
+            physical_path: Some("usb-0000:00:14.0-2/input2".into()),
+        };
+        let mouse = DeviceInfo {
+            path: CString::new("/dev/hidraw1").unwrap(),
+            vendor_id: 0x046D,
+            product_id: 0xB019,
+            serial_number: WcharString::String("11:22:33:44:55:66".into()),
+            release_number: 0,
+            manufacturer_string: WcharString::String("".into()),
+            product_string: WcharString::String("MX Master 2S".into()),
+            usage_page: 0,
+            usage: 0,
+            interface_number: -1,
+            bus_type: BusType::Bluetooth,
+            physical_path: Some("aa:bb:cc:dd:ee:ff".into()),
+        };
+        // One DeviceInfo per top level usage, and the device on the virtual
+        // bus is left out
+        let expected = usages(include_bytes!("../tests/assets/mouse2.data"))
+            .into_iter()
+            .map(|(usage_page, usage)| (&receiver, usage_page, usage))
+            .chain(
+                usages(include_bytes!("../tests/assets/mouse1.data"))
+                    .into_iter()
+                    .map(|(usage_page, usage)| (&mouse, usage_page, usage)),
+            )
+            .map(|(info, usage_page, usage)| DeviceInfo {
+                usage_page,
+                usage,
+                ..info.clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(sysfs::enumerate(&root), expected);
+
+        let api =
+            crate::HidApi::with_backend(crate::backend::LinuxSysfs::with_root(&root)).unwrap();
+        assert_eq!(api.device_list().count(), expected.len());
+        assert!(sysfs::enumerate(&root.join("missing")).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "linux-native")]
+    fn test_uevent_to_device_events() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sysfs");
+        let device = |name: &str| {
+            sysfs::SysfsDevice::new(&root, &root.join("class/hidraw").join(name)).unwrap()
+        };
+        let path = CString::new("/dev/hidraw1").unwrap();
+
+        // One event per top level usage, like the device list
+        let added = uevent_to_device_events(Some(OsStr::new("add")), &device("hidraw1"))
+            .into_iter()
+            .map(|event| match event {
+                DeviceEvent::Added(info) => info,
+                DeviceEvent::Removed(path) => panic!("removed {path:?}"),
+            })
+            .collect::<Vec<_>>();
+        let expected = sysfs::enumerate(&root)
+            .into_iter()
+            .filter(|info| info.path() == path.as_c_str())
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(added, expected);
+
+        let removed = uevent_to_device_events(Some(OsStr::new("remove")), &device("hidraw1"));
+        assert!(matches!(&removed[..], [DeviceEvent::Removed(removed)] if *removed == path));
+
+        // The device on the virtual bus is left out, like in the device list
+        assert!(uevent_to_device_events(Some(OsStr::new("add")), &device("hidraw2")).is_empty());
+        for action in [Some("change"), Some("bind"), None] {
+            let events = uevent_to_device_events(action.map(OsStr::new), &device("hidraw1"));
+            assert!(events.is_empty(), "{action:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "linux-native")]
+    fn test_monitor_backend() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sysfs");
+        let api =
+            crate::HidApi::with_backend(crate::backend::LinuxSysfs::with_root(&root)).unwrap();
+        match api.monitor() {
+            Err(e) => assert_eq!(e.to_string(), "hidapi error: monitor: not supported"),
+            Ok(_) => panic!("the sysfs backend cannot monitor devices"),
+        }
     }
 
     #[test]
@@ -630,7 +1148,147 @@ mod test {
         let desc = HidrawReportDescriptor::from_slice(&data[..]).expect("descriptor");
         let values = desc.usages().collect::<Vec<_>>();
 
-        let expected = vec![(1, 2), (1, 1), (1, 128), (12, 1), (65280, 14)];
-        assert_eq!(expected, values);
+        let expected = [
+            generic_desktop::MOUSE,
+            generic_desktop::POINTER,
+            generic_desktop::SYSTEM_CONTROL,
+            consumer::CONSUMER_CONTROL,
+            Usage::new(0xFF00, 0x0E),
+        ]
+        .map(|u| (u.page, u.id));
+        assert_eq!(expected, values[..]);
+    }
+}
+
+/// End-to-end tests against kernel devices
+///
+/// They need access to `/dev/uhid` and the hidraw nodes it creates, usually
+/// as root, and Linux 5.11 for the Input and Output report ioctls, so they
+/// are ignored by default. Run them with
+/// `cargo test --features linux-native,uhid -- --ignored`, like the CI does.
+#[cfg(all(test, feature = "uhid"))]
+mod uhid_test {
+    use super::*;
+    use crate::descriptor::ReportType;
+    use crate::uhid::{VirtualDevice, VirtualDeviceBuilder};
+    use std::time::{Duration, Instant};
+
+    /// Vendor defined Input, Output and Feature reports of two bytes with
+    /// report ID 1
+    const DESCRIPTOR: [u8; 30] = [
+        0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0x85, 0x01, 0x15, 0x00, 0x26, 0xFF, 0x00, 0x75,
+        0x08, 0x95, 0x02, 0x09, 0x02, 0x81, 0x02, 0x09, 0x03, 0x91, 0x02, 0x09, 0x04, 0xB1, 0x02,
+    ];
+
+    fn create(builder: VirtualDeviceBuilder) -> (VirtualDevice, CString) {
+        let mut descriptor = DESCRIPTOR.to_vec();
+        descriptor.push(0xC0);
+        let device = builder
+            .report_descriptor(&descriptor)
+            .build()
+            .unwrap_or_else(|e| panic!("cannot create a uhid device: {e}"));
+        let path = device.wait_for_hidraw(Duration::from_secs(5)).unwrap();
+        (device, path)
+    }
+
+    #[test]
+    #[ignore = "needs access to /dev/uhid"]
+    fn test_uhid_enumerate_and_open() {
+        let (_device, path) = create(
+            VirtualDevice::builder(0x1209, 0x0001)
+                .name("hidapi test device")
+                .phys("hidapi-test/input0")
+                .uniq("uhid-enumerate"),
+        );
+
+        let infos = crate::default_backend().enumerate(0x1209, 0x0001).unwrap();
+        let info = infos.iter().find(|info| info.path == path).unwrap();
+        assert_eq!(info.serial_number(), Some("uhid-enumerate"));
+        assert_eq!(info.product_string(), Some("hidapi test device"));
+        assert_eq!((info.usage_page, info.usage), (0xFF00, 0x01));
+        assert_eq!(info.bus_type, BusType::Usb);
+        assert_eq!(info.physical_path(), Some("hidapi-test/input0"));
+        assert!(sysfs::enumerate(Path::new(sysfs::SYSFS_ROOT)).contains(info));
+
+        let device = HidDevice::open_path(&path).unwrap();
+        assert_eq!(device.get_device_info().unwrap(), *info);
+        let mut buf = [0u8; 64];
+        let len = device.get_report_descriptor(&mut buf).unwrap();
+        assert_eq!(buf[..len - 1], DESCRIPTOR);
+    }
+
+    #[test]
+    #[ignore = "needs access to /dev/uhid"]
+    #[cfg(feature = "linux-native")]
+    fn test_uhid_monitor() {
+        let mut monitor = HidApiBackend::monitor().unwrap();
+        let (device, path) = create(VirtualDevice::builder(0x1209, 0x0003));
+
+        // The hidraw node exists, so its event is already queued
+        let added = monitor
+            .by_ref()
+            .find_map(|event| match event.unwrap() {
+                DeviceEvent::Added(info) if info.path() == path.as_c_str() => Some(info),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!((added.vendor_id(), added.product_id()), (0x1209, 0x0003));
+
+        drop(device);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            assert!(Instant::now() < deadline, "no Removed event for {path:?}");
+            if let Some(DeviceEvent::Removed(removed)) = monitor.next_event(1000).unwrap() {
+                if removed == path {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "needs access to /dev/uhid"]
+    fn test_uhid_reports() {
+        let (virtual_device, path) = create(VirtualDevice::builder(0x1209, 0x0002).on_get_report(
+            |report_type, report_id| match report_type {
+                ReportType::Feature => Ok(vec![report_id, 0x12, 0x34]),
+                ReportType::Input => Ok(vec![report_id, 0x56, 0x78]),
+                ReportType::Output => panic!("GET_REPORT for an Output report"),
+            },
+        ));
+        let device = HidDevice::open_path(&path).unwrap();
+
+        let mut buf = [0u8; 3];
+        assert_eq!(device.read_timeout(&mut buf, 0).unwrap(), 0);
+        virtual_device
+            .push_input_report(&[0x01, 0x05, 0x06])
+            .unwrap();
+        assert_eq!(device.read_timeout(&mut buf, 1000).unwrap(), 3);
+        assert_eq!(buf, [0x01, 0x05, 0x06]);
+
+        let mut feature = [0x01, 0x00, 0x00];
+        assert_eq!(device.get_feature_report(&mut feature).unwrap(), 3);
+        assert_eq!(feature, [0x01, 0x12, 0x34]);
+        device.send_feature_report(&[0x01, 0x07, 0x08]).unwrap();
+        assert_eq!(
+            virtual_device.sent_feature_reports(),
+            [vec![0x01, 0x07, 0x08]]
+        );
+
+        let mut input = [0x01, 0x00, 0x00];
+        assert_eq!(device.get_input_report(&mut input).unwrap(), 3);
+        assert_eq!(input, [0x01, 0x56, 0x78]);
+        device.send_output_report(&[0x01, 0x0B, 0x0C]).unwrap();
+        assert_eq!(
+            virtual_device.sent_output_reports(),
+            [vec![0x01, 0x0B, 0x0C]]
+        );
+
+        device.write(&[0x01, 0x09, 0x0A]).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while virtual_device.written_reports().is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(virtual_device.written_reports(), [vec![0x01, 0x09, 0x0A]]);
     }
 }
@@ -5,8 +5,36 @@ use nix::{ioctl_read, ioctl_read_buf, ioctl_write_buf};
 // From linux/hidraw.h
 const HIDRAW_IOC_MAGIC: u8 = b'H';
 const HIDRAW_IOC_GRDESCSIZE: u8 = 0x01;
+const HIDRAW_IOC_GRDESC: u8 = 0x02;
+const HIDRAW_IOC_GRAWINFO: u8 = 0x03;
+const HIDRAW_IOC_GRAWNAME: u8 = 0x04;
+const HIDRAW_IOC_GRAWPHYS: u8 = 0x05;
 const HIDRAW_SET_FEATURE: u8 = 0x06;
 const HIDRAW_GET_FEATURE: u8 = 0x07;
+// Since Linux 5.11
+const HIDRAW_GET_INPUT: u8 = 0x0A;
+const HIDRAW_SET_OUTPUT: u8 = 0x0B;
+// Since Linux 5.6
+const HIDRAW_IOC_GRAWUNIQ: u8 = 0x08;
+
+/// From linux/hid.h
+pub const HID_MAX_DESCRIPTOR_SIZE: usize = 4096;
+
+/// `struct hidraw_report_descriptor`
+#[repr(C)]
+pub struct HidrawReportDescriptor {
+    pub size: u32,
+    pub value: [u8; HID_MAX_DESCRIPTOR_SIZE],
+}
+
+/// `struct hidraw_devinfo`
+#[repr(C)]
+#[derive(Default)]
+pub struct HidrawDevinfo {
+    pub bustype: u32,
+    pub vendor: i16,
+    pub product: i16,
+}
 
 ioctl_read!(
     hidraw_ioc_grdescsize,
@@ -14,6 +42,38 @@ ioctl_read!(
     HIDRAW_IOC_GRDESCSIZE,
     libc::c_int
 );
+ioctl_read!(
+    hidraw_ioc_grdesc,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_IOC_GRDESC,
+    HidrawReportDescriptor
+);
+ioctl_read!(
+    hidraw_ioc_grawinfo,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_IOC_GRAWINFO,
+    HidrawDevinfo
+);
+
+// The length of the buffer is part of the request of these
+ioctl_read_buf!(
+    hidraw_ioc_grawname,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_IOC_GRAWNAME,
+    u8
+);
+ioctl_read_buf!(
+    hidraw_ioc_grawphys,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_IOC_GRAWPHYS,
+    u8
+);
+ioctl_read_buf!(
+    hidraw_ioc_grawuniq,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_IOC_GRAWUNIQ,
+    u8
+);
 
 ioctl_write_buf!(
     hidraw_ioc_set_feature,
@@ -27,3 +87,11 @@ ioctl_read_buf!(
     HIDRAW_GET_FEATURE,
     u8
 );
+
+ioctl_read_buf!(hidraw_ioc_get_input, HIDRAW_IOC_MAGIC, HIDRAW_GET_INPUT, u8);
+ioctl_write_buf!(
+    hidraw_ioc_set_output,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_SET_OUTPUT,
+    u8
+);
@@ -0,0 +1,431 @@
+//! Enumeration that reads sysfs directly instead of asking libudev
+//!
+//! The hidraw devices are listed in `class/hidraw`. The properties that udev
+//! reports for them come from the `uevent` files of the devices, and the
+//! attributes are the other files, so the devices give the same
+//! [`DeviceInfo`]s as with udev.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+
+use super::{device_to_hid_device_info, SysDevice};
+use crate::DeviceInfo;
+
+/// Where sysfs is mounted
+pub(crate) const SYSFS_ROOT: &str = "/sys";
+
+/// All hidraw devices below the sysfs `root`, in the order of their syspaths
+/// like udev
+pub(crate) fn enumerate(root: &Path) -> Vec<DeviceInfo> {
+    let Ok(entries) = fs::read_dir(root.join("class/hidraw")) else {
+        return Vec::new();
+    };
+    let mut devices = entries
+        .filter_map(|entry| SysfsDevice::new(root, &entry.ok()?.path()))
+        .collect::<Vec<_>>();
+    devices.sort_by(|a, b| a.syspath.cmp(&b.syspath));
+
+    devices
+        .iter()
+        .filter_map(device_to_hid_device_info)
+        .flatten()
+        .collect()
+}
+
+/// A device directory in sysfs
+pub(crate) struct SysfsDevice {
+    root: PathBuf,
+    syspath: PathBuf,
+}
+
+impl SysfsDevice {
+    /// The device at `path`, which may be a symlink like the ones in
+    /// `class/hidraw` and `dev/char`
+    pub(crate) fn new(root: &Path, path: &Path) -> Option<Self> {
+        Some(Self {
+            root: root.canonicalize().ok()?,
+            syspath: path.canonicalize().ok()?,
+        })
+    }
+
+    fn subsystem(&self) -> Option<OsString> {
+        let link = fs::read_link(self.syspath.join("subsystem")).ok()?;
+        link.file_name().map(OsStr::to_os_string)
+    }
+}
+
+impl SysDevice for SysfsDevice {
+    fn syspath(&self) -> &Path {
+        &self.syspath
+    }
+
+    fn devnode(&self) -> Option<PathBuf> {
+        self.property("DEVNAME")
+            .map(|name| Path::new("/dev").join(name))
+    }
+
+    fn property(&self, key: &str) -> Option<OsString> {
+        let uevent = fs::read(self.syspath.join("uevent")).ok()?;
+        uevent.split(|b| *b == b'\n').find_map(|line| {
+            let value = line.strip_prefix(key.as_bytes())?.strip_prefix(b"=")?;
+            Some(OsString::from_vec(value.to_vec()))
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<OsString> {
+        let mut value = fs::read(self.syspath.join(name)).ok()?;
+        // Like udev, drop the line break at the end
+        while value.last().is_some_and(|b| *b == b'\n' || *b == b'\r') {
+            value.pop();
+        }
+        Some(OsString::from_vec(value))
+    }
+
+    fn parent(&self, subsystem: &str, devtype: Option<&str>) -> Option<Self> {
+        self.syspath
+            .ancestors()
+            .skip(1)
+            .take_while(|path| path.starts_with(&self.root) && *path != self.root)
+            .map(|path| SysfsDevice {
+                root: self.root.clone(),
+                syspath: path.to_path_buf(),
+            })
+            .find(|device| {
+                device.subsystem().as_deref() == Some(OsStr::new(subsystem))
+                    && (devtype.is_none()
+                        || device.property("DEVTYPE").as_deref() == devtype.map(OsStr::new))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::symlink;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::linux_native::{BUS_BLUETOOTH, BUS_I2C, BUS_SPI, BUS_USB};
+    use crate::{BusType, HidApi, WcharString};
+
+    /// Major number of the hidraw device nodes
+    const HIDRAW_MAJOR: u32 = 245;
+
+    /// A HID device as the kernel describes it in its `uevent`
+    struct Hid<'a> {
+        bus: u16,
+        vendor_id: u16,
+        product_id: u16,
+        name: &'a str,
+        uniq: &'a str,
+        /// The usage page and usage of each top level collection
+        collections: &'a [(u16, u16)],
+    }
+
+    /// A sysfs tree in a temporary directory, with the files and links that
+    /// the enumeration reads
+    struct FakeSysfs {
+        root: PathBuf,
+        hidraw_count: u32,
+    }
+
+    impl FakeSysfs {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let index = NEXT.fetch_add(1, Ordering::Relaxed);
+            let root =
+                std::env::temp_dir().join(format!("hidapi-sysfs-{}-{index}", std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            for dir in [
+                "class/hidraw",
+                "dev/char",
+                "bus/hid",
+                "bus/usb",
+                "bus/i2c",
+                "bus/spi",
+            ] {
+                fs::create_dir_all(root.join(dir)).unwrap();
+            }
+            Self {
+                root,
+                hidraw_count: 0,
+            }
+        }
+
+        /// Create a device directory with a `uevent` file and a link to
+        /// its subsystem
+        fn device(&self, path: &str, subsystem: Option<&str>, uevent: &[String]) -> PathBuf {
+            let dir = self.root.join("devices").join(path);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("uevent"), uevent.concat()).unwrap();
+            if let Some(subsystem) = subsystem {
+                symlink(self.root.join(subsystem), dir.join("subsystem")).unwrap();
+            }
+            dir
+        }
+
+        /// A USB device with its string descriptors, below `port` of the
+        /// first root hub
+        fn usb_device(&self, port: &str, manufacturer: &str, product: &str, bcd: u16) -> PathBuf {
+            let dir = self.device(
+                &format!("pci0000:00/0000:00:14.0/usb1/{port}"),
+                Some("bus/usb"),
+                &["DEVTYPE=usb_device\n".into(), "DRIVER=usb\n".into()],
+            );
+            fs::write(dir.join("manufacturer"), format!("{manufacturer}\n")).unwrap();
+            fs::write(dir.join("product"), format!("{product}\n")).unwrap();
+            fs::write(dir.join("bcdDevice"), format!("{bcd:04x}\n")).unwrap();
+            dir
+        }
+
+        fn usb_interface(&self, usb_device: &Path, number: u8) -> PathBuf {
+            let port = usb_device.file_name().unwrap().to_str().unwrap();
+            let path = usb_device.strip_prefix(self.root.join("devices")).unwrap();
+            let dir = self.device(
+                &format!("{}/{port}:1.{number}", path.display()),
+                Some("bus/usb"),
+                &["DEVTYPE=usb_interface\n".into(), "DRIVER=usbhid\n".into()],
+            );
+            fs::write(dir.join("bInterfaceNumber"), format!("{number:02x}\n")).unwrap();
+            dir
+        }
+
+        /// A HID device below `parent`, with a hidraw device
+        fn hid(&mut self, parent: &Path, hid: &Hid) {
+            let index = self.hidraw_count;
+            self.hidraw_count += 1;
+
+            let parent = parent.strip_prefix(self.root.join("devices")).unwrap();
+            let (bus, vid, pid) = (hid.bus, hid.vendor_id, hid.product_id);
+            let dir = self.device(
+                &format!(
+                    "{}/{bus:04X}:{vid:04X}:{pid:04X}.{:04X}",
+                    parent.display(),
+                    index + 1
+                ),
+                Some("bus/hid"),
+                &[
+                    "DRIVER=hid-generic\n".into(),
+                    format!("HID_ID={bus:04X}:{vid:08X}:{pid:08X}\n"),
+                    format!("HID_NAME={}\n", hid.name),
+                    format!("HID_PHYS=phys-{index}\n"),
+                    format!("HID_UNIQ={}\n", hid.uniq),
+                ],
+            );
+            fs::write(
+                dir.join("report_descriptor"),
+                report_descriptor(hid.collections),
+            )
+            .unwrap();
+
+            let hidraw = dir.join(format!("hidraw/hidraw{index}"));
+            fs::create_dir_all(&hidraw).unwrap();
+            fs::write(
+                hidraw.join("uevent"),
+                format!("MAJOR={HIDRAW_MAJOR}\nMINOR={index}\nDEVNAME=hidraw{index}\n"),
+            )
+            .unwrap();
+            symlink(self.root.join("class/hidraw"), hidraw.join("subsystem")).unwrap();
+            symlink(&dir, hidraw.join("device")).unwrap();
+            symlink(
+                &hidraw,
+                self.root.join(format!("class/hidraw/hidraw{index}")),
+            )
+            .unwrap();
+            symlink(
+                &hidraw,
+                self.root.join(format!("dev/char/{HIDRAW_MAJOR}:{index}")),
+            )
+            .unwrap();
+        }
+    }
+
+    impl Drop for FakeSysfs {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    /// A report descriptor with empty top level collections
+    fn report_descriptor(collections: &[(u16, u16)]) -> Vec<u8> {
+        collections
+            .iter()
+            .flat_map(|&(usage_page, usage)| {
+                let [page_lo, page_hi] = usage_page.to_le_bytes();
+                [0x06, page_lo, page_hi, 0x09, usage as u8, 0xA1, 0x01, 0xC0]
+            })
+            .collect()
+    }
+
+    fn info(
+        index: u32,
+        (vendor_id, product_id): (u16, u16),
+        (usage_page, usage): (u16, u16),
+        bus_type: BusType,
+    ) -> DeviceInfo {
+        DeviceInfo {
+            path: std::ffi::CString::new(format!("/dev/hidraw{index}")).unwrap(),
+            vendor_id,
+            product_id,
+            serial_number: WcharString::String("".into()),
+            release_number: 0,
+            manufacturer_string: WcharString::String("".into()),
+            product_string: WcharString::None,
+            usage_page,
+            usage,
+            interface_number: -1,
+            bus_type,
+            physical_path: Some(format!("phys-{index}")),
+        }
+    }
+
+    #[test]
+    fn test_enumerate_fake_sysfs() {
+        let mut sysfs = FakeSysfs::new();
+
+        let keyboard = sysfs.usb_device("1-3", "Logitech", "USB Keyboard", 0x6400);
+        let keys = Hid {
+            bus: BUS_USB,
+            vendor_id: 0x046D,
+            product_id: 0xC31C,
+            name: "Logitech USB Keyboard",
+            uniq: "",
+            collections: &[(0x01, 0x06)],
+        };
+        sysfs.hid(&sysfs.usb_interface(&keyboard, 0), &keys);
+        let media_keys = Hid {
+            collections: &[(0x0C, 0x01), (0x01, 0x80)],
+            ..keys
+        };
+        sysfs.hid(&sysfs.usb_interface(&keyboard, 1), &media_keys);
+
+        let ble = sysfs.device("virtual/misc/uhid", Some("class/misc"), &[]);
+        sysfs.hid(
+            &ble,
+            &Hid {
+                bus: BUS_BLUETOOTH,
+                vendor_id: 0x046D,
+                product_id: 0xB025,
+                name: "MX Anywhere 3",
+                uniq: "d2:1f:aa:bb:cc:dd",
+                collections: &[(0x01, 0x02)],
+            },
+        );
+
+        let i2c = sysfs.device(
+            "platform/AMDI0010:01/i2c-1/i2c-ELAN0001:00",
+            Some("bus/i2c"),
+            &["DRIVER=i2c_hid_acpi\n".into()],
+        );
+        sysfs.hid(
+            &i2c,
+            &Hid {
+                bus: BUS_I2C,
+                vendor_id: 0x04F3,
+                product_id: 0x3195,
+                name: "ELAN0001:00 04F3:3195",
+                uniq: "",
+                collections: &[(0x01, 0x02), (0x0D, 0x05)],
+            },
+        );
+
+        let spi = sysfs.device(
+            "pci0000:00/0000:00:1e.3/pxa2xx-spi.3/spi_master/spi2/spi-APP000D:00",
+            Some("bus/spi"),
+            &["DRIVER=applespi\n".into()],
+        );
+        sysfs.hid(
+            &spi,
+            &Hid {
+                bus: BUS_SPI,
+                vendor_id: 0x05AC,
+                product_id: 0x0277,
+                name: "Apple SPI Keyboard",
+                uniq: "",
+                collections: &[(0x01, 0x06)],
+            },
+        );
+
+        // Virtual devices are left out, like by the C library
+        sysfs.hid(
+            &ble,
+            &Hid {
+                bus: 0x06,
+                vendor_id: 0x1209,
+                product_id: 0x0001,
+                name: "Virtual",
+                uniq: "",
+                collections: &[(0x01, 0x02)],
+            },
+        );
+
+        let usb_keyboard = |index, usage, interface_number| DeviceInfo {
+            release_number: 0x6400,
+            manufacturer_string: WcharString::String("Logitech".into()),
+            product_string: WcharString::String("USB Keyboard".into()),
+            interface_number,
+            ..info(index, (0x046D, 0xC31C), usage, BusType::Usb)
+        };
+        let named = |info: DeviceInfo, name: &str| DeviceInfo {
+            product_string: WcharString::String(name.into()),
+            ..info
+        };
+        let spi_keyboard = info(4, (0x05AC, 0x0277), (0x01, 0x06), BusType::Spi);
+        let touchpad = info(3, (0x04F3, 0x3195), (0x01, 0x02), BusType::I2c);
+        let mouse = DeviceInfo {
+            serial_number: WcharString::String("d2:1f:aa:bb:cc:dd".into()),
+            ..info(2, (0x046D, 0xB025), (0x01, 0x02), BusType::Bluetooth)
+        };
+        // In the order of the syspaths
+        let expected = [
+            usb_keyboard(0, (0x01, 0x06), 0),
+            usb_keyboard(1, (0x0C, 0x01), 1),
+            usb_keyboard(1, (0x01, 0x80), 1),
+            named(spi_keyboard, "Apple SPI Keyboard"),
+            named(touchpad.clone(), "ELAN0001:00 04F3:3195"),
+            named(
+                DeviceInfo {
+                    usage_page: 0x0D,
+                    usage: 0x05,
+                    ..touchpad
+                },
+                "ELAN0001:00 04F3:3195",
+            ),
+            named(mouse, "MX Anywhere 3"),
+        ];
+
+        let api = HidApi::with_backend(crate::backend::LinuxSysfs::with_root(&sysfs.root)).unwrap();
+        assert_eq!(api.device_list().cloned().collect::<Vec<_>>(), expected);
+
+        // get_device_info() starts from the device number of the hidraw node
+        let device = SysfsDevice::new(&sysfs.root, &sysfs.root.join("dev/char/245:1")).unwrap();
+        assert_eq!(device_to_hid_device_info(&device).unwrap(), expected[1..3]);
+    }
+
+    #[test]
+    fn test_usb_without_usb_parent() {
+        // A USB HID device whose USB parents are missing, like uhid devices
+        // created with the USB bus type
+        let mut sysfs = FakeSysfs::new();
+        let uhid = sysfs.device("virtual/misc/uhid", Some("class/misc"), &[]);
+        sysfs.hid(
+            &uhid,
+            &Hid {
+                bus: BUS_USB,
+                vendor_id: 0x1209,
+                product_id: 0x0001,
+                name: "uhid device",
+                uniq: "1234",
+                collections: &[(0xFF00, 0x01)],
+            },
+        );
+
+        let expected = DeviceInfo {
+            serial_number: WcharString::String("1234".into()),
+            product_string: WcharString::String("uhid device".into()),
+            ..info(0, (0x1209, 0x0001), (0xFF00, 0x01), BusType::Usb)
+        };
+        assert_eq!(enumerate(&sysfs.root), [expected]);
+    }
+}
@@ -0,0 +1,793 @@
+//! In-memory devices to test code that uses [`HidDevice`] without hardware
+//!
+//! A [`VirtualDevice`] is described by a [`DeviceInfo`] and a report
+//! descriptor, and opened into a genuine [`HidDevice`]. Writes and Feature
+//! reports are answered from scripted reports or by closures, and the test
+//! injects Input reports and disconnects through the [`VirtualDevice`] handle.
+//!
+//! ```
+//! use hidapi::mock::{MockHidApi, VirtualDevice};
+//!
+//! let device = VirtualDevice::builder(0x046D, 0xC534)
+//!     .product_string("Virtual Receiver")
+//!     .feature_report(&[0x10, 0x01, 0x02])
+//!     // Answer every Output report with an Input report
+//!     .on_write(|device, data| {
+//!         device.push_input_report(&[0x11, data[1]]);
+//!         Ok(data.len())
+//!     })
+//!     .build();
+//!
+//! let mut api = MockHidApi::new();
+//! api.add_device(device.clone());
+//! let hid = api.open(0x046D, 0xC534).unwrap();
+//!
+//! hid.write(&[0x10, 0x42]).unwrap();
+//! let mut buf = [0u8; 8];
+//! assert_eq!(hid.read(&mut buf).unwrap(), 2);
+//! assert_eq!(buf[..2], [0x11, 0x42]);
+//!
+//! let mut feature = [0x10, 0, 0];
+//! assert_eq!(hid.get_feature_report(&mut feature).unwrap(), 3);
+//! assert_eq!(feature, [0x10, 0x01, 0x02]);
+//!
+//! device.disconnect();
+//! assert!(hid.read(&mut buf).is_err());
+//! ```
+
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::{Backend, BusType, DeviceBackend, DeviceInfo, HidDevice, HidError, HidResult};
+
+type WriteHandler = Box<dyn FnMut(&VirtualDevice, &[u8]) -> HidResult<usize> + Send>;
+type GetReportHandler = Box<dyn FnMut(&VirtualDevice, &mut [u8]) -> HidResult<usize> + Send>;
+type SendReportHandler = Box<dyn FnMut(&VirtualDevice, &[u8]) -> HidResult<()> + Send>;
+
+/// Used for the default paths, so every virtual device has a unique one
+static NEXT_DEVICE_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// Describes a [`VirtualDevice`], see [`VirtualDevice::builder`]
+pub struct VirtualDeviceBuilder {
+    info: DeviceInfo,
+    report_descriptor: Vec<u8>,
+    feature_reports: HashMap<u8, Vec<u8>>,
+    input_reports: HashMap<u8, Vec<u8>>,
+    handlers: Handlers,
+}
+
+impl VirtualDeviceBuilder {
+    /// The path used to open the device, `mock:<n>` by default
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path contains a NUL byte.
+    pub fn path(mut self, path: &str) -> Self {
+        self.info.path = CString::new(path).expect("path contains a NUL byte");
+        self
+    }
+
+    pub fn serial_number(mut self, serial_number: &str) -> Self {
+        self.info = self.info.with_serial_number(serial_number);
+        self
+    }
+
+    pub fn manufacturer_string(mut self, manufacturer_string: &str) -> Self {
+        self.info = self.info.with_manufacturer_string(manufacturer_string);
+        self
+    }
+
+    pub fn product_string(mut self, product_string: &str) -> Self {
+        self.info = self.info.with_product_string(product_string);
+        self
+    }
+
+    pub fn release_number(mut self, release_number: u16) -> Self {
+        self.info = self.info.with_release_number(release_number);
+        self
+    }
+
+    /// The usage of the top level collection
+    pub fn usage(mut self, usage_page: u16, usage: u16) -> Self {
+        self.info = self.info.with_usage(usage_page, usage);
+        self
+    }
+
+    pub fn interface_number(mut self, interface_number: i32) -> Self {
+        self.info = self.info.with_interface_number(interface_number);
+        self
+    }
+
+    pub fn bus_type(mut self, bus_type: BusType) -> Self {
+        self.info = self.info.with_bus_type(bus_type);
+        self
+    }
+
+    pub fn report_descriptor(mut self, report_descriptor: &[u8]) -> Self {
+        self.report_descriptor = report_descriptor.to_vec();
+        self
+    }
+
+    /// A Feature report returned by [`HidDevice::get_feature_report`] for the
+    /// report ID in its first byte
+    ///
+    /// Sending a Feature report replaces the report with the same ID.
+    pub fn feature_report(mut self, data: &[u8]) -> Self {
+        if let Some(report_id) = data.first() {
+            self.feature_reports.insert(*report_id, data.to_vec());
+        }
+        self
+    }
+
+    /// An Input report returned by [`HidDevice::get_input_report`] for the
+    /// report ID in its first byte
+    ///
+    /// Unlike [`VirtualDevice::push_input_report`] the report is not
+    /// consumed, like the current state of a device.
+    pub fn input_report(mut self, data: &[u8]) -> Self {
+        if let Some(report_id) = data.first() {
+            self.input_reports.insert(*report_id, data.to_vec());
+        }
+        self
+    }
+
+    /// Handle [`HidDevice::write`] instead of accepting all Output reports
+    pub fn on_write(
+        mut self,
+        handler: impl FnMut(&VirtualDevice, &[u8]) -> HidResult<usize> + Send + 'static,
+    ) -> Self {
+        self.handlers.write = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle [`HidDevice::get_feature_report`] instead of returning the
+    /// scripted Feature reports
+    pub fn on_get_feature_report(
+        mut self,
+        handler: impl FnMut(&VirtualDevice, &mut [u8]) -> HidResult<usize> + Send + 'static,
+    ) -> Self {
+        self.handlers.get_feature_report = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle [`HidDevice::send_feature_report`] instead of storing the
+    /// Feature reports
+    pub fn on_send_feature_report(
+        mut self,
+        handler: impl FnMut(&VirtualDevice, &[u8]) -> HidResult<()> + Send + 'static,
+    ) -> Self {
+        self.handlers.send_feature_report = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle [`HidDevice::get_input_report`] instead of returning the
+    /// scripted Input reports
+    pub fn on_get_input_report(
+        mut self,
+        handler: impl FnMut(&VirtualDevice, &mut [u8]) -> HidResult<usize> + Send + 'static,
+    ) -> Self {
+        self.handlers.get_input_report = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle [`HidDevice::send_output_report`] instead of accepting all
+    /// Output reports
+    pub fn on_send_output_report(
+        mut self,
+        handler: impl FnMut(&VirtualDevice, &[u8]) -> HidResult<()> + Send + 'static,
+    ) -> Self {
+        self.handlers.send_output_report = Some(Box::new(handler));
+        self
+    }
+
+    pub fn build(self) -> VirtualDevice {
+        #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+        let (signal_sender, signal) = signal::pair();
+
+        VirtualDevice {
+            shared: Arc::new(Shared {
+                info: self.info,
+                report_descriptor: self.report_descriptor,
+                state: Mutex::new(State {
+                    connected: true,
+                    input_reports: VecDeque::new(),
+                    written_reports: Vec::new(),
+                    sent_feature_reports: Vec::new(),
+                    sent_output_reports: Vec::new(),
+                    feature_reports: self.feature_reports,
+                    polled_input_reports: self.input_reports,
+                    handlers: self.handlers,
+                    #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+                    signal_sender: Some(signal_sender),
+                }),
+                input_available: Condvar::new(),
+                #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+                signal,
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for VirtualDeviceBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualDeviceBuilder")
+            .field("info", &self.info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Handle to control a virtual device and inspect what was sent to it
+///
+/// Clones refer to the same device.
+#[derive(Clone)]
+pub struct VirtualDevice {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    info: DeviceInfo,
+    report_descriptor: Vec<u8>,
+    state: Mutex<State>,
+    input_available: Condvar,
+    /// Readable while Input reports are queued or after a disconnect
+    #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+    signal: std::os::unix::net::UnixStream,
+}
+
+struct State {
+    connected: bool,
+    input_reports: VecDeque<Vec<u8>>,
+    written_reports: Vec<Vec<u8>>,
+    sent_feature_reports: Vec<Vec<u8>>,
+    sent_output_reports: Vec<Vec<u8>>,
+    feature_reports: HashMap<u8, Vec<u8>>,
+    /// The Input reports for `get_input_report`, by report ID
+    polled_input_reports: HashMap<u8, Vec<u8>>,
+    handlers: Handlers,
+    #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+    signal_sender: Option<std::os::unix::net::UnixStream>,
+}
+
+#[derive(Default)]
+struct Handlers {
+    write: Option<WriteHandler>,
+    get_feature_report: Option<GetReportHandler>,
+    send_feature_report: Option<SendReportHandler>,
+    get_input_report: Option<GetReportHandler>,
+    send_output_report: Option<SendReportHandler>,
+}
+
+impl VirtualDevice {
+    /// Start describing a virtual device
+    pub fn builder(vendor_id: u16, product_id: u16) -> VirtualDeviceBuilder {
+        let index = NEXT_DEVICE_INDEX.fetch_add(1, Ordering::Relaxed);
+        VirtualDeviceBuilder {
+            info: DeviceInfo::new(
+                CString::new(format!("mock:{index}")).unwrap(),
+                vendor_id,
+                product_id,
+            ),
+            report_descriptor: Vec::new(),
+            feature_reports: HashMap::new(),
+            input_reports: HashMap::new(),
+            handlers: Handlers::default(),
+        }
+    }
+
+    pub fn info(&self) -> &DeviceInfo {
+        &self.shared.info
+    }
+
+    /// Open the device, fails after [`disconnect`](VirtualDevice::disconnect)
+    pub fn open(&self) -> HidResult<HidDevice> {
+        if !self.is_connected() {
+            return Err(disconnected());
+        }
+        Ok(HidDevice::from_backend(Box::new(VirtualHandle {
+            device: self.clone(),
+            blocking: Cell::new(true),
+        })))
+    }
+
+    /// Queue an Input report for [`HidDevice::read`]
+    ///
+    /// The first byte must be the report ID if the report descriptor uses
+    /// report IDs, as it is returned by `read()` unchanged.
+    pub fn push_input_report(&self, data: &[u8]) {
+        let mut state = self.state();
+        if !state.connected {
+            return;
+        }
+        state.input_reports.push_back(data.to_vec());
+        #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+        if let Some(sender) = &state.signal_sender {
+            signal::raise(sender);
+        }
+        self.shared.input_available.notify_all();
+    }
+
+    /// Simulate unplugging the device: pending and future calls on opened
+    /// devices fail
+    pub fn disconnect(&self) {
+        let mut state = self.state();
+        state.connected = false;
+        state.input_reports.clear();
+        #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+        state.signal_sender.take();
+        self.shared.input_available.notify_all();
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state().connected
+    }
+
+    /// All reports passed to [`HidDevice::write`], including the report ID
+    pub fn written_reports(&self) -> Vec<Vec<u8>> {
+        self.state().written_reports.clone()
+    }
+
+    /// All reports passed to [`HidDevice::send_feature_report`], including
+    /// the report ID
+    pub fn sent_feature_reports(&self) -> Vec<Vec<u8>> {
+        self.state().sent_feature_reports.clone()
+    }
+
+    /// All reports passed to [`HidDevice::send_output_report`], including
+    /// the report ID
+    pub fn sent_output_reports(&self) -> Vec<Vec<u8>> {
+        self.state().sent_output_reports.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panicking handler must not break the remaining assertions of a test
+        self.shared.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn connected_state(&self) -> HidResult<MutexGuard<'_, State>> {
+        let state = self.state();
+        if state.connected {
+            Ok(state)
+        } else {
+            Err(disconnected())
+        }
+    }
+
+    fn check_connected(&self) -> HidResult<()> {
+        self.connected_state().map(drop)
+    }
+
+    /// Call a handler without holding the lock, so it can use the device
+    fn with_handler<H, R>(
+        &self,
+        handler: fn(&mut Handlers) -> &mut Option<H>,
+        call: impl FnOnce(&mut H) -> R,
+    ) -> Option<R> {
+        let mut taken = handler(&mut self.state().handlers).take()?;
+        let result = call(&mut taken);
+        *handler(&mut self.state().handlers) = Some(taken);
+        Some(result)
+    }
+}
+
+impl fmt::Debug for VirtualDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualDevice")
+            .field("info", &self.shared.info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Stands in for [`HidApi`](crate::HidApi) to enumerate and open virtual devices
+///
+/// It is also a [`Backend`], so code that takes a [`HidApi`](crate::HidApi)
+/// can be tested with [`HidApi::with_backend`](crate::HidApi::with_backend).
+#[derive(Debug, Default)]
+pub struct MockHidApi {
+    devices: Vec<VirtualDevice>,
+}
+
+impl MockHidApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_device(&mut self, device: VirtualDevice) {
+        self.devices.push(device);
+    }
+
+    /// The devices that are still connected
+    pub fn device_list(&self) -> impl Iterator<Item = &DeviceInfo> {
+        self.connected_devices().map(VirtualDevice::info)
+    }
+
+    /// Open the first connected device with the given VID and PID
+    pub fn open(&self, vid: u16, pid: u16) -> HidResult<HidDevice> {
+        self.find(|info| info.vendor_id == vid && info.product_id == pid)
+    }
+
+    pub fn open_serial(&self, vid: u16, pid: u16, sn: &str) -> HidResult<HidDevice> {
+        self.find(|info| {
+            info.vendor_id == vid && info.product_id == pid && info.serial_number() == Some(sn)
+        })
+    }
+
+    pub fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        self.find(|info| info.path() == device_path)
+    }
+
+    fn connected_devices(&self) -> impl Iterator<Item = &VirtualDevice> {
+        self.devices.iter().filter(|d| d.is_connected())
+    }
+
+    fn find(&self, predicate: impl Fn(&DeviceInfo) -> bool) -> HidResult<HidDevice> {
+        self.connected_devices()
+            .find(|d| predicate(d.info()))
+            .ok_or_else(|| HidError::HidApiError {
+                message: "device not found".into(),
+            })?
+            .open()
+    }
+}
+
+impl Backend for MockHidApi {
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        Ok(self
+            .device_list()
+            .filter(|info| vid == 0 || info.vendor_id == vid)
+            .filter(|info| pid == 0 || info.product_id == pid)
+            .cloned()
+            .collect())
+    }
+
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        MockHidApi::open_path(self, device_path)
+    }
+}
+
+/// An opened virtual device, the backend of the [`HidDevice`]
+struct VirtualHandle {
+    device: VirtualDevice,
+    blocking: Cell<bool>,
+}
+
+impl VirtualHandle {
+    /// Answer a GET_REPORT by the handler, or from the scripted reports
+    fn get_report(
+        &self,
+        buf: &mut [u8],
+        kind: &str,
+        handler: fn(&mut Handlers) -> &mut Option<GetReportHandler>,
+        reports: fn(&State) -> &HashMap<u8, Vec<u8>>,
+    ) -> HidResult<usize> {
+        if buf.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+        self.device.check_connected()?;
+
+        let device = &self.device;
+        device
+            .with_handler(handler, |handler| handler(device, buf))
+            .unwrap_or_else(|| {
+                let state = device.state();
+                let report = reports(&state)
+                    .get(&buf[0])
+                    .ok_or_else(|| HidError::HidApiError {
+                        message: format!("no {kind} report with ID {}", buf[0]),
+                    })?;
+                let len = report.len().min(buf.len());
+                buf[..len].copy_from_slice(&report[..len]);
+                Ok(len)
+            })
+    }
+}
+
+impl DeviceBackend for VirtualHandle {
+    fn write(&self, data: &[u8]) -> HidResult<usize> {
+        if data.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+        self.device
+            .connected_state()?
+            .written_reports
+            .push(data.to_vec());
+
+        let device = &self.device;
+        device
+            .with_handler(|h| &mut h.write, |handler| handler(device, data))
+            .unwrap_or(Ok(data.len()))
+    }
+
+    fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let timeout = if self.blocking.get() { -1 } else { 0 };
+        self.read_timeout(buf, timeout)
+    }
+
+    fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        let deadline =
+            (timeout > 0).then(|| Instant::now() + Duration::from_millis(timeout as u64));
+        let mut state = self.device.connected_state()?;
+        loop {
+            if let Some(report) = state.input_reports.pop_front() {
+                #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+                signal::consume(&self.device.shared.signal);
+                let len = report.len().min(buf.len());
+                buf[..len].copy_from_slice(&report[..len]);
+                return Ok(len);
+            }
+
+            let condvar = &self.device.shared.input_available;
+            state = match deadline {
+                _ if timeout == 0 => return Ok(0),
+                None => condvar.wait(state).unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                        return Ok(0);
+                    };
+                    condvar
+                        .wait_timeout(state, remaining)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+            if !state.connected {
+                return Err(disconnected());
+            }
+        }
+    }
+
+    fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+        if data.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+        self.device
+            .connected_state()?
+            .sent_feature_reports
+            .push(data.to_vec());
+
+        let device = &self.device;
+        device
+            .with_handler(
+                |h| &mut h.send_feature_report,
+                |handler| handler(device, data),
+            )
+            .unwrap_or_else(|| {
+                device
+                    .state()
+                    .feature_reports
+                    .insert(data[0], data.to_vec());
+                Ok(())
+            })
+    }
+
+    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.get_report(
+            buf,
+            "feature",
+            |h| &mut h.get_feature_report,
+            |s| &s.feature_reports,
+        )
+    }
+
+    fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.get_report(
+            buf,
+            "input",
+            |h| &mut h.get_input_report,
+            |s| &s.polled_input_reports,
+        )
+    }
+
+    fn send_output_report(&self, data: &[u8]) -> HidResult<()> {
+        if data.is_empty() {
+            return Err(HidError::InvalidZeroSizeData);
+        }
+        self.device
+            .connected_state()?
+            .sent_output_reports
+            .push(data.to_vec());
+
+        let device = &self.device;
+        device
+            .with_handler(
+                |h| &mut h.send_output_report,
+                |handler| handler(device, data),
+            )
+            .unwrap_or(Ok(()))
+    }
+
+    fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
+        self.blocking.set(blocking);
+        Ok(())
+    }
+
+    fn get_device_info(&self) -> HidResult<DeviceInfo> {
+        Ok(self.device.info().clone())
+    }
+
+    fn get_manufacturer_string(&self) -> HidResult<Option<String>> {
+        Ok(self.device.info().manufacturer_string().map(str::to_string))
+    }
+
+    fn get_product_string(&self) -> HidResult<Option<String>> {
+        Ok(self.device.info().product_string().map(str::to_string))
+    }
+
+    fn get_serial_number_string(&self) -> HidResult<Option<String>> {
+        Ok(self.device.info().serial_number().map(str::to_string))
+    }
+
+    fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.device.check_connected()?;
+        let descriptor = &self.device.shared.report_descriptor;
+        let len = descriptor.len().min(buf.len());
+        buf[..len].copy_from_slice(&descriptor[..len]);
+        Ok(len)
+    }
+
+    #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd<'_>> {
+        Some(std::os::fd::AsFd::as_fd(&self.device.shared.signal))
+    }
+}
+
+fn disconnected() -> HidError {
+    HidError::IoError {
+        error: io::Error::new(io::ErrorKind::NotConnected, "virtual device disconnected"),
+    }
+}
+
+/// A socket pair that makes virtual devices pollable like hidraw devices
+///
+/// Each queued Input report is one byte in the socket, and dropping the
+/// sending end on disconnect makes the receiving end readable for good.
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+mod signal {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    pub(super) fn pair() -> (UnixStream, UnixStream) {
+        let (sender, receiver) = UnixStream::pair().expect("failed to create a socket pair");
+        sender.set_nonblocking(true).unwrap();
+        receiver.set_nonblocking(true).unwrap();
+        (sender, receiver)
+    }
+
+    pub(super) fn raise(mut sender: &UnixStream) {
+        let _ = sender.write(&[0]);
+    }
+
+    pub(super) fn consume(mut receiver: &UnixStream) {
+        let _ = receiver.read(&mut [0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enumerate_and_open() {
+        let mut api = MockHidApi::new();
+        let mouse = VirtualDevice::builder(0x046D, 0xC077)
+            .path("/dev/hidraw7")
+            .serial_number("1234")
+            .usage(0x01, 0x02)
+            .build();
+        api.add_device(mouse.clone());
+        api.add_device(VirtualDevice::builder(0x046D, 0xC534).build());
+
+        assert_eq!(api.device_list().count(), 2);
+        let device = api.open_serial(0x046D, 0xC077, "1234").unwrap();
+        assert_eq!(
+            device.get_serial_number_string().unwrap().as_deref(),
+            Some("1234")
+        );
+        assert!(api
+            .open_path(&CString::new("/dev/hidraw7").unwrap())
+            .is_ok());
+        assert!(api.open_serial(0x046D, 0xC077, "5678").is_err());
+
+        mouse.disconnect();
+        assert_eq!(api.device_list().count(), 1);
+        assert!(api.open(0x046D, 0xC077).is_err());
+    }
+
+    #[test]
+    fn test_hidapi_with_backend() {
+        let mut mock = MockHidApi::new();
+        let keyboard = VirtualDevice::builder(0x046D, 0xC31C)
+            .serial_number("1234")
+            .build();
+        mock.add_device(keyboard.clone());
+        mock.add_device(VirtualDevice::builder(0x046D, 0xC077).build());
+
+        let mut api = crate::HidApi::with_backend(mock).unwrap();
+        assert_eq!(api.device_list().count(), 2);
+        let device = api.open_serial(0x046D, 0xC31C, "1234").unwrap();
+        assert_eq!(device.get_device_info().unwrap(), *keyboard.info());
+
+        keyboard.disconnect();
+        let diff = api.refresh_devices_diff().unwrap();
+        assert_eq!(diff.removed, [keyboard.info().clone()]);
+        assert!(api.open(0x046D, 0xC31C).is_err());
+    }
+
+    #[test]
+    fn test_reports() {
+        let device = VirtualDevice::builder(0x046D, 0xC534)
+            .report_descriptor(&[0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0xC0])
+            .on_write(|device, data| {
+                device.push_input_report(data);
+                Ok(data.len())
+            })
+            .build();
+        let hid = device.open().unwrap();
+
+        let mut buf = [0u8; 4];
+        hid.set_blocking_mode(false).unwrap();
+        assert_eq!(hid.read(&mut buf).unwrap(), 0);
+        assert_eq!(hid.read_timeout(&mut buf, 10).unwrap(), 0);
+
+        hid.write(&[0x01, 0x02]).unwrap();
+        assert_eq!(hid.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [0x01, 0x02]);
+        assert_eq!(device.written_reports(), [vec![0x01, 0x02]]);
+
+        hid.send_feature_report(&[0x03, 0x04]).unwrap();
+        let mut feature = [0x03, 0x00];
+        assert_eq!(hid.get_feature_report(&mut feature).unwrap(), 2);
+        assert_eq!(feature, [0x03, 0x04]);
+        assert!(hid.get_feature_report(&mut [0x05, 0x00]).is_err());
+
+        assert_eq!(hid.report_descriptor().unwrap().collections().len(), 1);
+    }
+
+    #[test]
+    fn test_control_reports() {
+        let device = VirtualDevice::builder(0x046D, 0xC534)
+            .input_report(&[0x02, 0x64, 0x01])
+            .on_send_output_report(|_, data| match data[0] {
+                0x04 => Ok(()),
+                _ => Err(HidError::HidApiError {
+                    message: "stall".into(),
+                }),
+            })
+            .build();
+        let hid = device.open().unwrap();
+
+        let mut input = [0x02, 0x00, 0x00];
+        assert_eq!(hid.get_input_report(&mut input).unwrap(), 3);
+        assert_eq!(input, [0x02, 0x64, 0x01]);
+        // The report is still there, and not queued for read()
+        assert_eq!(hid.get_input_report(&mut input).unwrap(), 3);
+        hid.set_blocking_mode(false).unwrap();
+        assert_eq!(hid.read(&mut input).unwrap(), 0);
+        assert!(hid.get_input_report(&mut [0x03, 0x00]).is_err());
+
+        hid.send_output_report(&[0x04, 0x01]).unwrap();
+        assert!(hid.send_output_report(&[0x05, 0x01]).is_err());
+        assert_eq!(
+            device.sent_output_reports(),
+            [vec![0x04, 0x01], vec![0x05, 0x01]]
+        );
+        assert!(device.written_reports().is_empty());
+    }
+
+    #[test]
+    fn test_disconnect_wakes_reader() {
+        let device = VirtualDevice::builder(0x046D, 0xC534).build();
+        let hid = device.open().unwrap();
+
+        let reader = std::thread::spawn(move || hid.read(&mut [0u8; 4]));
+        std::thread::sleep(Duration::from_millis(10));
+        device.disconnect();
+        assert!(reader.join().unwrap().is_err());
+        assert!(device.open().is_err());
+    }
+}
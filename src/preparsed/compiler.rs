@@ -0,0 +1,343 @@
+use crate::descriptor::{Collection, Field, ReportDescriptor, UsageRange};
+use crate::preparsed::typedefs::{Caps, CapsInfo, HidpPreparsedData, LinkCollectionNode, Range};
+use crate::preparsed::types::ReportType;
+use crate::preparsed::{as_bytes, zeroed, PreparsedDataError, PreparsedDataResult};
+use std::mem::size_of;
+use std::ops::Range as Span;
+
+/// Build the `_HIDP_PREPARSED_DATA` blob for each top level collection of a
+/// report descriptor
+///
+/// Windows splits a device into one HID device per top level collection, each
+/// with its own preparsed data. The blobs are returned in the order of the
+/// collections in the descriptor.
+///
+/// The capabilities and link collections are laid out as the Windows HID
+/// parser does. Like Windows, the logical and physical maxima are the sign
+/// extended item data, so a Logical Maximum of `0x25 0xFF` is stored as -1,
+/// where [`Field::logical_maximum`] reads it as 255. Bytes that Windows leaves
+/// uninitialized, such as the slots it reserves for padding items, are zero.
+///
+/// Fails if the descriptor is malformed, or if a value does not fit in the
+/// 16 bit fields of the preparsed data.
+pub fn compile_descriptor(descriptor: &[u8]) -> PreparsedDataResult<Vec<Vec<u8>>> {
+    let descriptor = ReportDescriptor::parse(descriptor)?;
+    let collections = descriptor.collections();
+
+    let top_level = collections
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.parent().is_none())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let spans = top_level
+        .iter()
+        .enumerate()
+        .map(|(i, start)| *start..top_level.get(i + 1).copied().unwrap_or(collections.len()))
+        .collect::<Vec<_>>();
+
+    spans
+        .into_iter()
+        .map(|span| compile_collection(&descriptor, span))
+        .collect()
+}
+
+/// Compile the top level collection whose subtree spans `span` in
+/// [`ReportDescriptor::collections`]
+fn compile_collection(
+    descriptor: &ReportDescriptor,
+    span: Span<usize>,
+) -> PreparsedDataResult<Vec<u8>> {
+    let collections = &descriptor.collections()[span.clone()];
+    let nodes = link_collection_nodes(collections, span.start)?;
+
+    let mut fields = descriptor
+        .fields()
+        .filter(|f| f.collection().is_some_and(|c| span.contains(&c)))
+        .collect::<Vec<_>>();
+    fields.sort_by_key(|f| f.offset());
+
+    let mut header: HidpPreparsedData = zeroed();
+    header.magic_key = *b"HidP KDR";
+    header.usage = collections[0].usage().id;
+    header.usage_page = collections[0].usage().page;
+
+    let mut caps_list = Vec::new();
+    let mut first_cap = 0;
+    for report_type in ReportType::values() {
+        let fields = fields
+            .iter()
+            .filter(|f| f.report_type() as u16 == report_type as u16)
+            .collect::<Vec<_>>();
+
+        let mut data_index = 0;
+        let mut slots = 0;
+        for field in &fields {
+            // The HID parser reserves a capability for each main item, but
+            // drops it again for padding
+            let caps = field_caps(field, &nodes, span.start, &mut data_index)?;
+            slots += caps.len().max(1);
+            caps_list.extend(caps);
+        }
+
+        let report_byte_length = descriptor
+            .reports()
+            .iter()
+            .filter(|r| {
+                r.report_type() as u16 == report_type as u16
+                    && fields.iter().any(|f| f.report_id() == r.report_id())
+            })
+            .map(|r| r.byte_length() + 1)
+            .max()
+            .unwrap_or(0);
+
+        header.caps_info[report_type as usize] = CapsInfo {
+            first_cap: to_u16(first_cap)?,
+            number_of_caps: to_u16(slots)?,
+            last_cap: to_u16(caps_list.len())?,
+            report_byte_length: to_u16(report_byte_length)?,
+        };
+        // Unused slots stay in the blob
+        caps_list.resize(first_cap + slots, zeroed());
+        first_cap += slots;
+    }
+
+    header.first_byte_of_link_collection_array = to_u16(caps_list.len() * size_of::<Caps>())?;
+    header.number_link_collection_nodes = to_u16(nodes.nodes.len())?;
+
+    let mut blob = Vec::new();
+    blob.extend_from_slice(as_bytes(&header));
+    for caps in &caps_list {
+        blob.extend_from_slice(as_bytes(caps));
+    }
+    for node in &nodes.nodes {
+        blob.extend_from_slice(as_bytes(node));
+    }
+    Ok(blob)
+}
+
+/// The capabilities of a main item, in the order the HID parser stores them
+///
+/// The HID parser keeps the local usages on a stack, so the capabilities of a
+/// main item appear in reverse order of their usages. Variable items number
+/// their data indices in that order as well, while array items number them in
+/// descriptor order. The aliases of a usage precede it and share its data
+/// indices, so the preferred usage is the last of its set.
+fn field_caps(
+    field: &Field,
+    nodes: &LinkCollectionNodes,
+    first_collection: usize,
+    data_index: &mut usize,
+) -> PreparsedDataResult<Vec<Caps>> {
+    let usages = field.usages();
+    if usages.is_empty() {
+        return Ok(Vec::new());
+    }
+    let collection = nodes.index[field.collection().unwrap() - first_collection];
+    let link_node = &nodes.nodes[collection as usize];
+    let is_button = field.flags().is_array() || field.report_size() == 1;
+
+    // The capabilities with their usages, the index of their preferred usage
+    // and whether they are an alias
+    let mut caps = Vec::new();
+    let mut control = 0;
+    for (index, usage) in usages.iter().enumerate() {
+        let (bit_offset, report_count) = if field.flags().is_array() {
+            (field.bit_offset(), field.report_count())
+        } else {
+            let remaining = field.report_count().saturating_sub(control);
+            let count = if index + 1 == usages.len() {
+                remaining
+            } else {
+                remaining.min(usage.len() as u32)
+            };
+            let bit_offset = field.bit_offset() + control as usize * field.report_size() as usize;
+            control += count;
+            (bit_offset, count)
+        };
+        let bit_count = field.report_size() as usize * report_count as usize;
+
+        let mut cap: Caps = zeroed();
+        cap.usage_page = usage.page;
+        cap.report_id = field.report_id();
+        cap.bit_position = (bit_offset % 8) as u8;
+        cap.byte_position = to_u16(bit_offset / 8 + 1)?;
+        cap.report_size = to_u16(field.report_size())?;
+        cap.report_count = to_u16(report_count)?;
+        cap.bit_count = to_u16(bit_count)?;
+        cap.bit_field = field.flags().0;
+        cap.next_byte_position =
+            to_u16(bit_offset / 8 + 1 + (bit_offset % 8 + bit_count).div_ceil(8))?;
+        cap.link_collection = collection;
+        cap.link_usage_page = link_node.link_usage_page;
+        cap.link_usage = link_node.link_usage;
+        cap.flags = caps_flags(field, usage, is_button);
+        cap.units = field.unit().0;
+        cap.units_exp = (field.unit_exponent() as u8 & 0x0F) as u32;
+
+        if is_button {
+            let button = cap.button_mut();
+            if field.flags().is_array() {
+                button.logical_min = field.logical_minimum();
+                button.logical_max = field.signed_logical_maximum;
+            }
+        } else {
+            let value = cap.not_button_mut();
+            value.has_nul = field.flags().has_null_state() as u8;
+            value.logical_min = field.logical_minimum();
+            value.logical_max = field.signed_logical_maximum;
+            value.physical_min = field.physical_minimum;
+            value.physical_max = field.signed_physical_maximum;
+        }
+        caps.push((cap, *usage, index, false));
+
+        for alias in field.aliases(index) {
+            let mut cap = cap;
+            cap.usage_page = alias.page;
+            cap.flags = caps_flags(field, &alias, is_button) | 1 << 5;
+            caps.push((cap, alias, index, true));
+        }
+    }
+
+    // Array items count their data indices in descriptor order
+    let mut first_data_indices = vec![0; usages.len()];
+    if field.flags().is_array() {
+        number_data_indices(&mut caps, field, data_index, &mut first_data_indices)?;
+    }
+    caps.reverse();
+    if !field.flags().is_array() {
+        number_data_indices(&mut caps, field, data_index, &mut first_data_indices)?;
+    }
+    for (cap, usage, index, _) in caps.iter_mut().filter(|(.., is_alias)| *is_alias) {
+        set_usages(cap, field, usage, &mut first_data_indices[*index].clone())?;
+    }
+
+    let last = caps.len() - 1;
+    Ok(caps
+        .into_iter()
+        .enumerate()
+        .map(|(i, (mut cap, ..))| {
+            // All but the last capability of an array share its controls
+            if field.flags().is_array() && i != last {
+                cap.flags |= 1 << 0;
+            }
+            cap
+        })
+        .collect())
+}
+
+/// Give the preferred usages their data indices, in the order of `caps`
+fn number_data_indices(
+    caps: &mut [(Caps, UsageRange, usize, bool)],
+    field: &Field,
+    data_index: &mut usize,
+    first_data_indices: &mut [usize],
+) -> PreparsedDataResult<()> {
+    for (cap, usage, index, _) in caps.iter_mut().filter(|(.., is_alias)| !*is_alias) {
+        first_data_indices[*index] = *data_index;
+        set_usages(cap, field, usage, data_index)?;
+    }
+    Ok(())
+}
+
+fn caps_flags(field: &Field, usage: &UsageRange, is_button: bool) -> u8 {
+    let mut flags = 0;
+    if field.flags().is_constant() {
+        flags |= 1 << 1;
+    }
+    if is_button {
+        flags |= 1 << 2;
+    }
+    if !field.flags().is_relative() {
+        flags |= 1 << 3;
+    }
+    if !usage.is_single() {
+        flags |= 1 << 4;
+    }
+    if field.strings().is_some_and(|s| s.start() != s.end()) {
+        flags |= 1 << 6;
+    }
+    if field.designators().is_some_and(|d| d.start() != d.end()) {
+        flags |= 1 << 7;
+    }
+    flags
+}
+
+/// Windows stores single usages like ranges with equal bounds
+fn set_usages(
+    cap: &mut Caps,
+    field: &Field,
+    usage: &UsageRange,
+    data_index: &mut usize,
+) -> PreparsedDataResult<()> {
+    let strings = field.strings().unwrap_or(0..=0);
+    let designators = field.designators().unwrap_or(0..=0);
+    let count = usage.len().max(1);
+    cap.set_range(Range {
+        usage_min: usage.minimum,
+        usage_max: usage.maximum,
+        string_min: to_u16(*strings.start())?,
+        string_max: to_u16(*strings.end())?,
+        designator_min: to_u16(*designators.start())?,
+        designator_max: to_u16(*designators.end())?,
+        data_index_min: to_u16(*data_index)?,
+        data_index_max: to_u16(*data_index + count - 1)?,
+    });
+    *data_index += count;
+    Ok(())
+}
+
+fn to_u16<T: TryInto<u16>>(value: T) -> PreparsedDataResult<u16> {
+    value
+        .try_into()
+        .map_err(|_| PreparsedDataError::ValueOutOfRange)
+}
+
+/// The link collection nodes of a top level collection
+struct LinkCollectionNodes {
+    nodes: Vec<LinkCollectionNode>,
+    /// The node of each collection, the aliases have nodes of their own
+    index: Vec<u16>,
+}
+
+/// Build the link collection nodes of the `collections` of a top level
+/// collection, the first of which has the index `first_collection`
+///
+/// The nodes are numbered in descriptor order. Siblings are linked from the
+/// last to the first, so `first_child` refers to the last child. An aliased
+/// collection is preceded by a node for each alias, like the capabilities.
+/// The top level collection has to be the first node, so its aliases are
+/// left out.
+fn link_collection_nodes(
+    collections: &[Collection],
+    first_collection: usize,
+) -> PreparsedDataResult<LinkCollectionNodes> {
+    let mut nodes: Vec<LinkCollectionNode> = Vec::new();
+    let mut index = Vec::new();
+    for (i, c) in collections.iter().enumerate() {
+        let mut node: LinkCollectionNode = zeroed();
+        node.parent = c.parent().map_or(0, |p| index[p - first_collection]);
+        node.bits = u8::from(c.collection_type()) as u32;
+        if i != 0 {
+            for alias in c.aliases().iter().rev() {
+                let mut alias_node = node;
+                alias_node.link_usage = alias.id;
+                alias_node.link_usage_page = alias.page;
+                alias_node.bits |= 1 << 8;
+                nodes.push(alias_node);
+            }
+        }
+        node.link_usage = c.usage().id;
+        node.link_usage_page = c.usage().page;
+        index.push(to_u16(nodes.len())?);
+        nodes.push(node);
+    }
+
+    for index in 1..nodes.len() {
+        let parent = nodes[index].parent as usize;
+        nodes[index].next_sibling = nodes[parent].first_child;
+        nodes[parent].first_child = index as u16;
+        nodes[parent].number_of_children += 1;
+    }
+    Ok(LinkCollectionNodes { nodes, index })
+}
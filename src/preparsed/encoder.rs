@@ -1,13 +1,14 @@
-use crate::windows_native::descriptor::typedefs::{Caps, LinkCollectionNode};
-use crate::windows_native::descriptor::types::{ItemNodeType, Items, MainItemNode, MainItems};
-use crate::windows_native::error::{WinError, WinResult};
-use crate::windows_native::utils::PeakIterExt;
+use crate::descriptor::{write_item, Tag};
+use crate::preparsed::typedefs::{Caps, LinkCollectionNode};
+use crate::preparsed::types::{ItemNodeType, MainItemNode, MainItems};
+use crate::preparsed::utils::PeakIterExt;
+use crate::preparsed::{PreparsedDataError, PreparsedDataResult};
 
 pub fn encode_descriptor(
     main_item_list: &[MainItemNode],
     caps_list: &[Caps],
     link_collection_nodes: &[LinkCollectionNode],
-) -> WinResult<Vec<u8>> {
+) -> PreparsedDataResult<Vec<u8>> {
     // ***********************************
     // Encode the report descriptor output
     // ***********************************
@@ -21,7 +22,7 @@ pub fn encode_descriptor(
     let mut last_unit_exponent = 0; // If Unit Exponent is Undefined it should be considered as 0 according USB HID spec 1.11 chapter 6.2.2.7
     let mut last_unit = 0; // If the first nibble is 7, or second nibble of Unit is 0, the unit is None according USB HID spec 1.11 chapter 6.2.2.7
     let mut inhibit_write_of_usage = false; // Needed in case of delimited usage print, before the normal collection or cap
-    let mut report_count = 0;
+    let mut report_count = 0u16;
 
     for (current, next) in main_item_list.iter().peaking() {
         let rt_idx = current.main_item_type;
@@ -34,7 +35,7 @@ pub fn encode_descriptor(
                     // Write "Usage Page" at the begin of a collection - except it refers the same table as wrote last
                     last_usage_page =
                         link_collection_nodes[current.collection_index].link_usage_page;
-                    writer.write(Items::GlobalUsagePage, last_usage_page)?;
+                    writer.write(Tag::UsagePage, last_usage_page)?;
                 }
                 if inhibit_write_of_usage {
                     // Inhibit only once after DELIMITER statement
@@ -42,48 +43,74 @@ pub fn encode_descriptor(
                 } else {
                     // Write "Usage" of collection
                     writer.write(
-                        Items::LocalUsage,
+                        Tag::Usage,
                         link_collection_nodes[current.collection_index].link_usage,
                     )?;
                 }
                 // Write begin of "Collection"
                 writer.write(
-                    Items::MainCollection,
+                    Tag::Collection,
                     link_collection_nodes[current.collection_index].collection_type(),
                 )?;
             }
             MainItems::CollectionEnd => {
                 // Write "End Collection"
-                writer.write(Items::MainCollectionEnd, 0)?;
+                writer.write(Tag::EndCollection, 0)?;
             }
             MainItems::DelimiterOpen => {
-                // Current.collection_index seems to always be != -1 -> removing branches compared to c
-                // Write "Usage Page" inside of a collection delmiter section
-                if last_usage_page
-                    != link_collection_nodes[current.collection_index].link_usage_page
-                {
-                    last_usage_page =
-                        link_collection_nodes[current.collection_index].link_usage_page;
-                    writer.write(
-                        Items::GlobalUsagePage,
-                        link_collection_nodes[current.collection_index].collection_type(),
-                    )?;
+                // Write "Usage Page" of the preferred usage before the delimiter section
+                let usage_page = match current.node_type {
+                    ItemNodeType::Collection => {
+                        link_collection_nodes[current.collection_index].link_usage_page
+                    }
+                    _ => caps_list[caps_idx as usize].usage_page,
+                };
+                if last_usage_page != usage_page {
+                    last_usage_page = usage_page;
+                    writer.write(Tag::UsagePage, last_usage_page)?;
                 }
                 // Write "Delimiter Open"
-                writer.write(Items::LocalDelimiter, 1)?; // 1 = open set of aliased usages
+                writer.write(Tag::Delimiter, 1)?; // 1 = open set of aliased usages
             }
             MainItems::DelimiterUsage => {
-                // Current.collection_index seems to always be != -1 -> removing branches compared to c
-                // Write aliased collection "Usage"
-                writer.write(
-                    Items::LocalUsage,
-                    link_collection_nodes[current.collection_index].link_usage,
-                )?;
+                // Aliases on another usage page are written as extended usages
+                let extended = |usage_page: u16, usage: u16| {
+                    if usage_page == last_usage_page {
+                        usage as u32
+                    } else {
+                        (usage_page as u32) << 16 | usage as u32
+                    }
+                };
+                if current.node_type == ItemNodeType::Collection {
+                    // Write aliased collection "Usage"
+                    let node = link_collection_nodes[current.collection_index];
+                    writer.write(Tag::Usage, extended(node.link_usage_page, node.link_usage))?;
+                } else {
+                    let caps = caps_list[caps_idx as usize];
+                    if caps.is_range() {
+                        // Write aliased range from "Usage Minimum" to "Usage Maximum"
+                        let range = caps.range();
+                        writer.write(
+                            Tag::UsageMinimum,
+                            extended(caps.usage_page, range.usage_min),
+                        )?;
+                        writer.write(
+                            Tag::UsageMaximum,
+                            extended(caps.usage_page, range.usage_max),
+                        )?;
+                    } else {
+                        // Write single aliased "Usage"
+                        writer.write(
+                            Tag::Usage,
+                            extended(caps.usage_page, caps.not_range().usage),
+                        )?;
+                    }
+                }
             }
             MainItems::DelimiterClose => {
                 // Write "Delimiter Close"
-                writer.write(Items::LocalDelimiter, 0)?; // 0 = close set of aliased usages
-                                                         // Inhibit next usage write
+                writer.write(Tag::Delimiter, 0)?; // 0 = close set of aliased usages
+                                                  // Inhibit next usage write
                 inhibit_write_of_usage = true;
             }
             _ if current.node_type == ItemNodeType::Padding => {
@@ -93,22 +120,25 @@ pub fn encode_descriptor(
 
                 // Write "Report Size" with number of padding bits
                 writer.write(
-                    Items::GlobalReportSize,
-                    current.last_bit - current.first_bit + 1,
+                    Tag::ReportSize,
+                    current
+                        .last_bit
+                        .wrapping_sub(current.first_bit)
+                        .wrapping_add(1),
                 )?;
 
                 // Write "Report Count" for padding always as 1
-                writer.write(Items::GlobalReportCount, 1)?;
+                writer.write(Tag::ReportCount, 1)?;
 
                 if rt_idx == MainItems::Input {
                     // Write "Input" main item - We know it's Constant - We can only guess the other bits, but they don't matter in case of const
-                    writer.write(Items::MainInput, 0x03)?; // Const / Abs
+                    writer.write(Tag::Input, 0x03)?; // Const / Abs
                 } else if rt_idx == MainItems::Output {
                     // Write "Output" main item - We know it's Constant - We can only guess the other bits, but they don't matter in case of const
-                    writer.write(Items::MainOutput, 0x03)?; // Const / Abs
+                    writer.write(Tag::Output, 0x03)?; // Const / Abs
                 } else if rt_idx == MainItems::Feature {
                     // Write "Feature" main item - We know it's Constant - We can only guess the other bits, but they don't matter in case of const
-                    writer.write(Items::MainFeature, 0x03)?; // Const / Abs
+                    writer.write(Tag::Feature, 0x03)?; // Const / Abs
                 }
                 report_count = 0;
             }
@@ -120,18 +150,22 @@ pub fn encode_descriptor(
                 if last_report_id != caps.report_id {
                     // Write "Report ID" if changed
                     last_report_id = caps.report_id;
-                    writer.write(Items::GlobalReportId, last_report_id)?;
+                    writer.write(Tag::ReportId, last_report_id)?;
                 }
 
                 // Write "Usage Page" when changed
                 if caps.usage_page != last_usage_page {
                     last_usage_page = caps.usage_page;
-                    writer.write(Items::GlobalUsagePage, last_usage_page)?;
+                    writer.write(Tag::UsagePage, last_usage_page)?;
                 }
 
                 // Write only local report items for each cap, if ReportCount > 1
                 if caps.is_range() {
-                    report_count += caps.range().data_index_max - caps.range().data_index_min;
+                    report_count = report_count.wrapping_add(
+                        caps.range()
+                            .data_index_max
+                            .wrapping_sub(caps.range().data_index_min),
+                    );
                 }
 
                 if inhibit_write_of_usage {
@@ -139,37 +173,34 @@ pub fn encode_descriptor(
                     inhibit_write_of_usage = false;
                 } else if caps.is_range() {
                     // Write range from "Usage Minimum" to "Usage Maximum"
-                    writer.write(Items::LocalUsageMinimum, caps.range().usage_min)?;
-                    writer.write(Items::LocalUsageMaximum, caps.range().usage_max)?;
+                    writer.write(Tag::UsageMinimum, caps.range().usage_min)?;
+                    writer.write(Tag::UsageMaximum, caps.range().usage_max)?;
                 } else {
                     // Write single "Usage"
-                    writer.write(Items::LocalUsage, caps.not_range().usage)?;
+                    writer.write(Tag::Usage, caps.not_range().usage)?;
                 }
 
                 if caps.is_designator_range() {
                     // Write physical descriptor indices range from "Designator Minimum" to "Designator Maximum"
-                    writer.write(Items::LocalDesignatorMinimum, caps.range().designator_min)?;
-                    writer.write(Items::LocalDesignatorMaximum, caps.range().designator_max)?;
+                    writer.write(Tag::DesignatorMinimum, caps.range().designator_min)?;
+                    writer.write(Tag::DesignatorMaximum, caps.range().designator_max)?;
                 } else if caps.not_range().designator_index != 0 {
                     // Designator set 0 is a special descriptor set (of the HID Physical Descriptor),
                     // that specifies the number of additional descriptor sets.
                     // Therefore Designator Index 0 can never be a useful reference for a control and we can inhibit it.
                     // Write single "Designator Index"
-                    writer.write(
-                        Items::LocalDesignatorIndex,
-                        caps.not_range().designator_index,
-                    )?;
+                    writer.write(Tag::DesignatorIndex, caps.not_range().designator_index)?;
                 }
 
                 if caps.is_string_range() {
                     // Write range of indices of the USB string descriptor, from "String Minimum" to "String Maximum"
-                    writer.write(Items::LocalStringMinimum, caps.range().string_min)?;
-                    writer.write(Items::LocalStringMaximum, caps.range().string_max)?;
+                    writer.write(Tag::StringMinimum, caps.range().string_min)?;
+                    writer.write(Tag::StringMaximum, caps.range().string_max)?;
                 } else if caps.not_range().string_index != 0 {
                     // String Index 0 is a special entry of the USB string descriptor, that contains a list of supported languages,
                     // therefore Designator Index 0 can never be a useful reference for a control and we can inhibit it.
                     // Write single "String Index"
-                    writer.write(Items::LocalString, caps.not_range().string_index)?;
+                    writer.write(Tag::StringIndex, caps.not_range().string_index)?;
                 }
 
                 if next.is_some_and(|next| {
@@ -193,60 +224,63 @@ pub fn encode_descriptor(
                         // While a HID report descriptor must always contain LogicalMinimum and LogicalMaximum,
                         // the preparsed data contain both fields set to zero, for the case of simple buttons
                         // Write "Logical Minimum" set to 0 and "Logical Maximum" set to 1
-                        writer.write(Items::GlobalLogicalMinimum, 0)?;
-                        writer.write(Items::GlobalLogicalMaximum, 1)?;
+                        writer.write(Tag::LogicalMinimum, 0)?;
+                        writer.write(Tag::LogicalMaximum, 1)?;
                     } else {
                         // Write logical range from "Logical Minimum" to "Logical Maximum"
-                        writer.write(Items::GlobalLogicalMinimum, caps.button().logical_min)?;
-                        writer.write(Items::GlobalLogicalMaximum, caps.button().logical_max)?;
+                        writer.write(Tag::LogicalMinimum, caps.button().logical_min)?;
+                        writer.write(Tag::LogicalMaximum, caps.button().logical_max)?;
                     }
 
                     // Write "Report Size"
-                    writer.write(Items::GlobalReportSize, caps.report_size)?;
+                    writer.write(Tag::ReportSize, caps.report_size)?;
 
                     // Write "Report Count"
                     if !caps.is_range() {
                         // Variable bit field with one bit per button
                         // In case of multiple usages with the same items, only "Usage" is written per cap, and "Report Count" is incremented
-                        writer.write(Items::GlobalReportCount, caps.report_count + report_count)?;
+                        writer.write(
+                            Tag::ReportCount,
+                            caps.report_count.wrapping_add(report_count),
+                        )?;
                     } else {
                         // Button array of "Report Size" x "Report Count
-                        writer.write(Items::GlobalReportCount, caps.report_count)?;
+                        writer.write(Tag::ReportCount, caps.report_count)?;
                     }
 
                     // Buttons have only 1 bit and therefore no physical limits/units -> Set to undefined state
                     if last_physical_min != 0 {
                         // Write "Physical Minimum", but only if changed
                         last_physical_min = 0;
-                        writer.write(Items::GlobalPhysicalMinimum, last_physical_min)?;
+                        writer.write(Tag::PhysicalMinimum, last_physical_min)?;
                     }
                     if last_physical_max != 0 {
                         // Write "Physical Maximum", but only if changed
                         last_physical_max = 0;
-                        writer.write(Items::GlobalPhysicalMaximum, last_physical_max)?;
+                        writer.write(Tag::PhysicalMaximum, last_physical_max)?;
                     }
                     if last_unit_exponent != 0 {
                         // Write "Unit Exponent", but only if changed
                         last_unit_exponent = 0;
-                        writer.write(Items::GlobalUnitExponent, last_unit_exponent)?;
+                        writer.write(Tag::UnitExponent, last_unit_exponent)?;
                     }
                     if last_unit != 0 {
                         // Write "Unit",but only if changed
                         last_unit = 0;
-                        writer.write(Items::GlobalUnit, last_unit)?;
+                        writer.write(Tag::Unit, last_unit)?;
                     }
 
                     // Write "Input" main item
                     if rt_idx == MainItems::Input {
-                        writer.write(Items::MainInput, caps.bit_field)?;
+                        writer.write(Tag::Input, caps.bit_field)?;
                     }
                     // Write "Output" main item
                     else if rt_idx == MainItems::Output {
-                        writer.write(Items::MainOutput, caps.bit_field)?;
+                        writer.write(Tag::Output, caps.bit_field)?;
                     }
                     // Write "Feature" main item
                     else if rt_idx == MainItems::Feature {
-                        writer.write(Items::MainFeature, caps.bit_field)?;
+                        writer.write(Tag::Feature, caps.bit_field)?;
                     }
                     report_count = 0;
                 }
@@ -257,13 +291,13 @@ pub fn encode_descriptor(
                 if last_report_id != caps.report_id {
                     // Write "Report ID" if changed
                     last_report_id = caps.report_id;
-                    writer.write(Items::GlobalReportId, last_report_id)?;
+                    writer.write(Tag::ReportId, last_report_id)?;
                 }
 
                 // Write "Usage Page" if changed
                 if caps.usage_page != last_usage_page {
                     last_usage_page = caps.usage_page;
-                    writer.write(Items::GlobalUsagePage, last_usage_page)?;
+                    writer.write(Tag::UsagePage, last_usage_page)?;
                 }
 
                 if inhibit_write_of_usage {
@@ -271,46 +305,46 @@ pub fn encode_descriptor(
                     inhibit_write_of_usage = false;
                 } else if caps.is_range() {
                     // Write usage range from "Usage Minimum" to "Usage Maximum"
-                    writer.write(Items::LocalUsageMinimum, caps.range().usage_min)?;
-                    writer.write(Items::LocalUsageMaximum, caps.range().usage_max)?;
+                    writer.write(Tag::UsageMinimum, caps.range().usage_min)?;
+                    writer.write(Tag::UsageMaximum, caps.range().usage_max)?;
                 } else {
                     // Write single "Usage"
-                    writer.write(Items::LocalUsage, caps.not_range().usage)?;
+                    writer.write(Tag::Usage, caps.not_range().usage)?;
                 }
 
                 if caps.is_designator_range() {
                     // Write physical descriptor indices range from "Designator Minimum" to "Designator Maximum"
-                    writer.write(Items::LocalDesignatorMinimum, caps.range().designator_min)?;
-                    writer.write(Items::LocalDesignatorMaximum, caps.range().designator_max)?;
+                    writer.write(Tag::DesignatorMinimum, caps.range().designator_min)?;
+                    writer.write(Tag::DesignatorMaximum, caps.range().designator_max)?;
                 } else if caps.not_range().designator_index != 0 {
                     // Designator set 0 is a special descriptor set (of the HID Physical Descriptor),
                     // that specifies the number of additional descriptor sets.
                     // Therefore Designator Index 0 can never be a useful reference for a control and we can inhibit it.
                     // Write single "Designator Index"
-                    writer.write(
-                        Items::LocalDesignatorIndex,
-                        caps.not_range().designator_index,
-                    )?;
+                    writer.write(Tag::DesignatorIndex, caps.not_range().designator_index)?;
                 }
 
                 if caps.is_string_range() {
                     // Write range of indices of the USB string descriptor, from "String Minimum" to "String Maximum"
-                    writer.write(Items::LocalStringMinimum, caps.range().string_min)?;
-                    writer.write(Items::LocalStringMaximum, caps.range().string_max)?;
+                    writer.write(Tag::StringMinimum, caps.range().string_min)?;
+                    writer.write(Tag::StringMaximum, caps.range().string_max)?;
                 } else if caps.not_range().string_index != 0 {
                     // String Index 0 is a special entry of the USB string descriptor, that contains a list of supported languages,
                     // therefore Designator Index 0 can never be a useful reference for a control and we can inhibit it.
                     // Write single "String Index"
-                    writer.write(Items::LocalString, caps.not_range().string_index)?;
+                    writer.write(Tag::StringIndex, caps.not_range().string_index)?;
                 }
 
                 if (caps.bit_field & 0x02) != 0x02 {
                     // In case of an value array overwrite "Report Count"
-                    caps.report_count =
-                        caps.range().data_index_max - caps.range().data_index_min + 1;
+                    caps.report_count = caps
+                        .range()
+                        .data_index_max
+                        .wrapping_sub(caps.range().data_index_min)
+                        .wrapping_add(1);
                 }
 
-                #[allow(clippy::blocks_in_if_conditions)]
+                #[allow(clippy::blocks_in_conditions)]
                 if next.is_some_and(|next| {
                     let next_caps = caps_list
                         .get(next.caps_index as usize)
@@ -340,8 +374,8 @@ pub fn encode_descriptor(
                     // Value
 
                     // Write logical range from "Logical Minimum" to "Logical Maximum"
-                    writer.write(Items::GlobalLogicalMinimum, caps.not_button().logical_min)?;
-                    writer.write(Items::GlobalLogicalMaximum, caps.not_button().logical_max)?;
+                    writer.write(Tag::LogicalMinimum, caps.not_button().logical_min)?;
+                    writer.write(Tag::LogicalMaximum, caps.not_button().logical_max)?;
 
                     if (last_physical_min != caps.not_button().physical_min)
                         || (last_physical_max != caps.not_button().physical_max)
@@ -349,37 +383,40 @@ pub fn encode_descriptor(
                         // Write range from "Physical Minimum" to " Physical Maximum", but only if one of them changed
                         last_physical_min = caps.not_button().physical_min;
                         last_physical_max = caps.not_button().physical_max;
-                        writer.write(Items::GlobalPhysicalMinimum, last_physical_min)?;
-                        writer.write(Items::GlobalPhysicalMaximum, last_physical_max)?;
+                        writer.write(Tag::PhysicalMinimum, last_physical_min)?;
+                        writer.write(Tag::PhysicalMaximum, last_physical_max)?;
                     }
 
                     if last_unit_exponent != caps.units_exp {
                         // Write "Unit Exponent", but only if changed
                         last_unit_exponent = caps.units_exp;
-                        writer.write(Items::GlobalUnitExponent, last_unit_exponent)?;
+                        writer.write(Tag::UnitExponent, last_unit_exponent)?;
                     }
 
                     if last_unit != caps.units {
                         // Write physical "Unit", but only if changed
                         last_unit = caps.units;
-                        writer.write(Items::GlobalUnit, last_unit)?;
+                        writer.write(Tag::Unit, last_unit)?;
                     }
 
                     // Write "Report Size"
-                    writer.write(Items::GlobalReportSize, caps.report_size)?;
+                    writer.write(Tag::ReportSize, caps.report_size)?;
 
                     // Write "Report Count"
-                    writer.write(Items::GlobalReportCount, caps.report_count + report_count)?;
+                    writer.write(
+                        Tag::ReportCount,
+                        caps.report_count.wrapping_add(report_count),
+                    )?;
 
                     if rt_idx == MainItems::Input {
                         // Write "Input" main item
-                        writer.write(Items::MainInput, caps.bit_field)?;
+                        writer.write(Tag::Input, caps.bit_field)?;
                     } else if rt_idx == MainItems::Output {
                         // Write "Output" main item
-                        writer.write(Items::MainOutput, caps.bit_field)?;
+                        writer.write(Tag::Output, caps.bit_field)?;
                     } else if rt_idx == MainItems::Feature {
                         // Write "Feature" main item
-                        writer.write(Items::MainFeature, caps.bit_field)?;
+                        writer.write(Tag::Feature, caps.bit_field)?;
                     }
                     report_count = 0;
                 }
@@ -395,45 +432,8 @@ struct DescriptorWriter(Vec<u8>);
 
 impl DescriptorWriter {
     // Writes a short report descriptor item according USB HID spec 1.11 chapter 6.2.2.2
-    fn write(&mut self, item: Items, data: impl Into<i64>) -> WinResult<()> {
-        let data = data.into();
-        match item {
-            Items::MainCollectionEnd => {
-                self.0.push(item as u8);
-            }
-            Items::GlobalLogicalMinimum
-            | Items::GlobalLogicalMaximum
-            | Items::GlobalPhysicalMinimum
-            | Items::GlobalPhysicalMaximum => {
-                if let Ok(data) = i8::try_from(data) {
-                    self.0.push((item as u8) + 0x01);
-                    self.0.extend(data.to_le_bytes())
-                } else if let Ok(data) = i16::try_from(data) {
-                    self.0.push((item as u8) + 0x02);
-                    self.0.extend(data.to_le_bytes())
-                } else if let Ok(data) = i32::try_from(data) {
-                    self.0.push((item as u8) + 0x03);
-                    self.0.extend(data.to_le_bytes())
-                } else {
-                    return Err(WinError::InvalidPreparsedData);
-                }
-            }
-            _ => {
-                if let Ok(data) = u8::try_from(data) {
-                    self.0.push((item as u8) + 0x01);
-                    self.0.extend(data.to_le_bytes())
-                } else if let Ok(data) = u16::try_from(data) {
-                    self.0.push((item as u8) + 0x02);
-                    self.0.extend(data.to_le_bytes())
-                } else if let Ok(data) = u32::try_from(data) {
-                    self.0.push((item as u8) + 0x03);
-                    self.0.extend(data.to_le_bytes())
-                } else {
-                    return Err(WinError::InvalidPreparsedData);
-                }
-            }
-        }
-        Ok(())
+    fn write(&mut self, tag: Tag, data: impl Into<i64>) -> PreparsedDataResult<()> {
+        write_item(&mut self.0, tag, data.into()).ok_or(PreparsedDataError::ValueOutOfRange)
     }
 
     fn finish(self) -> Vec<u8> {
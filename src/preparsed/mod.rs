@@ -1,68 +1,244 @@
+//! Conversion between report descriptors and Windows preparsed data
+//!
+//! Windows does not give access to the report descriptor of a device. It only
+//! provides the `_HIDP_PREPARSED_DATA` structure, which the HID parser driver
+//! builds from the descriptor. [`reconstruct_descriptor`] turns such a blob
+//! back into a report descriptor that is functionally equivalent to the
+//! original one. The reconstruction is pure Rust and works on every platform,
+//! e.g. to analyse blobs captured on a Windows machine.
+//!
+//! [`compile_descriptor`] goes the other way and builds the preparsed data
+//! Windows would create for a descriptor, e.g. to test code that consumes
+//! such blobs without the device at hand.
+//!
+//! The blob is expected in the little endian layout used by Windows. All
+//! offsets and indices in it are validated, so malformed data results in an
+//! error instead of undefined behavior.
+
+mod compiler;
 mod encoder;
 #[cfg(test)]
 mod tests;
 mod typedefs;
 mod types;
+mod utils;
 
-use crate::windows_native::descriptor::encoder::encode_descriptor;
-use crate::windows_native::descriptor::typedefs::{Caps, HidpPreparsedData, LinkCollectionNode};
-use crate::windows_native::descriptor::types::{
-    BitRange, ItemNodeType, MainItemNode, MainItems, ReportType,
-};
-use crate::windows_native::error::{WinError, WinResult};
-use crate::windows_native::hid::PreparsedData;
-use crate::windows_native::utils::PeakIterExt;
+use crate::descriptor::DescriptorError;
+pub use crate::preparsed::compiler::compile_descriptor;
+use crate::preparsed::encoder::encode_descriptor;
+use crate::preparsed::typedefs::{Caps, HidpPreparsedData, LinkCollectionNode};
+use crate::preparsed::types::{BitRange, ItemNodeType, MainItemNode, MainItems, ReportType};
+use crate::preparsed::utils::PeakIterExt;
 use std::collections::HashMap;
-use std::ffi::c_void;
-use std::slice;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::mem::size_of;
+
+/// Error returned when preparsed data can not be turned into a report descriptor
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PreparsedDataError {
+    /// The blob does not start with the magic key `HidP KDR`
+    InvalidMagicKey,
+    /// The blob is shorter than its header describes
+    Truncated { expected: usize, actual: usize },
+    /// A capability refers to an invalid collection or bit range
+    InvalidCaps,
+    /// The link collection nodes do not form a tree
+    InvalidLinkCollections,
+    /// A value is too large to be encoded in a descriptor item, or in the
+    /// preparsed data
+    ValueOutOfRange,
+    /// The report descriptor to compile is malformed
+    InvalidDescriptor(DescriptorError),
+}
+
+impl Display for PreparsedDataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagicKey => f.write_str("invalid magic key"),
+            Self::Truncated { expected, actual } => write!(
+                f,
+                "preparsed data truncated: expected {expected} bytes, got {actual}"
+            ),
+            Self::InvalidCaps => f.write_str("invalid capabilities"),
+            Self::InvalidLinkCollections => f.write_str("invalid link collections"),
+            Self::ValueOutOfRange => f.write_str("value out of range"),
+            Self::InvalidDescriptor(e) => write!(f, "invalid report descriptor: {e}"),
+        }
+    }
+}
 
-pub fn get_descriptor(pp_data: &PreparsedData) -> WinResult<Vec<u8>> {
-    unsafe { get_descriptor_ptr(pp_data.as_ptr()) }
+impl Error for PreparsedDataError {}
+
+impl From<DescriptorError> for PreparsedDataError {
+    fn from(e: DescriptorError) -> Self {
+        Self::InvalidDescriptor(e)
+    }
 }
 
-unsafe fn get_descriptor_ptr(pp_data: *const c_void) -> WinResult<Vec<u8>> {
+pub type PreparsedDataResult<T> = Result<T, PreparsedDataError>;
+
+/// Reconstruct the report descriptor from a `_HIDP_PREPARSED_DATA` blob
+pub fn reconstruct_descriptor(pp_data: &[u8]) -> PreparsedDataResult<Vec<u8>> {
     let (header, caps_list, link_collection_nodes) = extract_structures(pp_data)?;
 
-    let list = reconstruct_descriptor(header, caps_list, link_collection_nodes);
+    let list = reconstruct_main_items(header, &caps_list, &link_collection_nodes)?;
 
-    encode_descriptor(&list, caps_list, link_collection_nodes)
+    encode_descriptor(&list, &caps_list, &link_collection_nodes)
 }
 
-unsafe fn extract_structures<'a>(
-    pp_data: *const c_void,
-) -> WinResult<(HidpPreparsedData, &'a [Caps], &'a [LinkCollectionNode])> {
-    let header: *const HidpPreparsedData = pp_data as _;
+/// Size of the fixed header at the start of a `_HIDP_PREPARSED_DATA` blob
+pub const HEADER_SIZE: usize = size_of::<HidpPreparsedData>();
+
+/// The size of a `_HIDP_PREPARSED_DATA` blob, computed from its header
+///
+/// Returns `None` if `header` is shorter than [`HEADER_SIZE`] or the magic
+/// key is wrong.
+pub fn preparsed_data_size(header: &[u8]) -> Option<usize> {
+    let header: HidpPreparsedData = read_struct(header, 0)?;
+    (&header.magic_key == b"HidP KDR").then(|| {
+        HEADER_SIZE
+            + header.first_byte_of_link_collection_array as usize
+            + header.number_link_collection_nodes as usize * size_of::<LinkCollectionNode>()
+    })
+}
+
+fn extract_structures(
+    pp_data: &[u8],
+) -> PreparsedDataResult<(HidpPreparsedData, Vec<Caps>, Vec<LinkCollectionNode>)> {
+    let truncated = |expected| PreparsedDataError::Truncated {
+        expected,
+        actual: pp_data.len(),
+    };
+
+    let header: HidpPreparsedData = read_struct(pp_data, 0).ok_or(truncated(HEADER_SIZE))?;
 
     // Check if MagicKey is correct, to ensure that pp_data points to an valid preparse data structure
     ensure!(
-        &(*header).magic_key == b"HidP KDR",
-        Err(WinError::InvalidPreparsedData)
+        &header.magic_key == b"HidP KDR",
+        Err(PreparsedDataError::InvalidMagicKey)
     );
 
-    let caps_ptr: *const Caps = header.offset(1) as _;
+    let size = preparsed_data_size(pp_data).unwrap();
+    ensure!(pp_data.len() >= size, Err(truncated(size)));
+
+    let caps_offset = HEADER_SIZE;
     let caps_len = ReportType::values()
         .into_iter()
-        .map(|r| (*header).caps_info[r as usize].last_cap)
+        .map(|r| header.caps_info[r as usize].last_cap)
         .max()
         .unwrap() as usize;
+    ensure!(
+        caps_len * size_of::<Caps>() <= header.first_byte_of_link_collection_array as usize,
+        Err(PreparsedDataError::InvalidCaps)
+    );
+    let caps_list = (0..caps_len)
+        .map(|i| read_struct(pp_data, caps_offset + i * size_of::<Caps>()).unwrap())
+        .collect::<Vec<Caps>>();
 
-    let link_ptr: *const LinkCollectionNode = ((caps_ptr as *const c_void)
-        .offset((*header).first_byte_of_link_collection_array as isize))
-        as _;
-    let link_len = (*header).number_link_collection_nodes as usize;
+    let link_offset = caps_offset + header.first_byte_of_link_collection_array as usize;
+    let link_collection_nodes = (0..header.number_link_collection_nodes as usize)
+        .map(|i| read_struct(pp_data, link_offset + i * size_of::<LinkCollectionNode>()).unwrap())
+        .collect::<Vec<LinkCollectionNode>>();
 
-    Ok((
-        *header,
-        slice::from_raw_parts(caps_ptr, caps_len),
-        slice::from_raw_parts(link_ptr, link_len),
-    ))
+    validate_link_collections(&link_collection_nodes)?;
+    for rt_idx in ReportType::values() {
+        let caps_info = header.caps_info[rt_idx as usize];
+        ensure!(
+            caps_info.first_cap <= caps_info.last_cap,
+            Err(PreparsedDataError::InvalidCaps)
+        );
+        for caps in &caps_list[caps_info.first_cap as usize..caps_info.last_cap as usize] {
+            validate_caps(caps, link_collection_nodes.len())?;
+        }
+    }
+
+    Ok((header, caps_list, link_collection_nodes))
+}
+
+/// Copy a structure out of the blob, if it is large enough
+fn read_struct<T: Plain>(data: &[u8], offset: usize) -> Option<T> {
+    let bytes = data.get(offset..offset.checked_add(size_of::<T>())?)?;
+    // SAFETY: the range is in bounds and `Plain` types are valid for all bit patterns
+    Some(unsafe { bytes.as_ptr().cast::<T>().read_unaligned() })
 }
 
-fn reconstruct_descriptor(
+/// A structure with all bytes set to 0
+fn zeroed<T: Plain>() -> T {
+    // SAFETY: `Plain` types are valid for all bit patterns
+    unsafe { std::mem::zeroed() }
+}
+
+/// The bytes of a structure, as stored in the blob
+fn as_bytes<T: Plain>(value: &T) -> &[u8] {
+    // SAFETY: `Plain` types have no padding, so all bytes are initialized
+    unsafe { std::slice::from_raw_parts((value as *const T).cast::<u8>(), size_of::<T>()) }
+}
+
+/// Types that are made up of integers only and therefore valid for any bit pattern
+///
+/// # Safety
+///
+/// Implementors must not contain padding, references, enums or other types with invalid bit patterns.
+unsafe trait Plain: Copy {}
+
+unsafe impl Plain for HidpPreparsedData {}
+unsafe impl Plain for Caps {}
+unsafe impl Plain for LinkCollectionNode {}
+
+/// Ensure that the link collections form a tree rooted at node 0, so the
+/// traversals in [`reconstruct_main_items`] terminate
+fn validate_link_collections(nodes: &[LinkCollectionNode]) -> PreparsedDataResult<()> {
+    let err = Err(PreparsedDataError::InvalidLinkCollections);
+    ensure!(!nodes.is_empty() && nodes[0].next_sibling == 0, err);
+
+    let mut visited = vec![false; nodes.len()];
+    visited[0] = true;
+    let mut pending = vec![0];
+    while let Some(parent) = pending.pop() {
+        let mut child = nodes[parent].first_child as usize;
+        let mut number_of_children = 0;
+        while child != 0 {
+            ensure!(
+                child < nodes.len() && !visited[child] && nodes[child].parent as usize == parent,
+                err
+            );
+            visited[child] = true;
+            pending.push(child);
+            number_of_children += 1;
+            child = nodes[child].next_sibling as usize;
+        }
+        ensure!(nodes[parent].number_of_children == number_of_children, err);
+    }
+    ensure!(visited.iter().all(|v| *v), err);
+    Ok(())
+}
+
+fn validate_caps(caps: &Caps, number_link_collection_nodes: usize) -> PreparsedDataResult<()> {
+    let err = Err(PreparsedDataError::InvalidCaps);
+    ensure!(
+        (caps.link_collection as usize) < number_link_collection_nodes,
+        err
+    );
+    // The bit range of the capability must fit in the u16 of `get_bit_range`
+    let first_bit = caps
+        .byte_position
+        .checked_sub(1)
+        .and_then(|byte| byte.checked_mul(8))
+        .and_then(|bit| bit.checked_add(caps.bit_position as u16));
+    let bit_count = caps.report_size.checked_mul(caps.report_count);
+    let last_bit = first_bit
+        .zip(bit_count)
+        .and_then(|(first_bit, bit_count)| first_bit.checked_add(bit_count.checked_sub(1)?));
+    ensure!(last_bit.is_some(), err);
+    Ok(())
+}
+
+fn reconstruct_main_items(
     header: HidpPreparsedData,
     caps_list: &[Caps],
     link_collection_nodes: &[LinkCollectionNode],
-) -> Vec<MainItemNode> {
+) -> PreparsedDataResult<Vec<MainItemNode>> {
     // ****************************************************************************************************************************
     // Create lookup tables for the bit range of each report per collection (position of first bit and last bit in each collection)
     // coll_bit_range[COLLECTION_INDEX][REPORT_ID][INPUT/OUTPUT/FEATURE]
@@ -194,7 +370,7 @@ fn reconstruct_descriptor(
                                             .get(&(cur_coll_idx, report_idx, rt_idx))
                                             .map(|prev| prev.first_bit),
                                     )
-                                    .map_or(false, |(prev, cur)| prev > cur);
+                                    .is_some_and(|(prev, cur)| prev > cur);
                                 if swap {
                                     coll_child_order.insert(
                                         (collection_node_idx, (child_idx - 1)),
@@ -232,7 +408,6 @@ fn reconstruct_descriptor(
 
         let mut actual_coll_level = 0;
         let mut collection_node_idx = 0;
-        let mut first_delimiter_node = false;
 
         main_item_list.push(MainItemNode::new(
             0,
@@ -248,43 +423,13 @@ fn reconstruct_descriptor(
                 && coll_last_written_child[collection_node_idx] == -1
             {
                 // Collection has child collections, but none is written to the list yet
-                coll_last_written_child[collection_node_idx] =
-                    coll_child_order[&(collection_node_idx, 0)] as i32;
-                collection_node_idx = coll_child_order[&(collection_node_idx, 0)];
-
-                // In a HID Report Descriptor, the first usage declared is the most preferred usage for the control.
-                // While the order in the WIN32 capabiliy strutures is the opposite:
-                // Here the preferred usage is the last aliased usage in the sequence.
-                if link_collection_nodes[collection_node_idx].is_alias() && !first_delimiter_node {
-                    first_delimiter_node = true;
-                    main_item_list.push(MainItemNode::new(
-                        0,
-                        0,
-                        ItemNodeType::Collection,
-                        0,
-                        collection_node_idx,
-                        MainItems::DelimiterUsage,
-                        0,
-                    ));
-                    main_item_list.push(MainItemNode::new(
-                        0,
-                        0,
-                        ItemNodeType::Collection,
-                        0,
-                        collection_node_idx,
-                        MainItems::DelimiterClose,
-                        0,
-                    ));
-                } else {
-                    main_item_list.push(MainItemNode::new(
-                        0,
-                        0,
-                        ItemNodeType::Collection,
-                        0,
-                        collection_node_idx,
-                        MainItems::Collection,
-                        0,
-                    ));
+                let child = coll_child_order[&(collection_node_idx, 0)];
+                coll_last_written_child[collection_node_idx] = child as i32;
+
+                // The aliases are written together with their collection
+                if !link_collection_nodes[child].is_alias() {
+                    collection_node_idx = child;
+                    push_collection(&mut main_item_list, link_collection_nodes, child);
                     actual_coll_level += 1;
                 }
             } else if coll_number_of_direct_childs[collection_node_idx] > 1
@@ -301,85 +446,12 @@ fn reconstruct_descriptor(
                 {
                     next_child += 1;
                 }
-                coll_last_written_child[collection_node_idx] =
-                    coll_child_order[&(collection_node_idx, next_child)] as i32;
-                collection_node_idx = coll_child_order[&(collection_node_idx, next_child)];
-
-                if link_collection_nodes[collection_node_idx].is_alias() && !first_delimiter_node {
-                    // Alliased Collection (First node in link_collection_nodes -> Last entry in report descriptor output)
-                    first_delimiter_node = true;
-                    main_item_list.push(MainItemNode::new(
-                        0,
-                        0,
-                        ItemNodeType::Collection,
-                        0,
-                        collection_node_idx,
-                        MainItems::DelimiterUsage,
-                        0,
-                    ));
-                    main_item_list.push(MainItemNode::new(
-                        0,
-                        0,
-                        ItemNodeType::Collection,
-                        0,
-                        collection_node_idx,
-                        MainItems::DelimiterClose,
-                        0,
-                    ));
-                } else if link_collection_nodes[collection_node_idx].is_alias()
-                    && first_delimiter_node
-                {
-                    main_item_list.insert(
-                        1,
-                        MainItemNode::new(
-                            0,
-                            0,
-                            ItemNodeType::Collection,
-                            0,
-                            collection_node_idx,
-                            MainItems::DelimiterUsage,
-                            0,
-                        ),
-                    );
-                } else if !link_collection_nodes[collection_node_idx].is_alias()
-                    && first_delimiter_node
-                {
-                    main_item_list.insert(
-                        1,
-                        MainItemNode::new(
-                            0,
-                            0,
-                            ItemNodeType::Collection,
-                            0,
-                            collection_node_idx,
-                            MainItems::DelimiterUsage,
-                            0,
-                        ),
-                    );
-                    main_item_list.insert(
-                        1,
-                        MainItemNode::new(
-                            0,
-                            0,
-                            ItemNodeType::Collection,
-                            0,
-                            collection_node_idx,
-                            MainItems::DelimiterClose,
-                            0,
-                        ),
-                    );
-                    first_delimiter_node = false;
-                }
-                if !link_collection_nodes[collection_node_idx].is_alias() {
-                    main_item_list.push(MainItemNode::new(
-                        0,
-                        0,
-                        ItemNodeType::Collection,
-                        0,
-                        collection_node_idx,
-                        MainItems::Collection,
-                        0,
-                    ));
+                let child = coll_child_order[&(collection_node_idx, next_child)];
+                coll_last_written_child[collection_node_idx] = child as i32;
+
+                if !link_collection_nodes[child].is_alias() {
+                    collection_node_idx = child;
+                    push_collection(&mut main_item_list, link_collection_nodes, child);
                     actual_coll_level += 1;
                 }
             } else {
@@ -404,14 +476,24 @@ fn reconstruct_descriptor(
     // ****************************************************************
     for rt_idx in ReportType::values() {
         // Add all value caps to node list
-        let mut first_delimiter_node = false;
+        let mut aliases = Vec::new();
         let caps_info = header.caps_info[rt_idx as usize];
         for caps_idx in caps_info.first_cap..caps_info.last_cap {
             let caps = caps_list[caps_idx as usize];
+            // In a HID Report Descriptor, the first usage declared is the most preferred usage for the control.
+            // While the order in the WIN32 capabiliy strutures is the opposite:
+            // Here the preferred usage is the last aliased usage in the sequence.
+            if caps.is_alias() {
+                aliases.push(caps_idx);
+                continue;
+            }
             let mut coll_begin = main_item_list
                 .iter()
-                .position(|node| node.collection_index == caps.link_collection as usize)
-                .unwrap();
+                .position(|node| {
+                    node.collection_index == caps.link_collection as usize
+                        && node.main_item_type == MainItems::Collection
+                })
+                .ok_or(PreparsedDataError::InvalidCaps)?;
             let (first_bit, last_bit) = {
                 let range = caps.get_bit_range();
                 (range.first_bit, range.last_bit)
@@ -429,106 +511,54 @@ fn reconstruct_descriptor(
                     break;
                 }
                 let index = coll_child_order[&(caps.link_collection as usize, child_idx)];
+                if link_collection_nodes[index].is_alias() {
+                    continue;
+                }
                 coll_begin = main_item_list
                     .iter()
                     .rposition(|node| node.collection_index == index)
-                    .unwrap();
+                    .ok_or(PreparsedDataError::InvalidCaps)?;
             }
-            let list_node = 1 + search_list(
+            let mut list_node = 1 + search_list(
                 first_bit as i32,
                 rt_idx.into(),
                 caps.report_id,
                 coll_begin,
                 &main_item_list,
-            );
-
-            // In a HID Report Descriptor, the first usage declared is the most preferred usage for the control.
-            // While the order in the WIN32 capabiliy strutures is the opposite:
-            // Here the preferred usage is the last aliased usage in the sequence.
-
-            if caps.is_alias() && !first_delimiter_node {
-                // Alliased Usage (First node in pp_data->caps -> Last entry in report descriptor output)
-                first_delimiter_node = true;
-                main_item_list.insert(
-                    list_node,
-                    MainItemNode::new(
-                        first_bit,
-                        last_bit,
-                        ItemNodeType::Cap,
-                        caps_idx as i32,
-                        caps.link_collection as usize,
-                        MainItems::DelimiterUsage,
-                        caps.report_id,
-                    ),
-                );
-                main_item_list.insert(
-                    list_node,
-                    MainItemNode::new(
-                        first_bit,
-                        last_bit,
-                        ItemNodeType::Cap,
-                        caps_idx as i32,
-                        caps.link_collection as usize,
-                        MainItems::DelimiterClose,
-                        caps.report_id,
-                    ),
-                );
-            } else if caps.is_alias() && first_delimiter_node {
-                main_item_list.insert(
-                    list_node,
-                    MainItemNode::new(
-                        first_bit,
-                        last_bit,
-                        ItemNodeType::Cap,
-                        caps_idx as i32,
-                        caps.link_collection as usize,
-                        MainItems::DelimiterUsage,
-                        caps.report_id,
-                    ),
-                );
-            } else if !caps.is_alias() && first_delimiter_node {
-                // Alliased Collection (Last node in pp_data->caps -> First entry in report descriptor output)
-                main_item_list.insert(
-                    list_node,
-                    MainItemNode::new(
-                        first_bit,
-                        last_bit,
-                        ItemNodeType::Cap,
-                        caps_idx as i32,
-                        caps.link_collection as usize,
-                        MainItems::DelimiterUsage,
-                        caps.report_id,
-                    ),
-                );
-                main_item_list.insert(
-                    list_node,
-                    MainItemNode::new(
-                        first_bit,
-                        last_bit,
-                        ItemNodeType::Cap,
-                        caps_idx as i32,
-                        caps.link_collection as usize,
-                        MainItems::DelimiterOpen,
-                        caps.report_id,
-                    ),
-                );
-                first_delimiter_node = false;
+            )?;
+            // Keep delimiter sets together with their main item or collection
+            while list_node > 0 && main_item_list[list_node - 1].is_delimiter() {
+                list_node -= 1;
             }
-            if !caps.is_alias() {
-                main_item_list.insert(
-                    list_node,
-                    MainItemNode::new(
-                        first_bit,
-                        last_bit,
-                        ItemNodeType::Cap,
-                        caps_idx as i32,
-                        caps.link_collection as usize,
-                        rt_idx.into(),
-                        caps.report_id,
-                    ),
+
+            let node = |caps_idx: u16, main_item_type| {
+                MainItemNode::new(
+                    first_bit,
+                    last_bit,
+                    ItemNodeType::Cap,
+                    caps_idx as i32,
+                    caps.link_collection as usize,
+                    main_item_type,
+                    caps.report_id,
+                )
+            };
+            let mut nodes = Vec::new();
+            if !aliases.is_empty() {
+                nodes.push(node(caps_idx, MainItems::DelimiterOpen));
+                nodes.push(node(caps_idx, MainItems::DelimiterUsage));
+                nodes.extend(
+                    aliases
+                        .drain(..)
+                        .rev()
+                        .map(|i| node(i, MainItems::DelimiterUsage)),
                 );
+                nodes.push(node(caps_idx, MainItems::DelimiterClose));
             }
+            nodes.push(node(caps_idx, rt_idx.into()));
+            main_item_list.splice(list_node..list_node, nodes);
         }
+        // Aliases without a preferred usage
+        ensure!(aliases.is_empty(), Err(PreparsedDataError::InvalidCaps));
     }
 
     // ***********************************************************
@@ -563,12 +593,12 @@ fn reconstruct_descriptor(
                         current.report_id,
                         lrip.unwrap(),
                         &main_item_list,
-                    );
+                    )?;
                     main_item_list.insert(
                         list_node + 1,
                         MainItemNode::new(
                             (lbp + 1) as u16,
-                            current.first_bit - 1,
+                            current.first_bit.wrapping_sub(1),
                             ItemNodeType::Padding,
                             -1,
                             0,
@@ -617,7 +647,45 @@ fn reconstruct_descriptor(
             }
         }
     }
-    main_item_list
+    Ok(main_item_list)
+}
+
+/// Add a collection to the main item list, preceded by its aliases
+///
+/// The aliases are the nodes before the collection, the one declared last in
+/// the report descriptor first.
+fn push_collection(
+    main_item_list: &mut Vec<MainItemNode>,
+    link_collection_nodes: &[LinkCollectionNode],
+    collection_node_idx: usize,
+) {
+    let node = |collection_node_idx, main_item_type| {
+        MainItemNode::new(
+            0,
+            0,
+            ItemNodeType::Collection,
+            0,
+            collection_node_idx,
+            main_item_type,
+            0,
+        )
+    };
+    let parent = link_collection_nodes[collection_node_idx].parent;
+    let aliases = (1..collection_node_idx)
+        .rev()
+        .take_while(|i| {
+            link_collection_nodes[*i].is_alias() && link_collection_nodes[*i].parent == parent
+        })
+        .collect::<Vec<_>>();
+    if !aliases.is_empty() {
+        main_item_list.push(node(collection_node_idx, MainItems::DelimiterOpen));
+        main_item_list.push(node(collection_node_idx, MainItems::DelimiterUsage));
+        for alias in aliases {
+            main_item_list.push(node(alias, MainItems::DelimiterUsage));
+        }
+        main_item_list.push(node(collection_node_idx, MainItems::DelimiterClose));
+    }
+    main_item_list.push(node(collection_node_idx, MainItems::Collection));
 }
 
 fn search_list(
@@ -626,7 +694,7 @@ fn search_list(
     report_id: u8,
     start: usize,
     list: &[MainItemNode],
-) -> usize {
+) -> PreparsedDataResult<usize> {
     list[start..]
         .iter()
         .peaking()
@@ -639,6 +707,6 @@ fn search_list(
                         && next.main_item_type == main_item_type)
             })
         })
-        .unwrap()
-        + start
+        .map(|index| index + start)
+        .ok_or(PreparsedDataError::InvalidCaps)
 }
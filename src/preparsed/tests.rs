@@ -0,0 +1,368 @@
+use crate::preparsed::typedefs::{Caps, HidpPreparsedData};
+use crate::preparsed::types::ReportType;
+use crate::preparsed::{
+    compile_descriptor, extract_structures, reconstruct_descriptor, PreparsedDataError,
+};
+use std::fs::{read_dir, read_to_string};
+use std::mem::size_of;
+
+#[test]
+fn test_01() {
+    execute_testcase("045E_02FF_0005_0001");
+}
+#[test]
+fn test_02() {
+    execute_testcase("046A_0011_0006_0001");
+}
+#[test]
+fn test_03() {
+    execute_testcase("046D_0A37_0001_000C");
+}
+#[test]
+fn test_04() {
+    execute_testcase("046D_B010_0001_000C");
+}
+#[test]
+fn test_05() {
+    execute_testcase("046D_B010_0001_FF00");
+}
+#[test]
+fn test_06() {
+    execute_testcase("046D_B010_0002_0001");
+}
+#[test]
+fn test_07() {
+    execute_testcase("046D_B010_0002_FF00");
+}
+#[test]
+fn test_08() {
+    execute_testcase("046D_B010_0006_0001");
+}
+#[test]
+fn test_09() {
+    execute_testcase("046D_C077_0002_0001");
+}
+#[test]
+fn test_10() {
+    execute_testcase("046D_C283_0004_0001");
+}
+#[test]
+fn test_11() {
+    execute_testcase("046D_C52F_0001_000C");
+}
+#[test]
+fn test_12() {
+    execute_testcase("046D_C52F_0001_FF00");
+}
+#[test]
+fn test_13() {
+    execute_testcase("046D_C52F_0002_0001");
+}
+#[test]
+fn test_14() {
+    execute_testcase("046D_C52F_0002_FF00");
+}
+#[test]
+fn test_15() {
+    execute_testcase("046D_C534_0001_000C");
+}
+#[test]
+fn test_16() {
+    execute_testcase("046D_C534_0001_FF00");
+}
+#[test]
+fn test_17() {
+    execute_testcase("046D_C534_0002_0001");
+}
+#[test]
+fn test_18() {
+    execute_testcase("046D_C534_0002_FF00");
+}
+#[test]
+fn test_19() {
+    execute_testcase("046D_C534_0006_0001");
+}
+#[test]
+fn test_20() {
+    execute_testcase("046D_C534_0080_0001");
+}
+#[test]
+fn test_21() {
+    execute_testcase("047F_C056_0001_000C");
+}
+#[test]
+fn test_22() {
+    execute_testcase("047F_C056_0003_FFA0");
+}
+#[test]
+fn test_23() {
+    execute_testcase("047F_C056_0005_000B");
+}
+#[test]
+fn test_24() {
+    execute_testcase("17CC_1130_0000_FF01");
+}
+
+#[test]
+fn test_truncated() {
+    let pp_data = load_pp_data("046D_C077_0002_0001");
+    for len in [0, 10, pp_data.len() - 1] {
+        assert!(matches!(
+            reconstruct_descriptor(&pp_data[..len]),
+            Err(PreparsedDataError::Truncated { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_invalid_magic_key() {
+    let mut pp_data = load_pp_data("046D_C077_0002_0001");
+    pp_data[0] = b'X';
+    assert_eq!(
+        reconstruct_descriptor(&pp_data),
+        Err(PreparsedDataError::InvalidMagicKey)
+    );
+}
+
+#[test]
+fn test_invalid_link_collections() {
+    let mut pp_data = load_pp_data("045E_02FF_0005_0001");
+    let link_offset = 44 + u16::from_le_bytes([pp_data[40], pp_data[41]]) as usize;
+    // Make the first child of the root collection its own sibling
+    let first_child = u16::from_le_bytes([pp_data[link_offset + 10], pp_data[link_offset + 11]]);
+    let child_offset = link_offset + first_child as usize * 16;
+    pp_data[child_offset + 8..child_offset + 10].copy_from_slice(&first_child.to_le_bytes());
+    assert_eq!(
+        reconstruct_descriptor(&pp_data),
+        Err(PreparsedDataError::InvalidLinkCollections)
+    );
+}
+
+#[test]
+fn test_invalid_caps() {
+    let mut pp_data = load_pp_data("046D_C077_0002_0001");
+    // Point the link collection of the first capability beyond the last node
+    pp_data[44 + 18..44 + 20].copy_from_slice(&0xFFFFu16.to_le_bytes());
+    assert_eq!(
+        reconstruct_descriptor(&pp_data),
+        Err(PreparsedDataError::InvalidCaps)
+    );
+}
+
+#[test]
+fn test_caps_bit_range_overflow() {
+    fn with_bit_range(byte_position: u16, report_size: u16, report_count: u16) -> Vec<u8> {
+        let mut pp_data = load_pp_data("046D_C077_0002_0001");
+        pp_data[44 + 4..44 + 6].copy_from_slice(&report_size.to_le_bytes());
+        pp_data[44 + 6..44 + 8].copy_from_slice(&report_count.to_le_bytes());
+        pp_data[44 + 8..44 + 10].copy_from_slice(&byte_position.to_le_bytes());
+        pp_data
+    }
+
+    // The last bit of the first capability is 0xFFFF
+    assert!(reconstruct_descriptor(&with_bit_range(0x2000, 8, 1)).is_ok());
+    for (byte_position, report_size, report_count) in [
+        (0x2000, 16, 1),
+        (0xFFFF, 1, 1),
+        (1, 0x100, 0x100),
+        (0, 1, 1),
+        (1, 0, 1),
+    ] {
+        assert_eq!(
+            reconstruct_descriptor(&with_bit_range(byte_position, report_size, report_count)),
+            Err(PreparsedDataError::InvalidCaps)
+        );
+    }
+}
+
+/// Fixtures whose preparsed data the compiler cannot reproduce, because the
+/// `.expected` descriptor is a reconstruction that differs from the one the
+/// device sent to Windows
+const KNOWN_DIVERGENCES: [(&str, &str); 10] = [
+    (
+        "045E_02FF_0005_0001",
+        "drops 2 bytes of trailing padding, adds a padding item",
+    ),
+    ("046A_0011_0006_0001", "adds padding items"),
+    ("046D_B010_0006_0001", "adds padding items"),
+    ("046D_C283_0004_0001", "adds a padding item"),
+    ("046D_C534_0006_0001", "adds padding items"),
+    (
+        "046D_C534_0080_0001",
+        "adds a padding item, the reserved header bytes are set",
+    ),
+    ("047F_C056_0001_000C", "adds padding items"),
+    ("047F_C056_0003_FFA0", "adds padding items"),
+    ("047F_C056_0005_000B", "adds padding items"),
+    ("17CC_1130_0000_FF01", "merges two adjacent main items"),
+];
+
+#[test]
+fn test_compile_descriptor() {
+    let mut fixtures = read_dir("./tests/pp_data")
+        .unwrap()
+        .filter_map(|entry| {
+            let path = entry.unwrap().path();
+            (path.extension()? == "pp_data")
+                .then(|| path.file_stem()?.to_str().map(String::from))?
+        })
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert_eq!(fixtures.len(), 24);
+
+    for filename in fixtures {
+        println!("Testing: {filename}");
+        let mut pp_data = load_pp_data(&filename);
+        let expected_descriptor =
+            decode_hex(&read_to_string(format!("./tests/pp_data/{filename}.expected")).unwrap());
+        let compiled = compile_descriptor(&expected_descriptor).unwrap();
+        assert_eq!(compiled.len(), 1);
+        assert_eq!(
+            reconstruct_descriptor(&compiled[0]).unwrap(),
+            expected_descriptor
+        );
+
+        clear_uninitialized(&mut pp_data);
+        if KNOWN_DIVERGENCES.iter().any(|(name, _)| *name == filename) {
+            assert_ne!(compiled[0], pp_data, "{filename} is no longer divergent");
+        } else {
+            assert_eq!(compiled[0], pp_data);
+        }
+    }
+}
+
+#[test]
+fn test_compile_out_of_range() {
+    // Usage Minimum 0 and Usage Maximum 0xFFFF take all data indices
+    let mut descriptor = vec![
+        0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, 0x19, 0x00, 0x2A, 0xFF, 0xFF, 0x15, 0x00, 0x25, 0x01,
+        0x75, 0x01, 0x95, 0x01, 0x81, 0x02,
+    ];
+    let (_, caps_list, _) = extract_structures(
+        &compile_descriptor(&[descriptor.as_slice(), &[0xC0]].concat()).unwrap()[0],
+    )
+    .unwrap();
+    let range = caps_list[0].range();
+    assert_eq!((range.data_index_min, range.data_index_max), (0, 0xFFFF));
+
+    // So there is none left for the next control
+    descriptor.extend([0x09, 0x30, 0x75, 0x08, 0x81, 0x02, 0xC0]);
+    assert_eq!(
+        compile_descriptor(&descriptor),
+        Err(PreparsedDataError::ValueOutOfRange)
+    );
+    // 4096 values of 32 bits are more bits than a capability can count
+    assert_eq!(
+        compile_descriptor(&[
+            0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0x09, 0x02, 0x75, 0x20, 0x96, 0x00, 0x10,
+            0x81, 0x02, 0xC0
+        ]),
+        Err(PreparsedDataError::ValueOutOfRange)
+    );
+    assert!(matches!(
+        compile_descriptor(&[0xC0]),
+        Err(PreparsedDataError::InvalidDescriptor(_))
+    ));
+}
+
+#[test]
+fn test_compile_aliases() {
+    let descriptor = [
+        0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, // Mouse
+        0xA9, 0x01, 0x09, 0x01, 0x09, 0x03, 0xA9, 0x00, 0xA1, 0x00, // Pointer or Reserved
+        0xA9, 0x01, 0x09, 0x30, 0x09, 0x32, 0xA9, 0x00, 0x09, 0x31, // X or Z, Y
+        0x15, 0x81, 0x25, 0x7F, 0x75, 0x08, 0x95, 0x02, 0x81, 0x06, 0xC0, 0xC0,
+    ];
+    let compiled = compile_descriptor(&descriptor).unwrap();
+    let (_, caps_list, nodes) = extract_structures(&compiled[0]).unwrap();
+
+    // The alias Z precedes X and shares its data index
+    let caps = caps_list
+        .iter()
+        .map(|c| (c.range().usage_min, c.is_alias(), c.range().data_index_min))
+        .collect::<Vec<_>>();
+    assert_eq!(caps, [(0x31, false, 0), (0x32, true, 1), (0x30, false, 1)]);
+    assert!(caps_list.iter().all(|c| c.link_collection == 2));
+
+    // Likewise the node of the alias Reserved precedes Pointer
+    let nodes = nodes
+        .iter()
+        .map(|n| {
+            (
+                n.link_usage,
+                n.is_alias(),
+                n.parent,
+                n.first_child,
+                n.next_sibling,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        [
+            (0x02, false, 0, 2, 0),
+            (0x03, true, 0, 0, 0),
+            (0x01, false, 0, 0, 1)
+        ]
+    );
+
+    // Both delimiter sets survive the reconstruction
+    assert_eq!(reconstruct_descriptor(&compiled[0]).unwrap(), descriptor);
+}
+
+/// Zero the bytes of a fixture that Windows does not initialize, like the
+/// compiler does
+///
+/// These are the reserved bytes after the capability flags, the unused end of
+/// the logical range union of button capabilities, and the slots reserved for
+/// padding main items. The fixtures were captured with a debug heap, which
+/// fills them with 0xCD.
+fn clear_uninitialized(pp_data: &mut [u8]) {
+    let (header, caps_list, _) = extract_structures(pp_data).unwrap();
+    let mut ranges = Vec::new();
+    for report_type in ReportType::values() {
+        let info = header.caps_info[report_type as usize];
+        for index in info.first_cap..info.first_cap + info.number_of_caps {
+            let offset = size_of::<HidpPreparsedData>() + index as usize * size_of::<Caps>();
+            if index >= info.last_cap {
+                ranges.push(offset..offset + size_of::<Caps>());
+                continue;
+            }
+            ranges.push(offset + 25..offset + 28);
+            if caps_list[index as usize].is_button_cap() {
+                ranges.push(offset + 84..offset + 96);
+            }
+        }
+    }
+    for range in ranges {
+        let bytes = &mut pp_data[range];
+        assert!(bytes.iter().all(|b| *b == 0 || *b == 0xCD), "{bytes:02X?}");
+        bytes.fill(0);
+    }
+}
+
+fn load_pp_data(filename: &str) -> Vec<u8> {
+    decode_hex(&read_to_string(format!("./tests/pp_data/{filename}.pp_data")).unwrap())
+}
+
+fn execute_testcase(filename: &str) {
+    let source_path = format!("./tests/pp_data/{filename}.pp_data");
+    let expected_path = format!("./tests/pp_data/{filename}.expected");
+    println!("Testing: {:?} <-> {:?}", source_path, expected_path);
+    let pp_data = decode_hex(&read_to_string(&source_path).unwrap());
+    let expected_descriptor = decode_hex(&read_to_string(&expected_path).unwrap());
+    let constructed_descriptor = reconstruct_descriptor(&pp_data).unwrap();
+    assert_eq!(constructed_descriptor, expected_descriptor);
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    hex.lines()
+        .flat_map(|line| {
+            line.split(',')
+                .map(|hex| hex.trim())
+                .filter(|hex| !hex.is_empty())
+                .map(|hex| hex.strip_prefix("0x").unwrap())
+                .map(|hex| u8::from_str_radix(hex, 16).unwrap())
+        })
+        .collect()
+}
@@ -1,11 +1,10 @@
-use crate::windows_native::descriptor::types::BitRange;
+use crate::preparsed::types::BitRange;
 use std::mem::size_of;
 
 // Reverse engineered typedefs for the internal structure of the preparsed data taken from
 // https://github.com/libusb/hidapi/blob/master/windows/hidapi_descriptor_reconstruct.h
 // https://github.com/libusb/hidapi/pull/306
 
-#[macro_export]
 macro_rules! const_assert {
     ($x:expr $(,)?) => {
         #[allow(unknown_lints)]
@@ -179,9 +178,23 @@ impl Caps {
         unsafe { self.maybe_button.not_button }
     }
 
+    pub fn set_range(&mut self, range: Range) {
+        self.maybe_range.range = range;
+    }
+
+    pub fn button_mut(&mut self) -> &mut Button {
+        //Both union elements are valid for all bit patterns
+        unsafe { &mut self.maybe_button.button }
+    }
+
+    pub fn not_button_mut(&mut self) -> &mut NotButton {
+        //Both union elements are valid for all bit patterns
+        unsafe { &mut self.maybe_button.not_button }
+    }
+
     pub fn get_bit_range(&self) -> BitRange {
         let first_bit = (self.byte_position - 1) * 8 + self.bit_position as u16;
-        let last_bit = first_bit + self.report_size * self.report_count - 1;
+        let last_bit = first_bit + (self.report_size * self.report_count - 1);
         BitRange {
             first_bit,
             last_bit,
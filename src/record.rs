@@ -0,0 +1,853 @@
+//! Record the traffic of a device to a file, and replay it without hardware
+//!
+//! A [`RecordingDevice`] wraps a [`HidDevice`] and writes every Output,
+//! Input and Feature report to a recording. [`Replay`] reads the recording
+//! back and serves it as a [`HidDevice`], or as a [`Backend`] for
+//! [`HidApi::with_backend`](crate::HidApi::with_backend), so bugs can be
+//! reproduced without the device.
+//!
+//! ```no_run
+//! use hidapi::record::{RecordingDevice, Replay};
+//! use hidapi::HidApi;
+//! use std::fs::File;
+//!
+//! let api = HidApi::new()?;
+//! let device = api.open(0x046D, 0xC534)?;
+//! let device = RecordingDevice::new(device, File::create("session.hidrec")?)?.into_device();
+//! device.write(&[0x10, 0xFF, 0x00, 0x10])?;
+//! drop(device);
+//!
+//! let replay = Replay::read(File::open("session.hidrec")?)?;
+//! let device = replay.open();
+//! device.write(&[0x10, 0xFF, 0x00, 0x10])?;
+//! # Ok::<(), hidapi::HidError>(())
+//! ```
+//!
+//! # File format
+//!
+//! All integers are little endian. A recording starts with the magic bytes
+//! `HIDREC`, followed by the version byte 1 and a sequence of records:
+//!
+//! | Field   | Size         | Content                                               |
+//! |---------|--------------|-------------------------------------------------------|
+//! | kind    | 1            | see below                                             |
+//! | time    | 8            | microseconds since the recording started              |
+//! | status  | 1            | 0 if the call succeeded, 1 if it failed               |
+//! | length  | 4            | length of `data`                                      |
+//! | data    | length       | the report, see below                                 |
+//! | message | 4 + variable | only if `status` is 1, the length and UTF-8 error text |
+//!
+//! The first record is the [`DeviceInfo`] with kind `0x01`, and the second
+//! one the report descriptor with kind `0x02`. Then one record follows for
+//! each call of the device:
+//!
+//! | Kind   | Call                                        | `data`                                |
+//! |--------|---------------------------------------------|---------------------------------------|
+//! | `0x10` | [`write`](HidDevice::write)                 | the Output report                     |
+//! | `0x11` | [`read`](HidDevice::read), `read_timeout`   | the Input report, empty on a timeout  |
+//! | `0x12` | [`get_feature_report`](HidDevice::get_feature_report)   | the report, or only its report ID if the call failed |
+//! | `0x13` | [`send_feature_report`](HidDevice::send_feature_report) | the Feature report |
+//! | `0x14` | [`get_input_report`](HidDevice::get_input_report)       | the report, or only its report ID if the call failed |
+//! | `0x15` | [`send_output_report`](HidDevice::send_output_report)   | the Output report |
+//!
+//! The [`DeviceInfo`] is encoded as its fields in declaration order:
+//! the path, VID, PID, serial number, release number, manufacturer string,
+//! product string, usage page, usage, interface number, bus type and
+//! physical path. Numbers have their Rust types, and the bus type is a `u8`.
+//! Strings are a `u16` length and UTF-8 bytes. The path and the other strings
+//! are preceded by a `u8` that is 0 if the string is missing. Recordings
+//! from before the physical path end after the bus type. The usage page and
+//! usage are stored as the backend reported them, also with the Linux libusb
+//! backends, where [`DeviceInfo`] has no accessors for them.
+//!
+//! # pcapng
+//!
+//! [`RecordingDevice::pcapng`] writes the traffic as a pcapng capture
+//! instead, and [`Replay::write_pcapng`] converts a recording to one. The
+//! reports appear as USB transfers of the link type
+//! `LINKTYPE_USB_LINUX_MMAPPED`, the same as a capture with usbmon, so the
+//! HID dissector of Wireshark decodes them with the report descriptor:
+//!
+//! - Output reports are interrupt transfers to endpoint 0x01
+//! - Input reports are interrupt transfers from endpoint 0x81
+//! - Feature reports, and the Input and Output reports of
+//!   [`get_input_report`](HidDevice::get_input_report) and
+//!   [`send_output_report`](HidDevice::send_output_report), are `SET_REPORT`
+//!   and `GET_REPORT` control transfers
+//!
+//! The transfers are synthesized from the calls of the device, also for
+//! devices that are not connected by USB, and timeouts of reads are left out.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{
+    Backend, BusType, DeviceBackend, DeviceInfo, HidDevice, HidError, HidResult,
+    MAX_REPORT_DESCRIPTOR_SIZE,
+};
+
+mod pcapng;
+
+const MAGIC: &[u8] = b"HIDREC";
+const VERSION: u8 = 1;
+
+const KIND_INFO: u8 = 0x01;
+const KIND_REPORT_DESCRIPTOR: u8 = 0x02;
+
+/// The kind of call a recorded [`Event`] stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Write,
+    Read,
+    GetFeatureReport,
+    SendFeatureReport,
+    GetInputReport,
+    SendOutputReport,
+}
+
+impl EventKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            EventKind::Write => 0x10,
+            EventKind::Read => 0x11,
+            EventKind::GetFeatureReport => 0x12,
+            EventKind::SendFeatureReport => 0x13,
+            EventKind::GetInputReport => 0x14,
+            EventKind::SendOutputReport => 0x15,
+        }
+    }
+
+    fn from_byte(kind: u8) -> Option<Self> {
+        match kind {
+            0x10 => Some(EventKind::Write),
+            0x11 => Some(EventKind::Read),
+            0x12 => Some(EventKind::GetFeatureReport),
+            0x13 => Some(EventKind::SendFeatureReport),
+            0x14 => Some(EventKind::GetInputReport),
+            0x15 => Some(EventKind::SendOutputReport),
+            _ => None,
+        }
+    }
+}
+
+/// A recorded call of the device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// When the call returned, relative to the start of the recording
+    pub time: Duration,
+    pub kind: EventKind,
+    /// The report, see the [file format](self#file-format)
+    pub data: Vec<u8>,
+    /// The error message if the call failed
+    pub error: Option<String>,
+}
+
+/// Records the traffic of a [`HidDevice`] while passing the calls through
+///
+/// The recording is written and flushed as the calls happen, so it is
+/// complete up to the last call even if the application crashes. The first
+/// error writing the recording stops it, and is reported by
+/// [`finish`](RecordingDevice::finish).
+pub struct RecordingDevice<W: Write + Send> {
+    device: HidDevice,
+    recording: RefCell<Recording<W>>,
+}
+
+struct Recording<W> {
+    writer: W,
+    format: Format,
+    start: Instant,
+    error: Option<io::Error>,
+}
+
+enum Format {
+    Native,
+    Pcapng(pcapng::Encoder),
+}
+
+impl<W: Write + Send> RecordingDevice<W> {
+    /// Start recording with the device information and report descriptor
+    pub fn new(device: HidDevice, writer: W) -> HidResult<Self> {
+        Self::start(device, writer, |_| Format::Native)
+    }
+
+    /// Start capturing the traffic as USB packets for Wireshark, see
+    /// [pcapng](self#pcapng)
+    pub fn pcapng(device: HidDevice, writer: W) -> HidResult<Self> {
+        let start = SystemTime::now();
+        Self::start(device, writer, |info| {
+            Format::Pcapng(pcapng::Encoder::new(info, start))
+        })
+    }
+
+    fn start(
+        device: HidDevice,
+        writer: W,
+        format: impl FnOnce(&DeviceInfo) -> Format,
+    ) -> HidResult<Self> {
+        let info = device.get_device_info()?;
+        let mut buf = [0u8; MAX_REPORT_DESCRIPTOR_SIZE];
+        let descriptor = device
+            .get_report_descriptor(&mut buf)
+            .map(|len| &buf[..len]);
+
+        let mut recording = Recording {
+            writer,
+            format: format(&info),
+            start: Instant::now(),
+            error: None,
+        };
+        recording.write_header(&info, descriptor)?;
+
+        Ok(Self {
+            device,
+            recording: RefCell::new(recording),
+        })
+    }
+
+    /// Use the recording device wherever a [`HidDevice`] is expected
+    ///
+    /// The recording ends when the returned device is dropped.
+    pub fn into_device(self) -> HidDevice
+    where
+        W: 'static,
+    {
+        HidDevice::from_backend(Box::new(self))
+    }
+
+    /// Stop recording and get the device and the writer back
+    pub fn finish(self) -> HidResult<(HidDevice, W)> {
+        let recording = self.recording.into_inner();
+        match recording.error {
+            Some(error) => Err(error.into()),
+            None => Ok((self.device, recording.writer)),
+        }
+    }
+
+    fn record<T>(&self, kind: EventKind, data: &[u8], result: &HidResult<T>) {
+        let mut recording = self.recording.borrow_mut();
+        if recording.error.is_some() {
+            return;
+        }
+        if let Err(e) = recording.write_event(kind, data, result.as_ref().err()) {
+            recording.error = Some(e);
+        }
+    }
+
+    /// Record a call that gets a report, or only its report ID if it failed
+    fn record_get(
+        &self,
+        kind: EventKind,
+        buf: &mut [u8],
+        get: fn(&HidDevice, &mut [u8]) -> HidResult<usize>,
+    ) -> HidResult<usize> {
+        let report_id = buf.first().copied();
+        let result = get(&self.device, buf);
+        match &result {
+            Ok(len) => self.record(kind, &buf[..*len], &result),
+            Err(_) => self.record(kind, report_id.as_slice(), &result),
+        }
+        result
+    }
+}
+
+impl<W: Write> Recording<W> {
+    fn write_header(&mut self, info: &DeviceInfo, descriptor: HidResult<&[u8]>) -> io::Result<()> {
+        let header = match &mut self.format {
+            Format::Native => {
+                let mut header = MAGIC.to_vec();
+                header.push(VERSION);
+                encode_record(&mut header, KIND_INFO, 0, &encode_info(info), None);
+                match &descriptor {
+                    Ok(descriptor) => {
+                        encode_record(&mut header, KIND_REPORT_DESCRIPTOR, 0, descriptor, None)
+                    }
+                    Err(e) => encode_record(&mut header, KIND_REPORT_DESCRIPTOR, 0, &[], Some(e)),
+                }
+                header
+            }
+            Format::Pcapng(encoder) => encoder.header(info, descriptor.unwrap_or_default()),
+        };
+        self.writer.write_all(&header)?;
+        self.writer.flush()
+    }
+
+    fn write_event(
+        &mut self,
+        kind: EventKind,
+        data: &[u8],
+        error: Option<&HidError>,
+    ) -> io::Result<()> {
+        let time = self.start.elapsed();
+        let record = match &mut self.format {
+            Format::Native => {
+                let mut record = Vec::new();
+                let time = time.as_micros() as u64;
+                encode_record(&mut record, kind.to_byte(), time, data, error);
+                record
+            }
+            Format::Pcapng(encoder) => encoder.event(time, kind, data, error.is_some()),
+        };
+        self.writer.write_all(&record)?;
+        self.writer.flush()
+    }
+}
+
+fn encode_record(buf: &mut Vec<u8>, kind: u8, time: u64, data: &[u8], error: Option<&HidError>) {
+    buf.push(kind);
+    buf.extend_from_slice(&time.to_le_bytes());
+    buf.push(error.is_some() as u8);
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    if let Some(error) = error {
+        let message = error.to_string();
+        buf.extend_from_slice(&(message.len() as u32).to_le_bytes());
+        buf.extend_from_slice(message.as_bytes());
+    }
+}
+
+impl<W: Write + Send> fmt::Debug for RecordingDevice<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingDevice")
+            .field("device", &self.device)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: Write + Send> DeviceBackend for RecordingDevice<W> {
+    fn write(&self, data: &[u8]) -> HidResult<usize> {
+        let result = self.device.write(data);
+        self.record(EventKind::Write, data, &result);
+        result
+    }
+
+    fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let result = self.device.read(buf);
+        let len = *result.as_ref().unwrap_or(&0);
+        self.record(EventKind::Read, &buf[..len], &result);
+        result
+    }
+
+    fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> HidResult<usize> {
+        let result = self.device.read_timeout(buf, timeout);
+        let len = *result.as_ref().unwrap_or(&0);
+        self.record(EventKind::Read, &buf[..len], &result);
+        result
+    }
+
+    fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+        let result = self.device.send_feature_report(data);
+        self.record(EventKind::SendFeatureReport, data, &result);
+        result
+    }
+
+    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.record_get(
+            EventKind::GetFeatureReport,
+            buf,
+            HidDevice::get_feature_report,
+        )
+    }
+
+    fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.record_get(EventKind::GetInputReport, buf, HidDevice::get_input_report)
+    }
+
+    fn send_output_report(&self, data: &[u8]) -> HidResult<()> {
+        let result = self.device.send_output_report(data);
+        self.record(EventKind::SendOutputReport, data, &result);
+        result
+    }
+
+    fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
+        self.device.set_blocking_mode(blocking)
+    }
+
+    fn get_device_info(&self) -> HidResult<DeviceInfo> {
+        self.device.get_device_info()
+    }
+
+    fn get_manufacturer_string(&self) -> HidResult<Option<String>> {
+        self.device.get_manufacturer_string()
+    }
+
+    fn get_product_string(&self) -> HidResult<Option<String>> {
+        self.device.get_product_string()
+    }
+
+    fn get_serial_number_string(&self) -> HidResult<Option<String>> {
+        self.device.get_serial_number_string()
+    }
+
+    fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.device.get_report_descriptor(buf)
+    }
+
+    fn get_indexed_string(&self, index: i32) -> HidResult<Option<String>> {
+        self.device.get_indexed_string(index)
+    }
+
+    #[cfg(unix)]
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd<'_>> {
+        self.device.as_fd()
+    }
+}
+
+/// A recording read back from a file
+///
+/// Opening it gives a [`HidDevice`] that answers each call with the next
+/// recorded event of the same kind. Writes are not compared with the
+/// recording. Recorded errors are returned as [`HidError::HidApiError`], and
+/// all calls fail once the events of their kind are used up.
+///
+/// As a [`Backend`] it enumerates the recorded device, so it can stand in for
+/// the system with [`HidApi::with_backend`](crate::HidApi::with_backend).
+#[derive(Debug, Clone)]
+pub struct Replay {
+    info: DeviceInfo,
+    report_descriptor: Vec<u8>,
+    events: Vec<Event>,
+}
+
+impl Replay {
+    pub fn read(mut reader: impl Read) -> HidResult<Self> {
+        let mut header = [0u8; 7];
+        reader.read_exact(&mut header)?;
+        if header[..6] != *MAGIC || header[6] != VERSION {
+            return Err(invalid_data("not a recording of version 1"));
+        }
+
+        let mut records = Vec::new();
+        while let Some(record) = read_record(&mut reader)? {
+            records.push(record);
+        }
+        let mut records = records.into_iter();
+        let info = match records.next() {
+            Some((KIND_INFO, event)) => decode_info(&event.data)?,
+            _ => return Err(invalid_data("missing device information")),
+        };
+        let report_descriptor = match records.next() {
+            Some((KIND_REPORT_DESCRIPTOR, event)) => event.data,
+            _ => return Err(invalid_data("missing report descriptor")),
+        };
+        let events = records
+            .map(|(kind, event)| match EventKind::from_byte(kind) {
+                Some(kind) => Ok(Event { kind, ..event }),
+                None => Err(invalid_data("unknown record kind")),
+            })
+            .collect::<HidResult<_>>()?;
+
+        Ok(Self {
+            info,
+            report_descriptor,
+            events,
+        })
+    }
+
+    pub fn info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    pub fn report_descriptor(&self) -> &[u8] {
+        &self.report_descriptor
+    }
+
+    /// The recorded calls, in the order they happened
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Convert the recording to USB packets for Wireshark, see
+    /// [pcapng](self#pcapng)
+    ///
+    /// The recording does not store the wall clock time, so the packets are
+    /// timestamped as if it started at the Unix epoch.
+    pub fn write_pcapng(&self, mut writer: impl Write) -> HidResult<()> {
+        let mut encoder = pcapng::Encoder::new(&self.info, SystemTime::UNIX_EPOCH);
+        writer.write_all(&encoder.header(&self.info, &self.report_descriptor))?;
+        for event in &self.events {
+            let failed = event.error.is_some();
+            writer.write_all(&encoder.event(event.time, event.kind, &event.data, failed))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Replay the recording from the start
+    ///
+    /// Each kind of call replays the events of its kind in the recorded
+    /// order, independent of the other kinds. Reads return the recorded
+    /// Input reports even if the calls before them differ, e.g. if a write
+    /// was left out. A call fails with the recorded error, or once the events
+    /// of its kind are used up.
+    pub fn open(&self) -> HidDevice {
+        HidDevice::from_backend(Box::new(ReplayDevice {
+            replay: Arc::new(self.clone()),
+            next: Mutex::new(HashMap::new()),
+        }))
+    }
+}
+
+impl Backend for Replay {
+    fn enumerate(&self, vid: u16, pid: u16) -> HidResult<Vec<DeviceInfo>> {
+        let matches = (vid == 0 || vid == self.info.vendor_id())
+            && (pid == 0 || pid == self.info.product_id());
+        Ok(matches.then(|| self.info.clone()).into_iter().collect())
+    }
+
+    fn open_path(&self, device_path: &CStr) -> HidResult<HidDevice> {
+        if device_path != self.info.path() {
+            return Err(HidError::HidApiError {
+                message: "device not found".into(),
+            });
+        }
+        Ok(self.open())
+    }
+}
+
+/// An opened [`Replay`], with the position in the events of each kind
+struct ReplayDevice {
+    replay: Arc<Replay>,
+    next: Mutex<HashMap<EventKind, usize>>,
+}
+
+impl ReplayDevice {
+    /// The next event of the given kind
+    fn next_event(&self, kind: EventKind) -> HidResult<&Event> {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let next = next.entry(kind).or_default();
+        let events = &self.replay.events;
+        match events[*next..].iter().position(|e| e.kind == kind) {
+            Some(offset) => {
+                *next += offset + 1;
+                let event = &events[*next - 1];
+                match &event.error {
+                    Some(message) => Err(HidError::HidApiError {
+                        message: message.clone(),
+                    }),
+                    None => Ok(event),
+                }
+            }
+            None => Err(HidError::IoError {
+                error: io::Error::new(io::ErrorKind::UnexpectedEof, "end of the recording"),
+            }),
+        }
+    }
+
+    fn copy_next(&self, kind: EventKind, buf: &mut [u8]) -> HidResult<usize> {
+        let data = &self.next_event(kind)?.data;
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+}
+
+impl DeviceBackend for ReplayDevice {
+    fn write(&self, data: &[u8]) -> HidResult<usize> {
+        self.next_event(EventKind::Write)?;
+        Ok(data.len())
+    }
+
+    fn read(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.copy_next(EventKind::Read, buf)
+    }
+
+    fn read_timeout(&self, buf: &mut [u8], _timeout: i32) -> HidResult<usize> {
+        self.copy_next(EventKind::Read, buf)
+    }
+
+    fn send_feature_report(&self, _data: &[u8]) -> HidResult<()> {
+        self.next_event(EventKind::SendFeatureReport)?;
+        Ok(())
+    }
+
+    fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.copy_next(EventKind::GetFeatureReport, buf)
+    }
+
+    fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        self.copy_next(EventKind::GetInputReport, buf)
+    }
+
+    fn send_output_report(&self, _data: &[u8]) -> HidResult<()> {
+        self.next_event(EventKind::SendOutputReport)?;
+        Ok(())
+    }
+
+    fn set_blocking_mode(&self, _blocking: bool) -> HidResult<()> {
+        Ok(())
+    }
+
+    fn get_device_info(&self) -> HidResult<DeviceInfo> {
+        Ok(self.replay.info.clone())
+    }
+
+    fn get_manufacturer_string(&self) -> HidResult<Option<String>> {
+        Ok(self.replay.info.manufacturer_string().map(String::from))
+    }
+
+    fn get_product_string(&self) -> HidResult<Option<String>> {
+        Ok(self.replay.info.product_string().map(String::from))
+    }
+
+    fn get_serial_number_string(&self) -> HidResult<Option<String>> {
+        Ok(self.replay.info.serial_number().map(String::from))
+    }
+
+    fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let descriptor = &self.replay.report_descriptor;
+        let len = descriptor.len().min(buf.len());
+        buf[..len].copy_from_slice(&descriptor[..len]);
+        Ok(len)
+    }
+}
+
+fn invalid_data(message: &str) -> HidError {
+    HidError::IoError {
+        error: io::Error::new(io::ErrorKind::InvalidData, message),
+    }
+}
+
+/// Read the next record, `None` at the end of the file
+fn read_record(reader: &mut impl Read) -> HidResult<Option<(u8, Event)>> {
+    let mut kind = [0u8; 1];
+    if reader.read(&mut kind)? == 0 {
+        return Ok(None);
+    }
+    let mut header = [0u8; 13];
+    reader.read_exact(&mut header)?;
+    let time = Duration::from_micros(u64::from_le_bytes(header[..8].try_into().unwrap()));
+    let failed = header[8] != 0;
+    let data = read_bytes(reader, u32::from_le_bytes(header[9..].try_into().unwrap()))?;
+    let error = if failed {
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+        let message = read_bytes(reader, u32::from_le_bytes(len))?;
+        Some(String::from_utf8_lossy(&message).into_owned())
+    } else {
+        None
+    };
+
+    let event = Event {
+        time,
+        // Replaced by the caller for calls of the device
+        kind: EventKind::Write,
+        data,
+        error,
+    };
+    Ok(Some((kind[0], event)))
+}
+
+fn read_bytes(reader: &mut impl Read, len: u32) -> HidResult<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.take(len as u64).read_to_end(&mut data)?;
+    if data.len() != len as usize {
+        return Err(invalid_data("truncated record"));
+    }
+    Ok(data)
+}
+
+fn encode_info(info: &DeviceInfo) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let put_string = |buf: &mut Vec<u8>, s: Option<&str>| match s {
+        Some(s) => {
+            buf.push(1);
+            buf.extend_from_slice(&(s.len() as u16).to_le_bytes());
+            buf.extend_from_slice(s.as_bytes());
+        }
+        None => buf.push(0),
+    };
+    put_string(&mut buf, Some(&info.path().to_string_lossy()));
+    buf.extend_from_slice(&info.vendor_id().to_le_bytes());
+    buf.extend_from_slice(&info.product_id().to_le_bytes());
+    put_string(&mut buf, info.serial_number());
+    buf.extend_from_slice(&info.release_number().to_le_bytes());
+    put_string(&mut buf, info.manufacturer_string());
+    put_string(&mut buf, info.product_string());
+    // The fields, as the accessors are missing on Linux libusb backends
+    buf.extend_from_slice(&info.usage_page.to_le_bytes());
+    buf.extend_from_slice(&info.usage.to_le_bytes());
+    buf.extend_from_slice(&info.interface_number().to_le_bytes());
+    buf.push(info.bus_type() as u8);
+    put_string(&mut buf, info.physical_path());
+    buf
+}
+
+fn decode_info(data: &[u8]) -> HidResult<DeviceInfo> {
+    fn take<'a>(data: &mut &'a [u8], len: usize) -> HidResult<&'a [u8]> {
+        if data.len() < len {
+            return Err(invalid_data("truncated device information"));
+        }
+        let (head, tail) = data.split_at(len);
+        *data = tail;
+        Ok(head)
+    }
+    let mut data = data;
+    macro_rules! number {
+        ($ty:ty) => {
+            <$ty>::from_le_bytes(
+                take(&mut data, std::mem::size_of::<$ty>())?
+                    .try_into()
+                    .unwrap(),
+            )
+        };
+    }
+    macro_rules! string {
+        () => {
+            match take(&mut data, 1)?[0] {
+                0 => None,
+                _ => {
+                    let len = number!(u16) as usize;
+                    Some(String::from_utf8_lossy(take(&mut data, len)?).into_owned())
+                }
+            }
+        };
+    }
+
+    let path = string!().unwrap_or_default();
+    let path = CString::new(path).map_err(|_| invalid_data("path contains a NUL byte"))?;
+    let mut info = DeviceInfo::new(path, number!(u16), number!(u16));
+    if let Some(serial_number) = string!() {
+        info = info.with_serial_number(&serial_number);
+    }
+    info = info.with_release_number(number!(u16));
+    if let Some(manufacturer_string) = string!() {
+        info = info.with_manufacturer_string(&manufacturer_string);
+    }
+    if let Some(product_string) = string!() {
+        info = info.with_product_string(&product_string);
+    }
+    let usage_page = number!(u16);
+    info = info.with_usage(usage_page, number!(u16));
+    info = info.with_interface_number(number!(i32));
+    let bus_type = match take(&mut data, 1)?[0] {
+        0x01 => BusType::Usb,
+        0x02 => BusType::Bluetooth,
+        0x03 => BusType::I2c,
+        0x04 => BusType::Spi,
+        _ => BusType::Unknown,
+    };
+    info = info.with_bus_type(bus_type);
+    if !data.is_empty() {
+        if let Some(physical_path) = string!() {
+            info = info.with_physical_path(&physical_path);
+        }
+    }
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_round_trip() {
+        let info = DeviceInfo::new(CString::new("/dev/hidraw3").unwrap(), 0x046D, 0xC534)
+            .with_serial_number("1234")
+            .with_product_string("Receiver")
+            .with_usage(0xFF00, 0x01)
+            .with_interface_number(2)
+            .with_bus_type(BusType::Bluetooth);
+        let with_physical_path = info.clone().with_physical_path("aa:bb:cc:dd:ee:ff");
+        assert_eq!(
+            decode_info(&encode_info(&with_physical_path)).unwrap(),
+            with_physical_path
+        );
+
+        let encoded = encode_info(&with_physical_path);
+        assert!(decode_info(&encoded[..encoded.len() - 1]).is_err());
+
+        // Without the physical path, like older recordings
+        let encoded = encode_info(&info);
+        assert_eq!(decode_info(&encoded[..encoded.len() - 1]).unwrap(), info);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn test_record_and_replay() {
+        use crate::mock::VirtualDevice;
+
+        let virtual_device = VirtualDevice::builder(0x046D, 0xC534)
+            .product_string("Receiver")
+            .report_descriptor(&[0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0xC0])
+            .feature_report(&[0x02, 0x10])
+            .input_report(&[0x03, 0x30])
+            .on_write(|device, data| {
+                device.push_input_report(data);
+                Ok(data.len())
+            })
+            .build();
+
+        let device = RecordingDevice::new(virtual_device.open().unwrap(), Vec::new()).unwrap();
+        let mut buf = [0u8; 4];
+        device.write(&[0x01, 0x02]).unwrap();
+        assert_eq!(device.read_timeout(&mut buf, 0).unwrap(), 2);
+        assert_eq!(device.read_timeout(&mut buf, 0).unwrap(), 0);
+        device.send_feature_report(&[0x02, 0x20]).unwrap();
+        assert_eq!(device.get_feature_report(&mut [0x02, 0x00]).unwrap(), 2);
+        assert!(device.get_feature_report(&mut [0x03, 0x00]).is_err());
+        assert_eq!(device.get_input_report(&mut [0x03, 0x00]).unwrap(), 2);
+        device.send_output_report(&[0x04, 0x40]).unwrap();
+        let (_, recording) = device.finish().unwrap();
+
+        let replay = Replay::read(&recording[..]).unwrap();
+        assert_eq!(replay.info(), virtual_device.info());
+        let kinds = replay.events().iter().map(|e| e.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Write,
+                EventKind::Read,
+                EventKind::Read,
+                EventKind::SendFeatureReport,
+                EventKind::GetFeatureReport,
+                EventKind::GetFeatureReport,
+                EventKind::GetInputReport,
+                EventKind::SendOutputReport,
+            ]
+        );
+
+        let device = replay.open();
+        assert_eq!(
+            device.get_product_string().unwrap().as_deref(),
+            Some("Receiver")
+        );
+        assert_eq!(device.report_descriptor().unwrap().collections().len(), 1);
+        assert_eq!(device.write(&[0x01, 0x02]).unwrap(), 2);
+        assert_eq!(device.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [0x01, 0x02]);
+        assert_eq!(device.read(&mut buf).unwrap(), 0);
+        let mut feature = [0x02, 0x00];
+        assert_eq!(device.get_feature_report(&mut feature).unwrap(), 2);
+        assert_eq!(feature, [0x02, 0x20]);
+        assert!(device.get_feature_report(&mut feature).is_err());
+        let mut input = [0x03, 0x00];
+        assert_eq!(device.get_input_report(&mut input).unwrap(), 2);
+        assert_eq!(input, [0x03, 0x30]);
+        device.send_output_report(&[0x04, 0x40]).unwrap();
+        assert!(device.send_output_report(&[0x04, 0x40]).is_err());
+        assert!(device.read(&mut buf).is_err());
+
+        // The kinds of calls are replayed independently
+        let device = replay.open();
+        assert_eq!(device.get_input_report(&mut input).unwrap(), 2);
+        assert_eq!(device.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [0x01, 0x02]);
+        assert_eq!(device.write(&[0x01, 0x02]).unwrap(), 2);
+        assert!(device.write(&[0x01, 0x02]).is_err());
+        assert_eq!(device.read(&mut buf).unwrap(), 0);
+
+        let mut capture = Vec::new();
+        replay.write_pcapng(&mut capture).unwrap();
+        assert_eq!(capture[..4], [0x0A, 0x0D, 0x0D, 0x0A]);
+
+        let api = crate::HidApi::with_backend(replay).unwrap();
+        assert_eq!(api.device_list().count(), 1);
+        assert!(api.open(0x046D, 0xC534).is_ok());
+    }
+}
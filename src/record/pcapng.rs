@@ -0,0 +1,382 @@
+//! Encoding of the traffic as USB packets in a pcapng file, see the
+//! [module documentation](super#pcapng)
+//!
+//! Each call of the device becomes a submission and a completion of an URB.
+//! The capture starts with `GET_DESCRIPTOR` requests for the device,
+//! configuration and report descriptors, from which Wireshark learns that the
+//! device is a HID device and how to dissect its reports.
+
+use std::time::{Duration, SystemTime};
+
+use super::EventKind;
+use crate::DeviceInfo;
+
+const LINKTYPE_USB_LINUX_MMAPPED: u16 = 220;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D0D0A;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x00000001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x00000006;
+
+const OPTION_END: u16 = 0;
+const OPTION_IF_NAME: u16 = 2;
+
+const XFER_INTERRUPT: u8 = 1;
+const XFER_CONTROL: u8 = 2;
+
+const ENDPOINT_OUT: u8 = 0x01;
+const ENDPOINT_IN: u8 = 0x81;
+
+const EINPROGRESS: i32 = 115;
+const EPIPE: i32 = 32;
+const EPROTO: i32 = 71;
+
+/// Report types in the `wValue` of HID class requests
+const INPUT: u16 = 0x01;
+const OUTPUT: u16 = 0x02;
+const FEATURE: u16 = 0x03;
+
+/// The bus and address of the synthesized device
+const BUS_NUMBER: u16 = 1;
+const DEVICE_ADDRESS: u8 = 1;
+
+/// A USB setup packet
+#[derive(Clone, Copy)]
+struct Setup {
+    request_type: u8,
+    request: u8,
+    value: u16,
+    index: u16,
+    length: u16,
+}
+
+impl Setup {
+    fn to_bytes(self) -> [u8; 8] {
+        let mut setup = [self.request_type, self.request, 0, 0, 0, 0, 0, 0];
+        setup[2..4].copy_from_slice(&self.value.to_le_bytes());
+        setup[4..6].copy_from_slice(&self.index.to_le_bytes());
+        setup[6..8].copy_from_slice(&self.length.to_le_bytes());
+        setup
+    }
+}
+
+/// Turns the calls of a device into pcapng blocks
+pub(super) struct Encoder {
+    start: SystemTime,
+    next_id: u64,
+    interface: u16,
+}
+
+impl Encoder {
+    /// `start` is the wall clock time the times of the events are relative to
+    pub(super) fn new(info: &DeviceInfo, start: SystemTime) -> Self {
+        Self {
+            start,
+            next_id: 1,
+            interface: info.interface_number().max(0) as u16,
+        }
+    }
+
+    /// The section header, the USB interface and the descriptor requests
+    pub(super) fn header(&mut self, info: &DeviceInfo, report_descriptor: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        let mut body = Vec::new();
+        body.extend_from_slice(&0x1A2B3C4Du32.to_le_bytes());
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // The section length is unknown
+        body.extend_from_slice(&(-1i64).to_le_bytes());
+        push_block(&mut buf, BLOCK_SECTION_HEADER, &body);
+
+        let mut body = Vec::new();
+        body.extend_from_slice(&LINKTYPE_USB_LINUX_MMAPPED.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // No limit on the length of the packets
+        body.extend_from_slice(&0u32.to_le_bytes());
+        push_option(&mut body, OPTION_IF_NAME, info.path().to_bytes());
+        push_option(&mut body, OPTION_END, &[]);
+        push_block(&mut buf, BLOCK_INTERFACE_DESCRIPTION, &body);
+
+        let device_descriptor = device_descriptor(info);
+        let configuration_descriptor = self.configuration_descriptor(report_descriptor.len());
+        let requests: [(u16, u16, &[u8]); 3] = [
+            (0x0100, 0, &device_descriptor),
+            (0x0200, 0, &configuration_descriptor),
+            (0x2200, self.interface, report_descriptor),
+        ];
+        for (value, index, descriptor) in requests {
+            let setup = Setup {
+                request_type: 0x80 | if value == 0x2200 { 0x01 } else { 0x00 },
+                request: 0x06,
+                value,
+                index,
+                length: descriptor.len() as u16,
+            };
+            self.transfer(&mut buf, Duration::ZERO, 0x80, Some(setup), descriptor, 0);
+        }
+        buf
+    }
+
+    /// One call of the device, `data` is the report as in the recording
+    pub(super) fn event(
+        &mut self,
+        time: Duration,
+        kind: EventKind,
+        data: &[u8],
+        failed: bool,
+    ) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let report_id = data.first().copied().unwrap_or(0);
+        // Reports without ID are sent without the leading 0 on the bus
+        let report = match report_id {
+            0 if !data.is_empty() => &data[1..],
+            _ => data,
+        };
+        let setup = |request_type, request, report_type: u16, length: usize| Setup {
+            request_type,
+            request,
+            value: (report_type << 8) | report_id as u16,
+            index: self.interface,
+            length: length as u16,
+        };
+
+        match kind {
+            EventKind::Write => {
+                let status = if failed { -EPROTO } else { 0 };
+                self.transfer(&mut buf, time, ENDPOINT_OUT, None, report, status);
+            }
+            // A timeout is not a transfer
+            EventKind::Read if data.is_empty() && !failed => {}
+            EventKind::Read => {
+                let status = if failed { -EPROTO } else { 0 };
+                self.transfer(&mut buf, time, ENDPOINT_IN, None, report, status);
+            }
+            EventKind::SendFeatureReport | EventKind::SendOutputReport => {
+                let report_type = match kind {
+                    EventKind::SendOutputReport => OUTPUT,
+                    _ => FEATURE,
+                };
+                let status = if failed { -EPIPE } else { 0 };
+                let setup = setup(0x21, 0x09, report_type, report.len());
+                self.transfer(&mut buf, time, 0x00, Some(setup), report, status);
+            }
+            EventKind::GetFeatureReport | EventKind::GetInputReport => {
+                let report_type = match kind {
+                    EventKind::GetInputReport => INPUT,
+                    _ => FEATURE,
+                };
+                let (status, report) = if failed {
+                    (-EPIPE, &[][..])
+                } else {
+                    (0, report)
+                };
+                let setup = setup(0xA1, 0x01, report_type, report.len());
+                self.transfer(&mut buf, time, 0x80, Some(setup), report, status);
+            }
+        }
+        buf
+    }
+
+    /// A submission and a completion, the data is part of the submission
+    /// for OUT transfers and of the completion for IN transfers
+    fn transfer(
+        &mut self,
+        buf: &mut Vec<u8>,
+        time: Duration,
+        endpoint: u8,
+        setup: Option<Setup>,
+        data: &[u8],
+        status: i32,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let xfer_type = match setup {
+            Some(_) => XFER_CONTROL,
+            None => XFER_INTERRUPT,
+        };
+        let urb = Urb {
+            id,
+            xfer_type,
+            endpoint,
+            setup,
+            time: self.start + time,
+        };
+        let (submitted, completed) = match endpoint & 0x80 {
+            0 => (data, &[][..]),
+            _ => (&[][..], data),
+        };
+        urb.push(buf, b'S', -EINPROGRESS, data.len(), submitted);
+        urb.push(buf, b'C', status, data.len(), completed);
+    }
+
+    fn configuration_descriptor(&self, report_descriptor_length: usize) -> Vec<u8> {
+        let mut descriptor = Vec::new();
+        // Configuration, the total length is filled in below
+        descriptor.extend_from_slice(&[9, 0x02, 0, 0, 1, 1, 0, 0x80, 50]);
+        // Interface of the HID class with two endpoints
+        descriptor.extend_from_slice(&[9, 0x04, self.interface as u8, 0, 2, 0x03, 0, 0, 0]);
+        // HID descriptor with one report descriptor
+        let length = (report_descriptor_length as u16).to_le_bytes();
+        descriptor.extend_from_slice(&[9, 0x21, 0x11, 0x01, 0, 1, 0x22, length[0], length[1]]);
+        // Interrupt endpoints with 64 bytes and an interval of 1 ms
+        descriptor.extend_from_slice(&[7, 0x05, ENDPOINT_IN, 0x03, 64, 0, 1]);
+        descriptor.extend_from_slice(&[7, 0x05, ENDPOINT_OUT, 0x03, 64, 0, 1]);
+        let total_length = (descriptor.len() as u16).to_le_bytes();
+        descriptor[2..4].copy_from_slice(&total_length);
+        descriptor
+    }
+}
+
+fn device_descriptor(info: &DeviceInfo) -> [u8; 18] {
+    let mut descriptor = [0u8; 18];
+    descriptor[..8].copy_from_slice(&[18, 0x01, 0x00, 0x02, 0, 0, 0, 64]);
+    descriptor[8..10].copy_from_slice(&info.vendor_id().to_le_bytes());
+    descriptor[10..12].copy_from_slice(&info.product_id().to_le_bytes());
+    descriptor[12..14].copy_from_slice(&info.release_number().to_le_bytes());
+    // No string descriptors, and one configuration
+    descriptor[17] = 1;
+    descriptor
+}
+
+/// The header of a usbmon packet
+struct Urb {
+    id: u64,
+    xfer_type: u8,
+    endpoint: u8,
+    setup: Option<Setup>,
+    time: SystemTime,
+}
+
+impl Urb {
+    fn push(&self, buf: &mut Vec<u8>, event_type: u8, status: i32, length: usize, data: &[u8]) {
+        let since_epoch = self
+            .time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let mut packet = Vec::with_capacity(64 + data.len());
+        packet.extend_from_slice(&self.id.to_le_bytes());
+        packet.push(event_type);
+        packet.push(self.xfer_type);
+        packet.push(self.endpoint);
+        packet.push(DEVICE_ADDRESS);
+        packet.extend_from_slice(&BUS_NUMBER.to_le_bytes());
+        // The setup packet is only part of the submission
+        let setup = self.setup.filter(|_| event_type == b'S');
+        packet.push(if setup.is_some() { 0 } else { b'-' });
+        packet.push(if data.is_empty() { b'<' } else { 0 });
+        packet.extend_from_slice(&(since_epoch.as_secs() as i64).to_le_bytes());
+        packet.extend_from_slice(&(since_epoch.subsec_micros() as i32).to_le_bytes());
+        packet.extend_from_slice(&status.to_le_bytes());
+        packet.extend_from_slice(&(length as u32).to_le_bytes());
+        packet.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&setup.map_or([0; 8], Setup::to_bytes));
+        // Interval, start frame, transfer flags and number of ISO descriptors
+        let interval: i32 = if self.xfer_type == XFER_INTERRUPT {
+            1
+        } else {
+            0
+        };
+        packet.extend_from_slice(&interval.to_le_bytes());
+        packet.extend_from_slice(&[0; 12]);
+        packet.extend_from_slice(data);
+
+        let micros = since_epoch.as_micros() as u64;
+        let mut body = Vec::with_capacity(20 + packet.len());
+        // Interface 0
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        body.extend_from_slice(&packet);
+        pad(&mut body);
+        push_block(buf, BLOCK_ENHANCED_PACKET, &body);
+    }
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(buf.len().next_multiple_of(4), 0);
+}
+
+fn push_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    pad(buf);
+}
+
+/// A block with its type and length before and after the body
+fn push_block(buf: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+    let length = (12 + body.len() as u32).to_le_bytes();
+    buf.extend_from_slice(&block_type.to_le_bytes());
+    buf.extend_from_slice(&length);
+    buf.extend_from_slice(body);
+    buf.extend_from_slice(&length);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    /// Split a capture into the types and bodies of its blocks
+    fn blocks(mut capture: &[u8]) -> Vec<(u32, &[u8])> {
+        let mut blocks = Vec::new();
+        while !capture.is_empty() {
+            let block_type = u32::from_le_bytes(capture[..4].try_into().unwrap());
+            let length = u32::from_le_bytes(capture[4..8].try_into().unwrap()) as usize;
+            assert_eq!(length % 4, 0);
+            assert_eq!(capture[length - 4..length], capture[4..8]);
+            blocks.push((block_type, &capture[8..length - 4]));
+            capture = &capture[length..];
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_encode_capture() {
+        let info = DeviceInfo::new(CString::new("/dev/hidraw0").unwrap(), 0x046D, 0xC534)
+            .with_interface_number(2);
+        let mut encoder = Encoder::new(&info, SystemTime::UNIX_EPOCH);
+        let mut capture = encoder.header(&info, &[0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0xC0]);
+        let time = Duration::from_micros(1_500_000);
+        capture.extend(encoder.event(time, EventKind::Read, &[], false));
+        capture.extend(encoder.event(time, EventKind::SendFeatureReport, &[0x02, 0x20], false));
+        capture.extend(encoder.event(time, EventKind::Write, &[0x00, 0x10], true));
+        capture.extend(encoder.event(time, EventKind::GetInputReport, &[0x03, 0x30], false));
+
+        let blocks = blocks(&capture);
+        let types = blocks.iter().map(|(t, _)| *t).collect::<Vec<_>>();
+        assert_eq!(
+            types[..2],
+            [BLOCK_SECTION_HEADER, BLOCK_INTERFACE_DESCRIPTION]
+        );
+        // Three descriptor requests and three reports, the empty read is skipped
+        assert_eq!(types.len(), 2 + 6 * 2);
+        assert_eq!(blocks[1].1[..2], LINKTYPE_USB_LINUX_MMAPPED.to_le_bytes());
+
+        // The GET_DESCRIPTOR submission of the report descriptor
+        let packet = &blocks[6].1[20..];
+        assert_eq!(packet[8], b'S');
+        assert_eq!(packet[40..48], [0x81, 0x06, 0x00, 0x22, 2, 0, 8, 0]);
+
+        // The SET_REPORT submission carries the report
+        let (_, body) = blocks[8];
+        assert_eq!(body[4..12], [0, 0, 0, 0, 0x60, 0xE3, 0x16, 0x00]);
+        let packet = &body[20..];
+        assert_eq!(packet[9..11], [XFER_CONTROL, 0x00]);
+        assert_eq!(packet[40..48], [0x21, 0x09, 0x02, 0x03, 2, 0, 2, 0]);
+        assert_eq!(packet[64..66], [0x02, 0x20]);
+
+        // The failed write is sent without the report ID 0
+        let packet = &blocks[11].1[20..];
+        assert_eq!(packet[8..11], [b'C', XFER_INTERRUPT, ENDPOINT_OUT]);
+        assert_eq!(packet[28..32], (-EPROTO).to_le_bytes());
+        assert_eq!(packet[32..36], 1u32.to_le_bytes());
+
+        // GET_REPORT of an Input report
+        let packet = &blocks[12].1[20..];
+        assert_eq!(packet[40..48], [0xA1, 0x01, 0x03, 0x01, 2, 0, 2, 0]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Typed reports
+//!
+//! A struct that implements [`HidReport`] knows the layout of one report, so
+//! that it can be read and written with [`HidDevice::read_report`],
+//! [`HidDevice::write_report`], [`HidDevice::get_feature`] and
+//! [`HidDevice::send_feature`] instead of indexing into byte buffers. The
+//! trait is usually derived:
+//!
+//! ```
+//! use hidapi::report::HidReport;
+//!
+//! /// Input report 1 of `tests/assets/mouse2.data`
+//! #[derive(HidReport, Debug, PartialEq)]
+//! #[hid(input, id = 1, descriptor = "tests/assets/mouse2.data")]
+//! struct Pointer {
+//!     #[hid(size = 5)]
+//!     buttons: u8,
+//!     #[hid(offset = 8)]
+//!     x: i16,
+//!     y: i16,
+//!     wheel: i8,
+//!     pan: i8,
+//! }
+//!
+//! let bytes = [0x01, 0x05, 0xFE, 0xFF, 0x2C, 0x01, 0xFF, 0x01];
+//! let report = Pointer::from_bytes(&bytes).unwrap();
+//! assert_eq!(report, Pointer { buttons: 5, x: -2, y: 300, wheel: -1, pan: 1 });
+//! assert_eq!(report.to_bytes(), bytes);
+//! ```
+//!
+//! See [`HidReport`](derive@HidReport) for the options of the derive macro.
+//! With the `descriptor` option the layout is checked against the report
+//! descriptor at build time, so a struct that does not match fails to
+//! compile:
+//!
+//! ```compile_fail
+//! use hidapi::report::HidReport;
+//!
+//! #[derive(HidReport)]
+//! #[hid(input, id = 1, descriptor = "tests/assets/mouse2.data")]
+//! struct Pointer {
+//!     buttons: u8,
+//!     x: i16,
+//!     y: i16,
+//! }
+//! ```
+//!
+//! Fields must also fit their type:
+//!
+//! ```compile_fail
+//! use hidapi::report::HidReport;
+//!
+//! #[derive(HidReport)]
+//! #[hid(input)]
+//! struct Position {
+//!     #[hid(size = 12)]
+//!     x: u8,
+//! }
+//! ```
+//!
+//! [`HidDevice::read_report`]: crate::HidDevice::read_report
+//! [`HidDevice::write_report`]: crate::HidDevice::write_report
+//! [`HidDevice::get_feature`]: crate::HidDevice::get_feature
+//! [`HidDevice::send_feature`]: crate::HidDevice::send_feature
+
+use crate::descriptor::ReportType;
+use crate::HidResult;
+
+pub use hidapi_derive::HidReport;
+
+/// A report with a fixed layout
+///
+/// The bytes of a report always start with the report ID, which is 0 if
+/// the device does not use numbered reports, like the buffers of
+/// [`HidDevice::write`](crate::HidDevice::write) and
+/// [`HidDevice::get_feature_report`](crate::HidDevice::get_feature_report).
+pub trait HidReport: Sized {
+    const REPORT_TYPE: ReportType;
+    /// The report ID, or 0 if the device does not use numbered reports
+    const REPORT_ID: u8;
+    /// Length of the report in bytes, including the report ID
+    const LENGTH: usize;
+
+    /// Decode a report
+    ///
+    /// Fails if the report has another ID or is shorter than
+    /// [`LENGTH`](HidReport::LENGTH).
+    fn from_bytes(bytes: &[u8]) -> HidResult<Self>;
+
+    /// Encode the report, bits that are not part of a field are zero
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+/// An Input report, for [`HidDevice::read_report`](crate::HidDevice::read_report)
+///
+/// The derive macro implements the marker trait of the report type, so a
+/// report cannot be used as another type:
+///
+/// ```compile_fail
+/// # fn read(device: &hidapi::HidDevice) {
+/// use hidapi::report::HidReport;
+///
+/// #[derive(HidReport)]
+/// #[hid(feature)]
+/// struct Settings {
+///     level: u8,
+/// }
+///
+/// let settings = device.read_report::<Settings>();
+/// # }
+/// ```
+pub trait InputReport: HidReport {}
+
+/// An Output report, for [`HidDevice::write_report`](crate::HidDevice::write_report)
+pub trait OutputReport: HidReport {}
+
+/// A Feature report, for [`HidDevice::get_feature`](crate::HidDevice::get_feature)
+/// and [`HidDevice::send_feature`](crate::HidDevice::send_feature)
+pub trait FeatureReport: HidReport {}
+
+/// Helpers for the code generated by the derive macro
+#[doc(hidden)]
+pub mod __private {
+    use std::io;
+
+    use crate::{HidError, HidResult};
+
+    /// The data of a report, after its report ID
+    pub fn report_data(bytes: &[u8], report_id: u8, length: usize) -> HidResult<&[u8]> {
+        let message = match bytes.first() {
+            Some(id) if *id != report_id => format!("expected report ID {report_id}, got {id}"),
+            _ if bytes.len() < length => {
+                format!("expected {length} bytes of report, got {}", bytes.len())
+            }
+            _ => return Ok(&bytes[1..]),
+        };
+        Err(HidError::IoError {
+            error: io::Error::new(io::ErrorKind::InvalidData, message),
+        })
+    }
+
+    /// Read `size` bits at the bit `offset`, least significant bit first
+    pub fn get_bits(data: &[u8], offset: usize, size: u32) -> u64 {
+        (0..size as usize).fold(0, |value, i| {
+            let bit = offset + i;
+            value | ((data[bit / 8] >> (bit % 8)) as u64 & 1) << i
+        })
+    }
+
+    /// Write the low `size` bits of `value` at the bit `offset`
+    pub fn set_bits(data: &mut [u8], offset: usize, size: u32, value: u64) {
+        for i in 0..size as usize {
+            let bit = offset + i;
+            let mask = 1 << (bit % 8);
+            if value >> i & 1 != 0 {
+                data[bit / 8] |= mask;
+            } else {
+                data[bit / 8] &= !mask;
+            }
+        }
+    }
+
+    /// The types of report fields
+    pub trait ReportValue: Copy {
+        fn from_bits(bits: u64, size: u32) -> Self;
+        fn to_bits(self) -> u64;
+    }
+
+    impl ReportValue for bool {
+        fn from_bits(bits: u64, _size: u32) -> Self {
+            bits != 0
+        }
+
+        fn to_bits(self) -> u64 {
+            self as u64
+        }
+    }
+
+    macro_rules! impl_unsigned {
+        ($($t:ty),*) => {$(
+            impl ReportValue for $t {
+                fn from_bits(bits: u64, _size: u32) -> Self {
+                    bits as $t
+                }
+
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*};
+    }
+
+    /// Signed values are sign extended from the size of the field
+    macro_rules! impl_signed {
+        ($($t:ty),*) => {$(
+            impl ReportValue for $t {
+                fn from_bits(bits: u64, size: u32) -> Self {
+                    let shift = 64 - size;
+                    ((bits << shift) as i64 >> shift) as $t
+                }
+
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*};
+    }
+
+    impl_unsigned!(u8, u16, u32, u64);
+    impl_signed!(i8, i16, i32, i64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::__private::{get_bits, set_bits, ReportValue};
+    use super::*;
+
+    #[derive(HidReport, Debug, PartialEq)]
+    #[hid(feature, length = 6)]
+    struct Settings {
+        enabled: bool,
+        #[hid(size = 3)]
+        mode: u8,
+        #[hid(size = 12)]
+        offset: i16,
+        #[hid(offset = 24)]
+        level: u8,
+        #[hid(size = 4)]
+        steps: [u8; 3],
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut data = [0u8; 3];
+        set_bits(&mut data, 4, 12, 0xABC);
+        assert_eq!(data, [0xC0, 0xAB, 0x00]);
+        assert_eq!(get_bits(&data, 4, 12), 0xABC);
+        assert_eq!(get_bits(&data, 12, 8), 0x0A);
+
+        assert_eq!(i16::from_bits(0xFFF, 12), -1);
+        assert_eq!(i16::from_bits(0x7FF, 12), 2047);
+        assert_eq!(i64::from_bits(u64::MAX, 64), -1);
+        assert_eq!(u8::from_bits(0x1F, 5), 31);
+    }
+
+    #[test]
+    fn test_derive() {
+        assert_eq!(Settings::REPORT_TYPE, ReportType::Feature);
+        assert_eq!(Settings::LENGTH, 7);
+
+        let settings = Settings {
+            enabled: true,
+            mode: 5,
+            offset: -3,
+            level: 200,
+            steps: [1, 2, 15],
+        };
+        let bytes = settings.to_bytes();
+        assert_eq!(bytes, [0x00, 0xDB, 0xFF, 0x00, 200, 0x21, 0x0F]);
+        assert_eq!(Settings::from_bytes(&bytes).unwrap(), settings);
+
+        assert!(Settings::from_bytes(&bytes[..6]).is_err());
+        assert!(Settings::from_bytes(&[1, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn test_device_reports() {
+        use crate::mock::VirtualDevice;
+
+        #[derive(HidReport, Debug, PartialEq)]
+        #[hid(input, id = 2)]
+        struct Status {
+            battery: u8,
+            charging: bool,
+        }
+
+        let virtual_device = VirtualDevice::builder(0x046D, 0xC534)
+            .feature_report(&[0x00, 0x11, 0x00, 0x00, 0x07, 0x00, 0x00])
+            .build();
+        let device = virtual_device.open().unwrap();
+
+        virtual_device.push_input_report(&[0x01, 0x00, 0x00]);
+        virtual_device.push_input_report(&[0x02, 0x50, 0x01]);
+        let status = device.read_report::<Status>().unwrap();
+        assert_eq!(
+            status,
+            Status {
+                battery: 0x50,
+                charging: true
+            }
+        );
+        device.set_blocking_mode(false).unwrap();
+        assert!(device.read_report::<Status>().is_err());
+
+        let mut settings = device.get_feature::<Settings>().unwrap();
+        assert_eq!(settings.offset, 1);
+        assert_eq!(settings.level, 7);
+        settings.level = 8;
+        device.send_feature(&settings).unwrap();
+        assert_eq!(
+            virtual_device.sent_feature_reports(),
+            [[0x00, 0x11, 0x00, 0x00, 0x08, 0x00, 0x00]]
+        );
+
+        #[derive(HidReport, Debug, PartialEq)]
+        #[hid(output, id = 3)]
+        struct Leds {
+            #[hid(size = 2)]
+            mask: u8,
+        }
+        device.write_report(&Leds { mask: 2 }).unwrap();
+        assert_eq!(virtual_device.written_reports(), [[0x03, 0x02]]);
+    }
+}
@@ -0,0 +1,554 @@
+//! Runtime agnostic asynchronous access to devices with `futures` streams
+//!
+//! [`HidDevice::into_report_stream`] turns a device into a [`ReportStream`],
+//! which is a [`Stream`] of Input reports and a [`Sink`] for Output reports.
+//! It does not depend on a specific executor.
+//!
+//! With the `linux-native` feature, devices whose backend has a file
+//! descriptor are registered with the `async-io` reactor, which is also used
+//! by `smol`. Other devices have no pollable handle, so a helper thread reads
+//! the device with [`HidDevice::read_timeout`] while the stream is polled,
+//! and writes the Output reports of the sink.
+//!
+//! Flushing the sink waits until the reports are written, and returns the
+//! first write error.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+use crate::{HidDevice, HidError, HidResult};
+
+/// An Input or Output report with its report ID split off
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    report_id: u8,
+    data: Vec<u8>,
+    timestamp: Instant,
+}
+
+impl Report {
+    /// Create a report to send, use report ID 0 if the device does not use
+    /// report IDs
+    pub fn new(report_id: u8, data: Vec<u8>) -> Self {
+        Self {
+            report_id,
+            data,
+            timestamp: Instant::now(),
+        }
+    }
+
+    /// The report ID, or 0 if the device does not use report IDs
+    pub fn report_id(&self) -> u8 {
+        self.report_id
+    }
+
+    /// The report data, without the report ID
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// When the report was received, or created for reports to send
+    pub fn timestamp(&self) -> Instant {
+        self.timestamp
+    }
+
+    /// Split a buffer as returned by [`HidDevice::read`]
+    fn from_input(data: &[u8], numbered: bool) -> Self {
+        match data.split_first() {
+            Some((report_id, data)) if numbered => Self::new(*report_id, data.to_vec()),
+            _ => Self::new(0, data.to_vec()),
+        }
+    }
+
+    /// The buffer expected by [`HidDevice::write`]
+    fn to_output(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.data.len() + 1);
+        buf.push(self.report_id);
+        buf.extend_from_slice(&self.data);
+        buf
+    }
+}
+
+/// Stream of Input reports and sink for Output reports of a device
+///
+/// The stream ends after the first error, e.g. when the device is
+/// disconnected.
+#[derive(Debug)]
+pub struct ReportStream {
+    inner: Inner,
+    done: bool,
+}
+
+#[derive(Debug)]
+enum Inner {
+    #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+    Pollable(pollable::Inner),
+    Threaded(threaded::Inner),
+}
+
+impl Inner {
+    fn new(device: HidDevice, buffer_len: usize, numbered: bool) -> HidResult<Self> {
+        #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+        if device.as_fd().is_some() {
+            return Ok(Inner::Pollable(pollable::Inner::new(
+                device, buffer_len, numbered,
+            )?));
+        }
+        Ok(Inner::Threaded(threaded::Inner::new(
+            device, buffer_len, numbered,
+        )?))
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>) -> Poll<Option<HidResult<Report>>> {
+        match self {
+            #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+            Inner::Pollable(inner) => inner.poll_read(cx),
+            Inner::Threaded(inner) => inner.poll_read(cx),
+        }
+    }
+
+    fn start_send(&mut self, data: Vec<u8>) -> HidResult<()> {
+        match self {
+            #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+            Inner::Pollable(inner) => inner.start_send(data),
+            Inner::Threaded(inner) => inner.start_send(data),
+        }
+    }
+
+    fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<HidResult<()>> {
+        match self {
+            #[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+            Inner::Pollable(inner) => inner.poll_flush(cx),
+            Inner::Threaded(inner) => inner.poll_flush(cx),
+        }
+    }
+}
+
+impl HidDevice {
+    /// Read and write the device asynchronously
+    ///
+    /// The report descriptor decides whether Input reports start with a
+    /// report ID.
+    pub fn into_report_stream(self) -> HidResult<ReportStream> {
+        let capabilities = self.capabilities()?;
+        let buffer_len = capabilities.input_report_byte_length().max(1);
+        let numbered = !capabilities.report_ids().is_empty();
+        Ok(ReportStream {
+            inner: Inner::new(self, buffer_len, numbered)?,
+            done: false,
+        })
+    }
+}
+
+impl Stream for ReportStream {
+    type Item = HidResult<Report>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        let result = match self.inner.poll_read(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        Poll::Ready(result)
+    }
+}
+
+impl Sink<Report> for ReportStream {
+    type Error = HidError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<HidResult<()>> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, report: Report) -> HidResult<()> {
+        self.inner.start_send(report.to_output())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<HidResult<()>> {
+        self.inner.poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<HidResult<()>> {
+        self.inner.poll_flush(cx)
+    }
+}
+
+#[cfg(all(feature = "linux-native-sysfs", target_os = "linux"))]
+mod pollable {
+    use super::Report;
+    use crate::{HidDevice, HidError, HidResult};
+    use async_io::Async;
+    use std::io;
+    use std::os::fd::{AsFd, BorrowedFd};
+    use std::task::{ready, Context, Poll};
+
+    /// A device whose backend has a file descriptor
+    #[derive(Debug)]
+    struct Pollable(HidDevice);
+
+    impl AsFd for Pollable {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.0.as_fd().expect("checked by Inner::new")
+        }
+    }
+
+    /// The device registered with the `async-io` reactor
+    #[derive(Debug)]
+    pub(super) struct Inner {
+        device: Async<Pollable>,
+        buffer: Vec<u8>,
+        numbered: bool,
+        pending: Option<Vec<u8>>,
+    }
+
+    impl Inner {
+        pub(super) fn new(device: HidDevice, buffer_len: usize, numbered: bool) -> HidResult<Self> {
+            Ok(Self {
+                device: Async::new(Pollable(device))?,
+                buffer: vec![0; buffer_len],
+                numbered,
+                pending: None,
+            })
+        }
+
+        pub(super) fn poll_read(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<HidResult<Report>>> {
+            loop {
+                match self.device.get_ref().0.read_timeout(&mut self.buffer, 0) {
+                    Ok(0) => {
+                        if let Err(e) = ready!(self.device.poll_readable(cx)) {
+                            return Poll::Ready(Some(Err(e.into())));
+                        }
+                    }
+                    Ok(n) => {
+                        return Poll::Ready(Some(Ok(Report::from_input(
+                            &self.buffer[..n],
+                            self.numbered,
+                        ))))
+                    }
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                }
+            }
+        }
+
+        pub(super) fn start_send(&mut self, data: Vec<u8>) -> HidResult<()> {
+            self.pending = Some(data);
+            Ok(())
+        }
+
+        pub(super) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<HidResult<()>> {
+            while let Some(data) = &self.pending {
+                match self.device.get_ref().0.write(data) {
+                    Err(HidError::IoError { error })
+                        if error.kind() == io::ErrorKind::WouldBlock =>
+                    {
+                        ready!(self.device.poll_writable(cx))?
+                    }
+                    result => {
+                        self.pending = None;
+                        result?;
+                    }
+                }
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+mod threaded {
+    use super::Report;
+    use crate::{HidDevice, HidError, HidResult};
+    use futures_channel::mpsc::{unbounded, UnboundedReceiver};
+    use futures_core::Stream;
+    use std::pin::Pin;
+    use std::sync::mpsc::{channel, Sender, TryRecvError};
+    use std::task::{ready, Context, Poll};
+    use std::thread;
+
+    /// How long the helper thread waits for an Input report before it checks
+    /// for Output reports to send
+    ///
+    /// A device can only be used by one thread, and a read cannot be
+    /// interrupted, so the helper only waits for Input reports in slices while
+    /// the stream is polled. Otherwise it blocks until there is something to
+    /// do.
+    const READ_TIMEOUT_MS: i32 = 10;
+
+    #[derive(Debug)]
+    enum Command {
+        /// Read the next Input report
+        Read,
+        Write(Vec<u8>),
+    }
+
+    /// Channels to the helper thread that owns the device
+    #[derive(Debug)]
+    pub(super) struct Inner {
+        commands: Sender<Command>,
+        input: UnboundedReceiver<HidResult<Report>>,
+        /// The result of each write, in order
+        written: UnboundedReceiver<HidResult<()>>,
+        reading: bool,
+        unacknowledged: usize,
+    }
+
+    impl Inner {
+        pub(super) fn new(device: HidDevice, buffer_len: usize, numbered: bool) -> HidResult<Self> {
+            let (commands, command_receiver) = channel();
+            let (input_sender, input) = unbounded();
+            let (written_sender, written) = unbounded();
+            thread::Builder::new()
+                .name("hidapi-report-stream".into())
+                .spawn(move || {
+                    let mut buffer = vec![0; buffer_len];
+                    let mut reading = false;
+                    loop {
+                        let command = if reading {
+                            match command_receiver.try_recv() {
+                                Ok(command) => Some(command),
+                                Err(TryRecvError::Empty) => None,
+                                Err(TryRecvError::Disconnected) => return,
+                            }
+                        } else {
+                            match command_receiver.recv() {
+                                Ok(command) => Some(command),
+                                Err(_) => return,
+                            }
+                        };
+                        let sent = match command {
+                            Some(Command::Read) => {
+                                reading = true;
+                                true
+                            }
+                            Some(Command::Write(data)) => {
+                                let result = device.write(&data).map(drop);
+                                written_sender.unbounded_send(result).is_ok()
+                            }
+                            None => match device.read_timeout(&mut buffer, READ_TIMEOUT_MS) {
+                                Ok(0) => true,
+                                result => {
+                                    reading = false;
+                                    let report =
+                                        result.map(|n| Report::from_input(&buffer[..n], numbered));
+                                    input_sender.unbounded_send(report).is_ok()
+                                }
+                            },
+                        };
+                        if !sent {
+                            return;
+                        }
+                    }
+                })?;
+            Ok(Self {
+                commands,
+                input,
+                written,
+                reading: false,
+                unacknowledged: 0,
+            })
+        }
+
+        pub(super) fn poll_read(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<HidResult<Report>>> {
+            if !self.reading {
+                // Without a helper thread the input channel is closed as well
+                let _ = self.commands.send(Command::Read);
+                self.reading = true;
+            }
+            let result = ready!(Pin::new(&mut self.input).poll_next(cx));
+            self.reading = false;
+            Poll::Ready(result)
+        }
+
+        /// Output reports are written by the helper thread, flushing waits
+        /// for the result
+        pub(super) fn start_send(&mut self, data: Vec<u8>) -> HidResult<()> {
+            self.commands
+                .send(Command::Write(data))
+                .map_err(|_| helper_thread_exited())?;
+            self.unacknowledged += 1;
+            Ok(())
+        }
+
+        pub(super) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<HidResult<()>> {
+            while self.unacknowledged > 0 {
+                let result = ready!(Pin::new(&mut self.written).poll_next(cx))
+                    .ok_or_else(helper_thread_exited)?;
+                self.unacknowledged -= 1;
+                result?;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn helper_thread_exited() -> HidError {
+        HidError::HidApiError {
+            message: "the helper thread of the report stream exited".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "mock")]
+    use crate::mock::VirtualDevice;
+    #[cfg(feature = "mock")]
+    use std::{
+        future::{poll_fn, Future},
+        pin::pin,
+        sync::Arc,
+        task::{Wake, Waker},
+        thread,
+    };
+
+    #[test]
+    fn test_report_id() {
+        let report = Report::from_input(&[0x02, 0x10, 0x20], true);
+        assert_eq!(report.report_id(), 2);
+        assert_eq!(report.data(), [0x10, 0x20]);
+        assert_eq!(report.to_output(), [0x02, 0x10, 0x20]);
+
+        let report = Report::from_input(&[0x02, 0x10, 0x20], false);
+        assert_eq!(report.report_id(), 0);
+        assert_eq!(report.data(), [0x02, 0x10, 0x20]);
+        assert_eq!(report.to_output(), [0x00, 0x02, 0x10, 0x20]);
+    }
+
+    /// A vendor collection with report ID 1, and Input and Output reports of
+    /// two bytes
+    #[cfg(feature = "mock")]
+    const DESCRIPTOR: [u8; 27] = [
+        0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0x85, 0x01, 0x09, 0x02, 0x15, 0x00, 0x26, 0xFF,
+        0x00, 0x75, 0x08, 0x95, 0x02, 0x81, 0x02, 0x09, 0x03, 0x91, 0x02, 0xC0,
+    ];
+
+    #[cfg(feature = "mock")]
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unpark(thread::Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    fn next(stream: &mut ReportStream) -> Option<HidResult<Report>> {
+        block_on(poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)))
+    }
+
+    #[cfg(feature = "mock")]
+    fn send(stream: &mut ReportStream, report: Report) -> HidResult<()> {
+        block_on(poll_fn(|cx| Pin::new(&mut *stream).poll_ready(cx)))?;
+        Pin::new(&mut *stream).start_send(report)?;
+        block_on(poll_fn(|cx| Pin::new(&mut *stream).poll_flush(cx)))
+    }
+
+    /// A stream as chosen for the device, and one with a helper thread, each
+    /// of its own device
+    #[cfg(feature = "mock")]
+    fn streams(build: impl Fn() -> VirtualDevice) -> [(VirtualDevice, ReportStream); 2] {
+        let device = build();
+        let stream = device.open().unwrap().into_report_stream().unwrap();
+        let threaded_device = build();
+        let threaded = threaded::Inner::new(threaded_device.open().unwrap(), 3, true).unwrap();
+        [
+            (device, stream),
+            (
+                threaded_device,
+                ReportStream {
+                    inner: Inner::Threaded(threaded),
+                    done: false,
+                },
+            ),
+        ]
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn test_send_and_receive() {
+        let build = || {
+            VirtualDevice::builder(0x046D, 0xC077)
+                .report_descriptor(&DESCRIPTOR)
+                .on_write(|device, data| {
+                    if data[1] == 0xFF {
+                        return Err(HidError::HidApiError {
+                            message: "rejected".into(),
+                        });
+                    }
+                    device.push_input_report(data);
+                    Ok(data.len())
+                })
+                .build()
+        };
+
+        for (device, mut stream) in streams(build) {
+            send(&mut stream, Report::new(1, vec![0x10, 0x20])).unwrap();
+            // Flushing waits for the write
+            assert_eq!(device.written_reports(), [[1, 0x10, 0x20]]);
+            let report = next(&mut stream).unwrap().unwrap();
+            assert_eq!((report.report_id(), report.data()), (1, &[0x10, 0x20][..]));
+
+            // Write errors are returned by the sink and do not end the stream
+            assert!(send(&mut stream, Report::new(1, vec![0xFF, 0x00])).is_err());
+            send(&mut stream, Report::new(1, vec![0x30, 0x40])).unwrap();
+            let report = next(&mut stream).unwrap().unwrap();
+            assert_eq!(report.data(), [0x30, 0x40]);
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn test_disconnect() {
+        let build = || {
+            VirtualDevice::builder(0x046D, 0xC077)
+                .report_descriptor(&DESCRIPTOR)
+                .build()
+        };
+
+        for (device, mut stream) in streams(build) {
+            let unplug = thread::spawn({
+                let device = device.clone();
+                move || {
+                    device.push_input_report(&[1, 0x10, 0x20]);
+                    thread::sleep(std::time::Duration::from_millis(50));
+                    device.disconnect();
+                }
+            });
+            assert!(next(&mut stream).unwrap().is_ok());
+            // The stream ends after the error
+            assert!(next(&mut stream).unwrap().is_err());
+            assert!(next(&mut stream).is_none());
+            unplug.join().unwrap();
+            assert!(send(&mut stream, Report::new(1, vec![0x10, 0x20])).is_err());
+        }
+    }
+}
@@ -0,0 +1,671 @@
+//! Kernel HID devices backed by `/dev/uhid`, for end-to-end tests
+//!
+//! A [`VirtualDevice`] creates a HID device in the kernel from a report
+//! descriptor. The kernel exposes it like real hardware, with a hidraw node
+//! and udev properties, so the Linux backends enumerate and open it like any
+//! other device. Input reports are injected with
+//! [`VirtualDevice::push_input_report`], and callbacks answer the GET_REPORT
+//! and SET_REPORT requests of the host.
+//!
+//! Creating devices requires write access to `/dev/uhid`, which is usually
+//! limited to root.
+//!
+//! ```no_run
+//! use hidapi::uhid::VirtualDevice;
+//! use std::time::Duration;
+//!
+//! let device = VirtualDevice::builder(0x046D, 0xC534)
+//!     .report_descriptor(&[
+//!         0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0x75, 0x08, 0x95, 0x02,
+//!         0x09, 0x02, 0x81, 0x02, 0x09, 0x03, 0xB1, 0x02, 0xC0,
+//!     ])
+//!     .on_get_report(|_, _| Ok(vec![0x12, 0x34]))
+//!     .build()?;
+//! let path = device.wait_for_hidraw(Duration::from_secs(5))?;
+//!
+//! let api = hidapi::HidApi::new()?;
+//! let hid = api.open_path(&path)?;
+//! device.push_input_report(&[0x01, 0x02])?;
+//! let mut buf = [0u8; 2];
+//! hid.read_timeout(&mut buf, 1000)?;
+//! # Ok::<(), hidapi::HidError>(())
+//! ```
+
+use std::ffi::CString;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use nix::poll::{poll, PollFd, PollFlags};
+
+use crate::descriptor::ReportType;
+use crate::{BusType, HidError, HidResult};
+
+const UHID_PATH: &str = "/dev/uhid";
+/// Where the kernel puts the HID devices created through uhid
+const UHID_SYSFS_PATH: &str = "/sys/devices/virtual/misc/uhid";
+
+// Event types from linux/uhid.h
+const UHID_START: u32 = 2;
+const UHID_OUTPUT: u32 = 6;
+const UHID_GET_REPORT: u32 = 9;
+const UHID_GET_REPORT_REPLY: u32 = 10;
+const UHID_CREATE2: u32 = 11;
+const UHID_INPUT2: u32 = 12;
+const UHID_SET_REPORT: u32 = 13;
+const UHID_SET_REPORT_REPLY: u32 = 14;
+
+const UHID_DATA_MAX: usize = 4096;
+/// `struct uhid_event` is packed, its largest member is `uhid_create2_req`
+const UHID_EVENT_SIZE: usize = 4 + 256 + 20 + UHID_DATA_MAX;
+
+// Bus values from linux/input.h
+const BUS_USB: u16 = 0x03;
+const BUS_BLUETOOTH: u16 = 0x05;
+const BUS_VIRTUAL: u16 = 0x06;
+const BUS_I2C: u16 = 0x18;
+const BUS_SPI: u16 = 0x1C;
+
+type GetReportHandler = Box<dyn FnMut(ReportType, u8) -> HidResult<Vec<u8>> + Send>;
+type SetReportHandler = Box<dyn FnMut(ReportType, &[u8]) -> HidResult<()> + Send>;
+
+/// Used for the default `uniq` strings, which identify the device in sysfs
+static NEXT_DEVICE_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+/// Describes a [`VirtualDevice`] before it is created
+pub struct VirtualDeviceBuilder {
+    name: String,
+    phys: String,
+    uniq: String,
+    bus_type: BusType,
+    vendor_id: u16,
+    product_id: u16,
+    version: u32,
+    country: u32,
+    report_descriptor: Vec<u8>,
+    get_report: Option<GetReportHandler>,
+    set_report: Option<SetReportHandler>,
+}
+
+impl VirtualDeviceBuilder {
+    /// The product string, `hidapi uhid device` by default
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn phys(mut self, phys: &str) -> Self {
+        self.phys = phys.into();
+        self
+    }
+
+    /// The serial number, unique for each device by default
+    ///
+    /// [`VirtualDevice::wait_for_hidraw`] finds the device by it, so it should
+    /// stay unique.
+    pub fn uniq(mut self, uniq: &str) -> Self {
+        self.uniq = uniq.into();
+        self
+    }
+
+    /// USB by default. Note that the Linux backends skip devices on unknown
+    /// buses.
+    pub fn bus_type(mut self, bus_type: BusType) -> Self {
+        self.bus_type = bus_type;
+        self
+    }
+
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn country(mut self, country: u32) -> Self {
+        self.country = country;
+        self
+    }
+
+    pub fn report_descriptor(mut self, report_descriptor: &[u8]) -> Self {
+        self.report_descriptor = report_descriptor.to_vec();
+        self
+    }
+
+    /// Answer GET_REPORT requests, e.g. from [`HidDevice::get_feature_report`]
+    ///
+    /// The handler gets the report type and ID, and returns the report
+    /// starting with the report ID if the device uses report IDs. Without a
+    /// handler the requests fail with `EIO`.
+    ///
+    /// [`HidDevice::get_feature_report`]: crate::HidDevice::get_feature_report
+    pub fn on_get_report(
+        mut self,
+        handler: impl FnMut(ReportType, u8) -> HidResult<Vec<u8>> + Send + 'static,
+    ) -> Self {
+        self.get_report = Some(Box::new(handler));
+        self
+    }
+
+    /// Answer SET_REPORT requests, e.g. from [`HidDevice::send_feature_report`]
+    ///
+    /// The handler gets the report type and the report as sent by the host.
+    /// Without a handler the requests succeed. Either way the reports are
+    /// available from [`VirtualDevice::sent_feature_reports`] and
+    /// [`VirtualDevice::sent_output_reports`].
+    ///
+    /// [`HidDevice::send_feature_report`]: crate::HidDevice::send_feature_report
+    pub fn on_set_report(
+        mut self,
+        handler: impl FnMut(ReportType, &[u8]) -> HidResult<()> + Send + 'static,
+    ) -> Self {
+        self.set_report = Some(Box::new(handler));
+        self
+    }
+
+    /// Create the device in the kernel
+    ///
+    /// Fails if `/dev/uhid` cannot be opened, or if the report descriptor is
+    /// longer than the 4096 bytes uhid supports.
+    pub fn build(self) -> HidResult<VirtualDevice> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC | libc::O_NONBLOCK)
+            .open(UHID_PATH)?;
+        write_event(&file, &create2_event(&self)?)?;
+
+        let shared = Arc::new(Shared {
+            file,
+            uniq: self.uniq,
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            state: Mutex::new(State::default()),
+        });
+        let (stop, stop_receiver) = UnixStream::pair()?;
+        let handlers = Handlers {
+            get_report: self.get_report,
+            set_report: self.set_report,
+        };
+        let thread = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("hidapi-uhid".into())
+                .spawn(move || shared.run(handlers, stop_receiver))?
+        };
+
+        Ok(VirtualDevice {
+            shared,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl fmt::Debug for VirtualDeviceBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualDeviceBuilder")
+            .field("name", &self.name)
+            .field("uniq", &self.uniq)
+            .field("vendor_id", &self.vendor_id)
+            .field("product_id", &self.product_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A HID device created through `/dev/uhid`
+///
+/// A helper thread answers the requests of the kernel. Dropping the device
+/// removes it from the system.
+pub struct VirtualDevice {
+    shared: Arc<Shared>,
+    stop: UnixStream,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    file: File,
+    uniq: String,
+    vendor_id: u16,
+    product_id: u16,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    started: bool,
+    written_reports: Vec<Vec<u8>>,
+    sent_feature_reports: Vec<Vec<u8>>,
+    sent_output_reports: Vec<Vec<u8>>,
+}
+
+struct Handlers {
+    get_report: Option<GetReportHandler>,
+    set_report: Option<SetReportHandler>,
+}
+
+impl VirtualDevice {
+    /// Start describing a device
+    pub fn builder(vendor_id: u16, product_id: u16) -> VirtualDeviceBuilder {
+        let index = NEXT_DEVICE_INDEX.fetch_add(1, Ordering::Relaxed);
+        VirtualDeviceBuilder {
+            name: "hidapi uhid device".into(),
+            phys: String::new(),
+            uniq: format!("hidapi-{}-{index}", std::process::id()),
+            bus_type: BusType::Usb,
+            vendor_id,
+            product_id,
+            version: 0,
+            country: 0,
+            report_descriptor: Vec::new(),
+            get_report: None,
+            set_report: None,
+        }
+    }
+
+    /// Inject an Input report
+    ///
+    /// The first byte must be the report ID if the report descriptor uses
+    /// report IDs.
+    pub fn push_input_report(&self, data: &[u8]) -> HidResult<()> {
+        write_event(&self.shared.file, &input2_event(data)?)
+    }
+
+    /// Wait until the kernel created the hidraw node of the device, and
+    /// return its path
+    pub fn wait_for_hidraw(&self, timeout: Duration) -> HidResult<CString> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.shared.state().started {
+                if let Some(path) = self.shared.find_hidraw() {
+                    return Ok(path);
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(HidError::IoError {
+                    error: io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "the kernel did not create a hidraw device",
+                    ),
+                });
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Output reports written by the host, e.g. with [`HidDevice::write`]
+    ///
+    /// [`HidDevice::write`]: crate::HidDevice::write
+    pub fn written_reports(&self) -> Vec<Vec<u8>> {
+        self.shared.state().written_reports.clone()
+    }
+
+    /// Feature reports sent by the host in SET_REPORT requests
+    pub fn sent_feature_reports(&self) -> Vec<Vec<u8>> {
+        self.shared.state().sent_feature_reports.clone()
+    }
+
+    /// Output reports sent by the host in SET_REPORT requests, e.g. with
+    /// [`HidDevice::send_output_report`]
+    ///
+    /// [`HidDevice::send_output_report`]: crate::HidDevice::send_output_report
+    pub fn sent_output_reports(&self) -> Vec<Vec<u8>> {
+        self.shared.state().sent_output_reports.clone()
+    }
+}
+
+impl Drop for VirtualDevice {
+    fn drop(&mut self) {
+        let _ = self.stop.write(&[0]);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for VirtualDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualDevice")
+            .field("uniq", &self.shared.uniq)
+            .field("vendor_id", &self.shared.vendor_id)
+            .field("product_id", &self.shared.product_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Shared {
+    /// A panicking handler does not invalidate the recorded reports
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Answer the kernel until `stop` becomes readable
+    fn run(&self, mut handlers: Handlers, stop: UnixStream) {
+        let mut buf = vec![0u8; UHID_EVENT_SIZE];
+        loop {
+            let mut fds = [
+                PollFd::new(&self.file, PollFlags::POLLIN),
+                PollFd::new(&stop, PollFlags::POLLIN),
+            ];
+            match poll(&mut fds, -1) {
+                Ok(_) => {}
+                Err(nix::errno::Errno::EINTR) => continue,
+                Err(_) => return,
+            }
+            if fds[1].revents().is_some_and(|e| !e.is_empty()) {
+                return;
+            }
+
+            let len = match (&self.file).read(&mut buf) {
+                Ok(len) => len,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => continue,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => return,
+            };
+            if let Some(request) = Request::parse(&buf[..len]) {
+                // The kernel gives up on unanswered requests after a timeout
+                let _ = self.handle(&mut handlers, request);
+            }
+        }
+    }
+
+    fn handle(&self, handlers: &mut Handlers, request: Request) -> HidResult<()> {
+        match request {
+            Request::Start => self.state().started = true,
+            Request::Output(data) => self.state().written_reports.push(data),
+            Request::GetReport {
+                id,
+                report_type,
+                report_id,
+            } => {
+                let result = match &mut handlers.get_report {
+                    Some(handler) => handler(report_type, report_id),
+                    None => Err(io::Error::from_raw_os_error(libc::EIO).into()),
+                };
+                write_event(&self.file, &get_report_reply_event(id, result))?;
+            }
+            Request::SetReport {
+                id,
+                report_type,
+                data,
+            } => {
+                match report_type {
+                    ReportType::Feature => self.state().sent_feature_reports.push(data.clone()),
+                    ReportType::Output => self.state().sent_output_reports.push(data.clone()),
+                    ReportType::Input => {}
+                }
+                let result = match &mut handlers.set_report {
+                    Some(handler) => handler(report_type, &data),
+                    None => Ok(()),
+                };
+                write_event(&self.file, &set_report_reply_event(id, result))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The hidraw node below the HID device with our `uniq`
+    fn find_hidraw(&self) -> Option<CString> {
+        let id = format!(":{:08X}:{:08X}", self.vendor_id, self.product_id);
+        let uniq = format!("HID_UNIQ={}", self.uniq);
+        for entry in fs::read_dir(UHID_SYSFS_PATH).ok()?.flatten() {
+            let uevent = match fs::read_to_string(entry.path().join("uevent")) {
+                Ok(uevent) => uevent,
+                Err(_) => continue,
+            };
+            let matches = uevent.lines().any(|l| l == uniq)
+                && uevent
+                    .lines()
+                    .any(|l| l.starts_with("HID_ID=") && l.ends_with(&id));
+            if !matches {
+                continue;
+            }
+            let hidraw = fs::read_dir(entry.path().join("hidraw"))
+                .ok()?
+                .flatten()
+                .next()?;
+            let path = Path::new("/dev").join(hidraw.file_name());
+            if path.exists() {
+                return CString::new(path.into_os_string().into_vec()).ok();
+            }
+        }
+        None
+    }
+}
+
+/// The requests of the kernel that the device answers or records
+#[derive(Debug, PartialEq)]
+enum Request {
+    Start,
+    Output(Vec<u8>),
+    GetReport {
+        id: u32,
+        report_type: ReportType,
+        report_id: u8,
+    },
+    SetReport {
+        id: u32,
+        report_type: ReportType,
+        data: Vec<u8>,
+    },
+}
+
+impl Request {
+    /// Parse a `struct uhid_event`, ignoring the events we do not care about
+    fn parse(event: &[u8]) -> Option<Self> {
+        let event_type = u32::from_ne_bytes(event.get(..4)?.try_into().unwrap());
+        let payload = &event[4..];
+        let u16_at = |offset: usize| {
+            payload
+                .get(offset..offset + 2)
+                .map(|b| u16::from_ne_bytes(b.try_into().unwrap()) as usize)
+        };
+        let u32_at = |offset: usize| {
+            payload
+                .get(offset..offset + 4)
+                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()))
+        };
+        let report_type = |rtype: u8| match rtype {
+            0 => Some(ReportType::Feature),
+            1 => Some(ReportType::Output),
+            2 => Some(ReportType::Input),
+            _ => None,
+        };
+
+        match event_type {
+            UHID_START => Some(Request::Start),
+            UHID_OUTPUT => {
+                let size = u16_at(UHID_DATA_MAX)?.min(UHID_DATA_MAX);
+                Some(Request::Output(payload.get(..size)?.to_vec()))
+            }
+            UHID_GET_REPORT => Some(Request::GetReport {
+                id: u32_at(0)?,
+                report_id: *payload.get(4)?,
+                report_type: report_type(*payload.get(5)?)?,
+            }),
+            UHID_SET_REPORT => {
+                let size = u16_at(6)?.min(UHID_DATA_MAX);
+                Some(Request::SetReport {
+                    id: u32_at(0)?,
+                    report_type: report_type(*payload.get(5)?)?,
+                    data: payload.get(8..8 + size)?.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn event(event_type: u32) -> Vec<u8> {
+    let mut event = vec![0u8; UHID_EVENT_SIZE];
+    event[..4].copy_from_slice(&event_type.to_ne_bytes());
+    event
+}
+
+/// Copy `data` into the payload of an event
+fn put(event: &mut [u8], offset: usize, data: &[u8]) {
+    event[4 + offset..4 + offset + data.len()].copy_from_slice(data);
+}
+
+fn check_size(data: &[u8]) -> HidResult<()> {
+    if data.len() > UHID_DATA_MAX {
+        return Err(HidError::IoError {
+            error: io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "uhid supports at most 4096 bytes",
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn create2_event(builder: &VirtualDeviceBuilder) -> HidResult<Vec<u8>> {
+    check_size(&builder.report_descriptor)?;
+    let bus = match builder.bus_type {
+        BusType::Usb => BUS_USB,
+        BusType::Bluetooth => BUS_BLUETOOTH,
+        BusType::I2c => BUS_I2C,
+        BusType::Spi => BUS_SPI,
+        BusType::Unknown => BUS_VIRTUAL,
+    };
+
+    // The strings are NUL terminated, so they keep their last byte zero
+    let mut event = event(UHID_CREATE2);
+    let name = builder.name.as_bytes();
+    put(&mut event, 0, &name[..name.len().min(127)]);
+    let phys = builder.phys.as_bytes();
+    put(&mut event, 128, &phys[..phys.len().min(63)]);
+    let uniq = builder.uniq.as_bytes();
+    put(&mut event, 192, &uniq[..uniq.len().min(63)]);
+    put(
+        &mut event,
+        256,
+        &(builder.report_descriptor.len() as u16).to_ne_bytes(),
+    );
+    put(&mut event, 258, &bus.to_ne_bytes());
+    put(&mut event, 260, &(builder.vendor_id as u32).to_ne_bytes());
+    put(&mut event, 264, &(builder.product_id as u32).to_ne_bytes());
+    put(&mut event, 268, &builder.version.to_ne_bytes());
+    put(&mut event, 272, &builder.country.to_ne_bytes());
+    put(&mut event, 276, &builder.report_descriptor);
+    Ok(event)
+}
+
+fn input2_event(data: &[u8]) -> HidResult<Vec<u8>> {
+    check_size(data)?;
+    let mut event = event(UHID_INPUT2);
+    put(&mut event, 0, &(data.len() as u16).to_ne_bytes());
+    put(&mut event, 2, data);
+    Ok(event)
+}
+
+/// The errno of a failed request, as the kernel expects it in replies
+fn errno(error: &HidError) -> u16 {
+    match error {
+        HidError::IoError { error } => error.raw_os_error().unwrap_or(libc::EIO) as u16,
+        _ => libc::EIO as u16,
+    }
+}
+
+fn get_report_reply_event(id: u32, result: HidResult<Vec<u8>>) -> Vec<u8> {
+    let mut event = event(UHID_GET_REPORT_REPLY);
+    put(&mut event, 0, &id.to_ne_bytes());
+    match result {
+        Ok(mut data) => {
+            data.truncate(UHID_DATA_MAX);
+            put(&mut event, 6, &(data.len() as u16).to_ne_bytes());
+            put(&mut event, 8, &data);
+        }
+        Err(e) => put(&mut event, 4, &errno(&e).to_ne_bytes()),
+    }
+    event
+}
+
+fn set_report_reply_event(id: u32, result: HidResult<()>) -> Vec<u8> {
+    let mut event = event(UHID_SET_REPORT_REPLY);
+    put(&mut event, 0, &id.to_ne_bytes());
+    if let Err(e) = result {
+        put(&mut event, 4, &errno(&e).to_ne_bytes());
+    }
+    event
+}
+
+fn write_event(mut file: &File, event: &[u8]) -> HidResult<()> {
+    file.write_all(event)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create2_event() {
+        let builder = VirtualDevice::builder(0x046D, 0xC534)
+            .name("Receiver")
+            .uniq("1234")
+            .bus_type(BusType::Bluetooth)
+            .report_descriptor(&[0x06, 0x00, 0xFF]);
+        let event = create2_event(&builder).unwrap();
+
+        assert_eq!(event.len(), UHID_EVENT_SIZE);
+        assert_eq!(event[..4], UHID_CREATE2.to_ne_bytes());
+        assert_eq!(&event[4..13], b"Receiver\0");
+        assert_eq!(&event[196..201], b"1234\0");
+        assert_eq!(event[260..262], 3u16.to_ne_bytes());
+        assert_eq!(event[262..264], BUS_BLUETOOTH.to_ne_bytes());
+        assert_eq!(event[264..268], 0x046Du32.to_ne_bytes());
+        assert_eq!(event[268..272], 0xC534u32.to_ne_bytes());
+        assert_eq!(event[280..283], [0x06, 0x00, 0xFF]);
+
+        let builder = builder.report_descriptor(&[0; UHID_DATA_MAX + 1]);
+        assert!(create2_event(&builder).is_err());
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        let mut get_report = event(UHID_GET_REPORT);
+        put(&mut get_report, 0, &7u32.to_ne_bytes());
+        put(&mut get_report, 4, &[0x03, 0x00]);
+        assert_eq!(
+            Request::parse(&get_report),
+            Some(Request::GetReport {
+                id: 7,
+                report_type: ReportType::Feature,
+                report_id: 3,
+            })
+        );
+
+        let mut set_report = event(UHID_SET_REPORT);
+        put(&mut set_report, 0, &8u32.to_ne_bytes());
+        put(&mut set_report, 4, &[0x03, 0x00]);
+        put(&mut set_report, 6, &2u16.to_ne_bytes());
+        put(&mut set_report, 8, &[0x03, 0x04]);
+        assert_eq!(
+            Request::parse(&set_report),
+            Some(Request::SetReport {
+                id: 8,
+                report_type: ReportType::Feature,
+                data: vec![0x03, 0x04],
+            })
+        );
+
+        let mut output = event(UHID_OUTPUT);
+        put(&mut output, 0, &[0x01, 0x02]);
+        put(&mut output, UHID_DATA_MAX, &2u16.to_ne_bytes());
+        assert_eq!(
+            Request::parse(&output),
+            Some(Request::Output(vec![0x01, 0x02]))
+        );
+
+        // Open and close are not interesting
+        assert_eq!(Request::parse(&event(4)), None);
+        assert_eq!(Request::parse(&[0x02]), None);
+    }
+}
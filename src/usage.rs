@@ -0,0 +1,951 @@
+//! Names of the HID Usage Tables
+//!
+//! Every usage page of the [HID Usage Tables] has a module with its number
+//! as `PAGE` and a [`Usage`] constant for each named usage, so that
+//! descriptors and device lists can be matched without magic numbers.
+//!
+//! ```
+//! use hidapi::usage::{self, generic_desktop};
+//!
+//! assert_eq!(generic_desktop::MOUSE.page, generic_desktop::PAGE);
+//! assert_eq!(usage::name(generic_desktop::MOUSE).as_deref(), Some("Mouse"));
+//! assert_eq!(usage::from_name(0x01, "hat switch"), Some(generic_desktop::HAT_SWITCH));
+//! ```
+//!
+//! The tables cover the page names and the commonly used usages of the
+//! pages with constants. Vendor defined pages and usages that are not in the
+//! tables have no name.
+//!
+//! [HID Usage Tables]: https://usb.org/document-library/hid-usage-tables-15
+
+use std::borrow::Cow;
+
+pub use crate::descriptor::Usage;
+
+/// Number, name, and the usage IDs and names of a usage page
+type Page = (u16, &'static str, &'static [(u16, &'static str)]);
+
+/// Declare a module with the usage constants of each page, and the table
+/// of their names
+macro_rules! usage_pages {
+    ($(
+        $(#[$attr:meta])*
+        $module:ident = $page:literal, $page_name:literal {
+            $($usage:ident = $id:literal, $name:literal;)*
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub mod $module {
+                pub const PAGE: u16 = $page;
+
+                $(
+                    #[doc = $name]
+                    pub const $usage: super::Usage = super::Usage::new(PAGE, $id);
+                )*
+
+                pub(super) const NAMES: &[(u16, &str)] = &[$(($id, $name)),*];
+            }
+        )*
+
+        /// Every page, sorted by number
+        const PAGES: &[Page] = &[
+            $(($page, $page_name, $module::NAMES)),*
+        ];
+    };
+}
+
+/// The name of a usage page
+pub fn page_name(page: u16) -> Option<&'static str> {
+    find_page(page).map(|(_, name, _)| name)
+}
+
+/// The number of the usage page with the given name, ignoring ASCII case
+pub fn page_from_name(name: &str) -> Option<u16> {
+    PAGES
+        .iter()
+        .find(|(_, page_name, _)| page_name.eq_ignore_ascii_case(name))
+        .map(|(page, _, _)| *page)
+}
+
+/// The name of a usage
+///
+/// Buttons and ordinals are named after their number, like `Button 3`.
+pub fn name(usage: Usage) -> Option<Cow<'static, str>> {
+    let (_, _, names) = find_page(usage.page)?;
+    if let Ok(i) = names.binary_search_by_key(&usage.id, |(id, _)| *id) {
+        return Some(Cow::Borrowed(names[i].1));
+    }
+    match (usage.page, usage.id) {
+        (button::PAGE, id) => Some(Cow::Owned(format!("Button {id}"))),
+        (ordinal::PAGE, id @ 1..) => Some(Cow::Owned(format!("Instance {id}"))),
+        _ => None,
+    }
+}
+
+/// The usage on `page` with the given name, ignoring ASCII case
+pub fn from_name(page: u16, name: &str) -> Option<Usage> {
+    let (_, _, names) = find_page(page)?;
+    if let Some((id, _)) = names.iter().find(|(_, n)| n.eq_ignore_ascii_case(name)) {
+        return Some(Usage::new(page, *id));
+    }
+    let prefix = match page {
+        button::PAGE => "Button ",
+        ordinal::PAGE => "Instance ",
+        _ => return None,
+    };
+    let number = name
+        .get(..prefix.len())
+        .filter(|p| p.eq_ignore_ascii_case(prefix))
+        .and_then(|_| name[prefix.len()..].parse().ok())
+        .filter(|id| *id > 0)?;
+    Some(Usage::new(page, number))
+}
+
+fn find_page(page: u16) -> Option<Page> {
+    let i = PAGES.binary_search_by_key(&page, |(p, _, _)| *p).ok()?;
+    Some(PAGES[i])
+}
+
+usage_pages! {
+    /// Generic Desktop Page (0x01)
+    generic_desktop = 0x01, "Generic Desktop" {
+        POINTER = 0x01, "Pointer";
+        MOUSE = 0x02, "Mouse";
+        JOYSTICK = 0x04, "Joystick";
+        GAMEPAD = 0x05, "Gamepad";
+        KEYBOARD = 0x06, "Keyboard";
+        KEYPAD = 0x07, "Keypad";
+        MULTI_AXIS_CONTROLLER = 0x08, "Multi-axis Controller";
+        TABLET_PC_SYSTEM_CONTROLS = 0x09, "Tablet PC System Controls";
+        WATER_COOLING_DEVICE = 0x0A, "Water Cooling Device";
+        COMPUTER_CHASSIS_DEVICE = 0x0B, "Computer Chassis Device";
+        WIRELESS_RADIO_CONTROLS = 0x0C, "Wireless Radio Controls";
+        PORTABLE_DEVICE_CONTROL = 0x0D, "Portable Device Control";
+        SYSTEM_MULTI_AXIS_CONTROLLER = 0x0E, "System Multi-Axis Controller";
+        SPATIAL_CONTROLLER = 0x0F, "Spatial Controller";
+        ASSISTIVE_CONTROL = 0x10, "Assistive Control";
+        DEVICE_DOCK = 0x11, "Device Dock";
+        DOCKABLE_DEVICE = 0x12, "Dockable Device";
+        CALL_STATE_MANAGEMENT_CONTROL = 0x13, "Call State Management Control";
+        X = 0x30, "X";
+        Y = 0x31, "Y";
+        Z = 0x32, "Z";
+        RX = 0x33, "Rx";
+        RY = 0x34, "Ry";
+        RZ = 0x35, "Rz";
+        SLIDER = 0x36, "Slider";
+        DIAL = 0x37, "Dial";
+        WHEEL = 0x38, "Wheel";
+        HAT_SWITCH = 0x39, "Hat Switch";
+        COUNTED_BUFFER = 0x3A, "Counted Buffer";
+        BYTE_COUNT = 0x3B, "Byte Count";
+        MOTION_WAKEUP = 0x3C, "Motion Wakeup";
+        START = 0x3D, "Start";
+        SELECT = 0x3E, "Select";
+        VX = 0x40, "Vx";
+        VY = 0x41, "Vy";
+        VZ = 0x42, "Vz";
+        VBRX = 0x43, "Vbrx";
+        VBRY = 0x44, "Vbry";
+        VBRZ = 0x45, "Vbrz";
+        VNO = 0x46, "Vno";
+        FEATURE_NOTIFICATION = 0x47, "Feature Notification";
+        RESOLUTION_MULTIPLIER = 0x48, "Resolution Multiplier";
+        QX = 0x49, "Qx";
+        QY = 0x4A, "Qy";
+        QZ = 0x4B, "Qz";
+        QW = 0x4C, "Qw";
+        SYSTEM_CONTROL = 0x80, "System Control";
+        SYSTEM_POWER_DOWN = 0x81, "System Power Down";
+        SYSTEM_SLEEP = 0x82, "System Sleep";
+        SYSTEM_WAKE_UP = 0x83, "System Wake Up";
+        SYSTEM_CONTEXT_MENU = 0x84, "System Context Menu";
+        SYSTEM_MAIN_MENU = 0x85, "System Main Menu";
+        SYSTEM_APP_MENU = 0x86, "System App Menu";
+        SYSTEM_MENU_HELP = 0x87, "System Menu Help";
+        SYSTEM_MENU_EXIT = 0x88, "System Menu Exit";
+        SYSTEM_MENU_SELECT = 0x89, "System Menu Select";
+        SYSTEM_MENU_RIGHT = 0x8A, "System Menu Right";
+        SYSTEM_MENU_LEFT = 0x8B, "System Menu Left";
+        SYSTEM_MENU_UP = 0x8C, "System Menu Up";
+        SYSTEM_MENU_DOWN = 0x8D, "System Menu Down";
+        SYSTEM_COLD_RESTART = 0x8E, "System Cold Restart";
+        SYSTEM_WARM_RESTART = 0x8F, "System Warm Restart";
+        D_PAD_UP = 0x90, "D-pad Up";
+        D_PAD_DOWN = 0x91, "D-pad Down";
+        D_PAD_RIGHT = 0x92, "D-pad Right";
+        D_PAD_LEFT = 0x93, "D-pad Left";
+        SYSTEM_DOCK = 0xA0, "System Dock";
+        SYSTEM_UNDOCK = 0xA1, "System Undock";
+        SYSTEM_SETUP = 0xA2, "System Setup";
+        SYSTEM_BREAK = 0xA3, "System Break";
+        SYSTEM_DEBUGGER_BREAK = 0xA4, "System Debugger Break";
+        APPLICATION_BREAK = 0xA5, "Application Break";
+        APPLICATION_DEBUGGER_BREAK = 0xA6, "Application Debugger Break";
+        SYSTEM_SPEAKER_MUTE = 0xA7, "System Speaker Mute";
+        SYSTEM_HIBERNATE = 0xA8, "System Hibernate";
+        SYSTEM_DISPLAY_INVERT = 0xB0, "System Display Invert";
+        SYSTEM_DISPLAY_INTERNAL = 0xB1, "System Display Internal";
+        SYSTEM_DISPLAY_EXTERNAL = 0xB2, "System Display External";
+        SYSTEM_DISPLAY_BOTH = 0xB3, "System Display Both";
+        SYSTEM_DISPLAY_DUAL = 0xB4, "System Display Dual";
+        SYSTEM_DISPLAY_TOGGLE_INT_EXT = 0xB5, "System Display Toggle Int/Ext";
+        SYSTEM_DISPLAY_SWAP_PRIMARY_SECONDARY = 0xB6, "System Display Swap Primary/Secondary";
+        SYSTEM_DISPLAY_LCD_AUTOSCALE = 0xB7, "System Display LCD Autoscale";
+        WIRELESS_RADIO_BUTTON = 0xC6, "Wireless Radio Button";
+        WIRELESS_RADIO_LED = 0xC7, "Wireless Radio LED";
+        WIRELESS_RADIO_SLIDER_SWITCH = 0xC8, "Wireless Radio Slider Switch";
+    }
+    /// Simulation Controls Page (0x02)
+    simulation_controls = 0x02, "Simulation Controls" {}
+    /// VR Controls Page (0x03)
+    vr_controls = 0x03, "VR Controls" {}
+    /// Sport Controls Page (0x04)
+    sport_controls = 0x04, "Sport Controls" {}
+    /// Game Controls Page (0x05)
+    game_controls = 0x05, "Game Controls" {}
+    /// Generic Device Controls Page (0x06)
+    generic_device_controls = 0x06, "Generic Device Controls" {}
+    /// Keyboard/Keypad Page (0x07)
+    keyboard = 0x07, "Keyboard/Keypad" {
+        ERROR_ROLL_OVER = 0x01, "Keyboard ErrorRollOver";
+        POST_FAIL = 0x02, "Keyboard POSTFail";
+        ERROR_UNDEFINED = 0x03, "Keyboard ErrorUndefined";
+        KEYBOARD_A = 0x04, "Keyboard a and A";
+        KEYBOARD_B = 0x05, "Keyboard b and B";
+        KEYBOARD_C = 0x06, "Keyboard c and C";
+        KEYBOARD_D = 0x07, "Keyboard d and D";
+        KEYBOARD_E = 0x08, "Keyboard e and E";
+        KEYBOARD_F = 0x09, "Keyboard f and F";
+        KEYBOARD_G = 0x0A, "Keyboard g and G";
+        KEYBOARD_H = 0x0B, "Keyboard h and H";
+        KEYBOARD_I = 0x0C, "Keyboard i and I";
+        KEYBOARD_J = 0x0D, "Keyboard j and J";
+        KEYBOARD_K = 0x0E, "Keyboard k and K";
+        KEYBOARD_L = 0x0F, "Keyboard l and L";
+        KEYBOARD_M = 0x10, "Keyboard m and M";
+        KEYBOARD_N = 0x11, "Keyboard n and N";
+        KEYBOARD_O = 0x12, "Keyboard o and O";
+        KEYBOARD_P = 0x13, "Keyboard p and P";
+        KEYBOARD_Q = 0x14, "Keyboard q and Q";
+        KEYBOARD_R = 0x15, "Keyboard r and R";
+        KEYBOARD_S = 0x16, "Keyboard s and S";
+        KEYBOARD_T = 0x17, "Keyboard t and T";
+        KEYBOARD_U = 0x18, "Keyboard u and U";
+        KEYBOARD_V = 0x19, "Keyboard v and V";
+        KEYBOARD_W = 0x1A, "Keyboard w and W";
+        KEYBOARD_X = 0x1B, "Keyboard x and X";
+        KEYBOARD_Y = 0x1C, "Keyboard y and Y";
+        KEYBOARD_Z = 0x1D, "Keyboard z and Z";
+        KEYBOARD_1 = 0x1E, "Keyboard 1 and !";
+        KEYBOARD_2 = 0x1F, "Keyboard 2 and @";
+        KEYBOARD_3 = 0x20, "Keyboard 3 and #";
+        KEYBOARD_4 = 0x21, "Keyboard 4 and $";
+        KEYBOARD_5 = 0x22, "Keyboard 5 and %";
+        KEYBOARD_6 = 0x23, "Keyboard 6 and ^";
+        KEYBOARD_7 = 0x24, "Keyboard 7 and &";
+        KEYBOARD_8 = 0x25, "Keyboard 8 and *";
+        KEYBOARD_9 = 0x26, "Keyboard 9 and (";
+        KEYBOARD_0 = 0x27, "Keyboard 0 and )";
+        KEYBOARD_RETURN = 0x28, "Keyboard Return (ENTER)";
+        KEYBOARD_ESCAPE = 0x29, "Keyboard ESCAPE";
+        KEYBOARD_DELETE = 0x2A, "Keyboard DELETE (Backspace)";
+        KEYBOARD_TAB = 0x2B, "Keyboard Tab";
+        KEYBOARD_SPACEBAR = 0x2C, "Keyboard Spacebar";
+        KEYBOARD_MINUS = 0x2D, "Keyboard - and (underscore)";
+        KEYBOARD_EQUALS = 0x2E, "Keyboard = and +";
+        KEYBOARD_LEFT_BRACKET = 0x2F, "Keyboard [ and {";
+        KEYBOARD_RIGHT_BRACKET = 0x30, "Keyboard ] and }";
+        KEYBOARD_BACKSLASH = 0x31, "Keyboard \\ and |";
+        KEYBOARD_NON_US_HASH = 0x32, "Keyboard Non-US # and ~";
+        KEYBOARD_SEMICOLON = 0x33, "Keyboard ; and :";
+        KEYBOARD_APOSTROPHE = 0x34, "Keyboard ' and \"";
+        KEYBOARD_GRAVE_ACCENT = 0x35, "Keyboard Grave Accent and Tilde";
+        KEYBOARD_COMMA = 0x36, "Keyboard , and <";
+        KEYBOARD_PERIOD = 0x37, "Keyboard . and >";
+        KEYBOARD_SLASH = 0x38, "Keyboard / and ?";
+        KEYBOARD_CAPS_LOCK = 0x39, "Keyboard Caps Lock";
+        KEYBOARD_F1 = 0x3A, "Keyboard F1";
+        KEYBOARD_F2 = 0x3B, "Keyboard F2";
+        KEYBOARD_F3 = 0x3C, "Keyboard F3";
+        KEYBOARD_F4 = 0x3D, "Keyboard F4";
+        KEYBOARD_F5 = 0x3E, "Keyboard F5";
+        KEYBOARD_F6 = 0x3F, "Keyboard F6";
+        KEYBOARD_F7 = 0x40, "Keyboard F7";
+        KEYBOARD_F8 = 0x41, "Keyboard F8";
+        KEYBOARD_F9 = 0x42, "Keyboard F9";
+        KEYBOARD_F10 = 0x43, "Keyboard F10";
+        KEYBOARD_F11 = 0x44, "Keyboard F11";
+        KEYBOARD_F12 = 0x45, "Keyboard F12";
+        KEYBOARD_PRINT_SCREEN = 0x46, "Keyboard PrintScreen";
+        KEYBOARD_SCROLL_LOCK = 0x47, "Keyboard Scroll Lock";
+        KEYBOARD_PAUSE = 0x48, "Keyboard Pause";
+        KEYBOARD_INSERT = 0x49, "Keyboard Insert";
+        KEYBOARD_HOME = 0x4A, "Keyboard Home";
+        KEYBOARD_PAGE_UP = 0x4B, "Keyboard PageUp";
+        KEYBOARD_DELETE_FORWARD = 0x4C, "Keyboard Delete Forward";
+        KEYBOARD_END = 0x4D, "Keyboard End";
+        KEYBOARD_PAGE_DOWN = 0x4E, "Keyboard PageDown";
+        KEYBOARD_RIGHT_ARROW = 0x4F, "Keyboard RightArrow";
+        KEYBOARD_LEFT_ARROW = 0x50, "Keyboard LeftArrow";
+        KEYBOARD_DOWN_ARROW = 0x51, "Keyboard DownArrow";
+        KEYBOARD_UP_ARROW = 0x52, "Keyboard UpArrow";
+        KEYPAD_NUM_LOCK = 0x53, "Keypad Num Lock and Clear";
+        KEYPAD_DIVIDE = 0x54, "Keypad /";
+        KEYPAD_MULTIPLY = 0x55, "Keypad *";
+        KEYPAD_MINUS = 0x56, "Keypad -";
+        KEYPAD_PLUS = 0x57, "Keypad +";
+        KEYPAD_ENTER = 0x58, "Keypad ENTER";
+        KEYPAD_1 = 0x59, "Keypad 1 and End";
+        KEYPAD_2 = 0x5A, "Keypad 2 and Down Arrow";
+        KEYPAD_3 = 0x5B, "Keypad 3 and PageDn";
+        KEYPAD_4 = 0x5C, "Keypad 4 and Left Arrow";
+        KEYPAD_5 = 0x5D, "Keypad 5";
+        KEYPAD_6 = 0x5E, "Keypad 6 and Right Arrow";
+        KEYPAD_7 = 0x5F, "Keypad 7 and Home";
+        KEYPAD_8 = 0x60, "Keypad 8 and Up Arrow";
+        KEYPAD_9 = 0x61, "Keypad 9 and PageUp";
+        KEYPAD_0 = 0x62, "Keypad 0 and Insert";
+        KEYPAD_PERIOD = 0x63, "Keypad . and Delete";
+        KEYBOARD_NON_US_BACKSLASH = 0x64, "Keyboard Non-US \\ and |";
+        KEYBOARD_APPLICATION = 0x65, "Keyboard Application";
+        KEYBOARD_POWER = 0x66, "Keyboard Power";
+        KEYPAD_EQUALS = 0x67, "Keypad =";
+        KEYBOARD_F13 = 0x68, "Keyboard F13";
+        KEYBOARD_F14 = 0x69, "Keyboard F14";
+        KEYBOARD_F15 = 0x6A, "Keyboard F15";
+        KEYBOARD_F16 = 0x6B, "Keyboard F16";
+        KEYBOARD_F17 = 0x6C, "Keyboard F17";
+        KEYBOARD_F18 = 0x6D, "Keyboard F18";
+        KEYBOARD_F19 = 0x6E, "Keyboard F19";
+        KEYBOARD_F20 = 0x6F, "Keyboard F20";
+        KEYBOARD_F21 = 0x70, "Keyboard F21";
+        KEYBOARD_F22 = 0x71, "Keyboard F22";
+        KEYBOARD_F23 = 0x72, "Keyboard F23";
+        KEYBOARD_F24 = 0x73, "Keyboard F24";
+        KEYBOARD_EXECUTE = 0x74, "Keyboard Execute";
+        KEYBOARD_HELP = 0x75, "Keyboard Help";
+        KEYBOARD_MENU = 0x76, "Keyboard Menu";
+        KEYBOARD_SELECT = 0x77, "Keyboard Select";
+        KEYBOARD_STOP = 0x78, "Keyboard Stop";
+        KEYBOARD_AGAIN = 0x79, "Keyboard Again";
+        KEYBOARD_UNDO = 0x7A, "Keyboard Undo";
+        KEYBOARD_CUT = 0x7B, "Keyboard Cut";
+        KEYBOARD_COPY = 0x7C, "Keyboard Copy";
+        KEYBOARD_PASTE = 0x7D, "Keyboard Paste";
+        KEYBOARD_FIND = 0x7E, "Keyboard Find";
+        KEYBOARD_MUTE = 0x7F, "Keyboard Mute";
+        KEYBOARD_VOLUME_UP = 0x80, "Keyboard Volume Up";
+        KEYBOARD_VOLUME_DOWN = 0x81, "Keyboard Volume Down";
+        KEYBOARD_LEFT_CONTROL = 0xE0, "Keyboard LeftControl";
+        KEYBOARD_LEFT_SHIFT = 0xE1, "Keyboard LeftShift";
+        KEYBOARD_LEFT_ALT = 0xE2, "Keyboard LeftAlt";
+        KEYBOARD_LEFT_GUI = 0xE3, "Keyboard Left GUI";
+        KEYBOARD_RIGHT_CONTROL = 0xE4, "Keyboard RightControl";
+        KEYBOARD_RIGHT_SHIFT = 0xE5, "Keyboard RightShift";
+        KEYBOARD_RIGHT_ALT = 0xE6, "Keyboard RightAlt";
+        KEYBOARD_RIGHT_GUI = 0xE7, "Keyboard Right GUI";
+    }
+    /// LED Page (0x08)
+    led = 0x08, "LED" {
+        NUM_LOCK = 0x01, "Num Lock";
+        CAPS_LOCK = 0x02, "Caps Lock";
+        SCROLL_LOCK = 0x03, "Scroll Lock";
+        COMPOSE = 0x04, "Compose";
+        KANA = 0x05, "Kana";
+        POWER = 0x06, "Power";
+        SHIFT = 0x07, "Shift";
+        DO_NOT_DISTURB = 0x08, "Do Not Disturb";
+        MUTE = 0x09, "Mute";
+        TONE_ENABLE = 0x0A, "Tone Enable";
+        HIGH_CUT_FILTER = 0x0B, "High Cut Filter";
+        LOW_CUT_FILTER = 0x0C, "Low Cut Filter";
+        EQUALIZER_ENABLE = 0x0D, "Equalizer Enable";
+        SOUND_FIELD_ON = 0x0E, "Sound Field On";
+        SURROUND_ON = 0x0F, "Surround On";
+        REPEAT = 0x10, "Repeat";
+        STEREO = 0x11, "Stereo";
+        SAMPLING_RATE_DETECT = 0x12, "Sampling Rate Detect";
+        SPINNING = 0x13, "Spinning";
+        CAV = 0x14, "CAV";
+        CLV = 0x15, "CLV";
+        RECORDING_FORMAT_DETECT = 0x16, "Recording Format Detect";
+        OFF_HOOK = 0x17, "Off-Hook";
+        RING = 0x18, "Ring";
+        MESSAGE_WAITING = 0x19, "Message Waiting";
+        DATA_MODE = 0x1A, "Data Mode";
+        BATTERY_OPERATION = 0x1B, "Battery Operation";
+        BATTERY_OK = 0x1C, "Battery OK";
+        BATTERY_LOW = 0x1D, "Battery Low";
+        SPEAKER = 0x1E, "Speaker";
+        HEADSET = 0x1F, "Headset";
+        HOLD = 0x20, "Hold";
+        MICROPHONE = 0x21, "Microphone";
+        COVERAGE = 0x22, "Coverage";
+        NIGHT_MODE = 0x23, "Night Mode";
+        SEND_CALLS = 0x24, "Send Calls";
+        CALL_PICKUP = 0x25, "Call Pickup";
+        CONFERENCE = 0x26, "Conference";
+        STAND_BY = 0x27, "Stand-by";
+        CAMERA_ON = 0x28, "Camera On";
+        CAMERA_OFF = 0x29, "Camera Off";
+        ON_LINE = 0x2A, "On-Line";
+        OFF_LINE = 0x2B, "Off-Line";
+        BUSY = 0x2C, "Busy";
+        READY = 0x2D, "Ready";
+        PAPER_OUT = 0x2E, "Paper-Out";
+        PAPER_JAM = 0x2F, "Paper-Jam";
+        REMOTE = 0x30, "Remote";
+        FORWARD = 0x31, "Forward";
+        REVERSE = 0x32, "Reverse";
+        STOP = 0x33, "Stop";
+        REWIND = 0x34, "Rewind";
+        FAST_FORWARD = 0x35, "Fast Forward";
+        PLAY = 0x36, "Play";
+        PAUSE = 0x37, "Pause";
+        RECORD = 0x38, "Record";
+        ERROR = 0x39, "Error";
+        USAGE_SELECTED_INDICATOR = 0x3A, "Usage Selected Indicator";
+        USAGE_IN_USE_INDICATOR = 0x3B, "Usage In Use Indicator";
+        USAGE_MULTI_MODE_INDICATOR = 0x3C, "Usage Multi Mode Indicator";
+        INDICATOR_ON = 0x3D, "Indicator On";
+        INDICATOR_FLASH = 0x3E, "Indicator Flash";
+        INDICATOR_SLOW_BLINK = 0x3F, "Indicator Slow Blink";
+        INDICATOR_FAST_BLINK = 0x40, "Indicator Fast Blink";
+        INDICATOR_OFF = 0x41, "Indicator Off";
+        FLASH_ON_TIME = 0x42, "Flash On Time";
+        SLOW_BLINK_ON_TIME = 0x43, "Slow Blink On Time";
+        SLOW_BLINK_OFF_TIME = 0x44, "Slow Blink Off Time";
+        FAST_BLINK_ON_TIME = 0x45, "Fast Blink On Time";
+        FAST_BLINK_OFF_TIME = 0x46, "Fast Blink Off Time";
+        USAGE_INDICATOR_COLOR = 0x47, "Usage Indicator Color";
+        INDICATOR_RED = 0x48, "Indicator Red";
+        INDICATOR_GREEN = 0x49, "Indicator Green";
+        INDICATOR_AMBER = 0x4A, "Indicator Amber";
+        GENERIC_INDICATOR = 0x4B, "Generic Indicator";
+        SYSTEM_SUSPEND = 0x4C, "System Suspend";
+        EXTERNAL_POWER_CONNECTED = 0x4D, "External Power Connected";
+    }
+    /// Button Page (0x09)
+///
+/// Usage `n` is named `Button n`.
+    button = 0x09, "Button" {
+        NO_BUTTON_PRESSED = 0x00, "No Button Pressed";
+    }
+    /// Ordinal Page (0x0A)
+///
+/// Usage `n` is named `Instance n`.
+    ordinal = 0x0A, "Ordinal" {}
+    /// Telephony Device Page (0x0B)
+    telephony = 0x0B, "Telephony Device" {}
+    /// Consumer Page (0x0C)
+    consumer = 0x0C, "Consumer" {
+        CONSUMER_CONTROL = 0x01, "Consumer Control";
+        NUMERIC_KEY_PAD = 0x02, "Numeric Key Pad";
+        PROGRAMMABLE_BUTTONS = 0x03, "Programmable Buttons";
+        MICROPHONE = 0x04, "Microphone";
+        HEADPHONE = 0x05, "Headphone";
+        GRAPHIC_EQUALIZER = 0x06, "Graphic Equalizer";
+        PLUS_10 = 0x20, "+10";
+        PLUS_100 = 0x21, "+100";
+        AM_PM = 0x22, "AM/PM";
+        POWER = 0x30, "Power";
+        RESET = 0x31, "Reset";
+        SLEEP = 0x32, "Sleep";
+        SLEEP_AFTER = 0x33, "Sleep After";
+        SLEEP_MODE = 0x34, "Sleep Mode";
+        ILLUMINATION = 0x35, "Illumination";
+        FUNCTION_BUTTONS = 0x36, "Function Buttons";
+        MENU = 0x40, "Menu";
+        MENU_PICK = 0x41, "Menu Pick";
+        MENU_UP = 0x42, "Menu Up";
+        MENU_DOWN = 0x43, "Menu Down";
+        MENU_LEFT = 0x44, "Menu Left";
+        MENU_RIGHT = 0x45, "Menu Right";
+        MENU_ESCAPE = 0x46, "Menu Escape";
+        MENU_VALUE_INCREASE = 0x47, "Menu Value Increase";
+        MENU_VALUE_DECREASE = 0x48, "Menu Value Decrease";
+        DATA_ON_SCREEN = 0x60, "Data On Screen";
+        CLOSED_CAPTION = 0x61, "Closed Caption";
+        CLOSED_CAPTION_SELECT = 0x62, "Closed Caption Select";
+        VCR_TV = 0x63, "VCR/TV";
+        BROADCAST_MODE = 0x64, "Broadcast Mode";
+        SNAPSHOT = 0x65, "Snapshot";
+        STILL = 0x66, "Still";
+        DISPLAY_BRIGHTNESS_INCREMENT = 0x6F, "Display Brightness Increment";
+        DISPLAY_BRIGHTNESS_DECREMENT = 0x70, "Display Brightness Decrement";
+        PLAY = 0xB0, "Play";
+        PAUSE = 0xB1, "Pause";
+        RECORD = 0xB2, "Record";
+        FAST_FORWARD = 0xB3, "Fast Forward";
+        REWIND = 0xB4, "Rewind";
+        SCAN_NEXT_TRACK = 0xB5, "Scan Next Track";
+        SCAN_PREVIOUS_TRACK = 0xB6, "Scan Previous Track";
+        STOP = 0xB7, "Stop";
+        EJECT = 0xB8, "Eject";
+        RANDOM_PLAY = 0xB9, "Random Play";
+        PLAY_PAUSE = 0xCD, "Play/Pause";
+        VOLUME = 0xE0, "Volume";
+        BALANCE = 0xE1, "Balance";
+        MUTE = 0xE2, "Mute";
+        BASS = 0xE3, "Bass";
+        TREBLE = 0xE4, "Treble";
+        BASS_BOOST = 0xE5, "Bass Boost";
+        VOLUME_INCREMENT = 0xE9, "Volume Increment";
+        VOLUME_DECREMENT = 0xEA, "Volume Decrement";
+        AL_CONSUMER_CONTROL_CONFIGURATION = 0x183, "AL Consumer Control Configuration";
+        AL_EMAIL_READER = 0x18A, "AL Email Reader";
+        AL_CALCULATOR = 0x192, "AL Calculator";
+        AL_LOCAL_MACHINE_BROWSER = 0x194, "AL Local Machine Browser";
+        AC_NEW = 0x201, "AC New";
+        AC_OPEN = 0x202, "AC Open";
+        AC_CLOSE = 0x203, "AC Close";
+        AC_EXIT = 0x204, "AC Exit";
+        AC_SAVE = 0x207, "AC Save";
+        AC_PRINT = 0x208, "AC Print";
+        AC_UNDO = 0x21A, "AC Undo";
+        AC_COPY = 0x21B, "AC Copy";
+        AC_CUT = 0x21C, "AC Cut";
+        AC_PASTE = 0x21D, "AC Paste";
+        AC_FIND = 0x21F, "AC Find";
+        AC_SEARCH = 0x221, "AC Search";
+        AC_HOME = 0x223, "AC Home";
+        AC_BACK = 0x224, "AC Back";
+        AC_FORWARD = 0x225, "AC Forward";
+        AC_STOP = 0x226, "AC Stop";
+        AC_REFRESH = 0x227, "AC Refresh";
+        AC_BOOKMARKS = 0x22A, "AC Bookmarks";
+        AC_ZOOM_IN = 0x22D, "AC Zoom In";
+        AC_ZOOM_OUT = 0x22E, "AC Zoom Out";
+        AC_ZOOM = 0x22F, "AC Zoom";
+        AC_PAN = 0x238, "AC Pan";
+    }
+    /// Digitizers Page (0x0D)
+    digitizers = 0x0D, "Digitizers" {
+        DIGITIZER = 0x01, "Digitizer";
+        PEN = 0x02, "Pen";
+        LIGHT_PEN = 0x03, "Light Pen";
+        TOUCH_SCREEN = 0x04, "Touch Screen";
+        TOUCH_PAD = 0x05, "Touch Pad";
+        WHITEBOARD = 0x06, "Whiteboard";
+        COORDINATE_MEASURING_MACHINE = 0x07, "Coordinate Measuring Machine";
+        DIGITIZER_3D = 0x08, "3D Digitizer";
+        STEREO_PLOTTER = 0x09, "Stereo Plotter";
+        ARTICULATED_ARM = 0x0A, "Articulated Arm";
+        ARMATURE = 0x0B, "Armature";
+        MULTIPLE_POINT_DIGITIZER = 0x0C, "Multiple Point Digitizer";
+        FREE_SPACE_WAND = 0x0D, "Free Space Wand";
+        DEVICE_CONFIGURATION = 0x0E, "Device Configuration";
+        CAPACITIVE_HEAT_MAP_DIGITIZER = 0x0F, "Capacitive Heat Map Digitizer";
+        STYLUS = 0x20, "Stylus";
+        PUCK = 0x21, "Puck";
+        FINGER = 0x22, "Finger";
+        DEVICE_SETTINGS = 0x23, "Device Settings";
+        CHARACTER_GESTURE = 0x24, "Character Gesture";
+        TIP_PRESSURE = 0x30, "Tip Pressure";
+        BARREL_PRESSURE = 0x31, "Barrel Pressure";
+        IN_RANGE = 0x32, "In Range";
+        TOUCH = 0x33, "Touch";
+        UNTOUCH = 0x34, "Untouch";
+        TAP = 0x35, "Tap";
+        QUALITY = 0x36, "Quality";
+        DATA_VALID = 0x37, "Data Valid";
+        TRANSDUCER_INDEX = 0x38, "Transducer Index";
+        TABLET_FUNCTION_KEYS = 0x39, "Tablet Function Keys";
+        PROGRAM_CHANGE_KEYS = 0x3A, "Program Change Keys";
+        BATTERY_STRENGTH = 0x3B, "Battery Strength";
+        INVERT = 0x3C, "Invert";
+        X_TILT = 0x3D, "X Tilt";
+        Y_TILT = 0x3E, "Y Tilt";
+        AZIMUTH = 0x3F, "Azimuth";
+        ALTITUDE = 0x40, "Altitude";
+        TWIST = 0x41, "Twist";
+        TIP_SWITCH = 0x42, "Tip Switch";
+        SECONDARY_TIP_SWITCH = 0x43, "Secondary Tip Switch";
+        BARREL_SWITCH = 0x44, "Barrel Switch";
+        ERASER = 0x45, "Eraser";
+        TABLET_PICK = 0x46, "Tablet Pick";
+        TOUCH_VALID = 0x47, "Touch Valid";
+        WIDTH = 0x48, "Width";
+        HEIGHT = 0x49, "Height";
+        CONTACT_IDENTIFIER = 0x51, "Contact Identifier";
+        DEVICE_MODE = 0x52, "Device Mode";
+        DEVICE_IDENTIFIER = 0x53, "Device Identifier";
+        CONTACT_COUNT = 0x54, "Contact Count";
+        CONTACT_COUNT_MAXIMUM = 0x55, "Contact Count Maximum";
+        SCAN_TIME = 0x56, "Scan Time";
+        SURFACE_SWITCH = 0x57, "Surface Switch";
+        BUTTON_SWITCH = 0x58, "Button Switch";
+        PAD_TYPE = 0x59, "Pad Type";
+        SECONDARY_BARREL_SWITCH = 0x5A, "Secondary Barrel Switch";
+        TRANSDUCER_SERIAL_NUMBER = 0x5B, "Transducer Serial Number";
+        PREFERRED_COLOR = 0x5C, "Preferred Color";
+        PREFERRED_COLOR_IS_LOCKED = 0x5D, "Preferred Color is Locked";
+        PREFERRED_LINE_WIDTH = 0x5E, "Preferred Line Width";
+        PREFERRED_LINE_WIDTH_IS_LOCKED = 0x5F, "Preferred Line Width is Locked";
+        LATENCY_MODE = 0x60, "Latency Mode";
+    }
+    /// Haptics Page (0x0E)
+    haptics = 0x0E, "Haptics" {}
+    /// Physical Input Device Page (0x0F)
+    physical_input_device = 0x0F, "Physical Input Device" {}
+    /// Unicode Page (0x10)
+    unicode = 0x10, "Unicode" {}
+    /// Eye and Head Trackers Page (0x12)
+    eye_and_head_trackers = 0x12, "Eye and Head Trackers" {}
+    /// Auxiliary Display Page (0x14)
+    auxiliary_display = 0x14, "Auxiliary Display" {}
+    /// Sensors Page (0x20)
+    sensors = 0x20, "Sensors" {
+        SENSOR = 0x01, "Sensor";
+        BIOMETRIC = 0x10, "Biometric";
+        BIOMETRIC_HUMAN_PRESENCE = 0x11, "Biometric: Human Presence";
+        BIOMETRIC_HUMAN_PROXIMITY = 0x12, "Biometric: Human Proximity";
+        BIOMETRIC_HUMAN_TOUCH = 0x13, "Biometric: Human Touch";
+        ELECTRICAL = 0x20, "Electrical";
+        ENVIRONMENTAL = 0x30, "Environmental";
+        ENVIRONMENTAL_ATMOSPHERIC_PRESSURE = 0x31, "Environmental: Atmospheric Pressure";
+        ENVIRONMENTAL_HUMIDITY = 0x32, "Environmental: Humidity";
+        ENVIRONMENTAL_TEMPERATURE = 0x33, "Environmental: Temperature";
+        ENVIRONMENTAL_WIND_DIRECTION = 0x34, "Environmental: Wind Direction";
+        ENVIRONMENTAL_WIND_SPEED = 0x35, "Environmental: Wind Speed";
+        LIGHT = 0x40, "Light";
+        LIGHT_AMBIENT_LIGHT = 0x41, "Light: Ambient Light";
+        LIGHT_CONSUMER_INFRARED = 0x42, "Light: Consumer Infrared";
+        LOCATION = 0x50, "Location";
+        MECHANICAL = 0x60, "Mechanical";
+        MOTION = 0x70, "Motion";
+        MOTION_ACCELEROMETER_1D = 0x71, "Motion: Accelerometer 1D";
+        MOTION_ACCELEROMETER_2D = 0x72, "Motion: Accelerometer 2D";
+        MOTION_ACCELEROMETER_3D = 0x73, "Motion: Accelerometer 3D";
+        MOTION_GYROMETER_1D = 0x74, "Motion: Gyrometer 1D";
+        MOTION_GYROMETER_2D = 0x75, "Motion: Gyrometer 2D";
+        MOTION_GYROMETER_3D = 0x76, "Motion: Gyrometer 3D";
+        MOTION_MOTION_DETECTOR = 0x77, "Motion: Motion Detector";
+        MOTION_SPEEDOMETER = 0x78, "Motion: Speedometer";
+        MOTION_ACCELEROMETER = 0x79, "Motion: Accelerometer";
+        MOTION_GYROMETER = 0x7A, "Motion: Gyrometer";
+        ORIENTATION = 0x80, "Orientation";
+        ORIENTATION_COMPASS_1D = 0x81, "Orientation: Compass 1D";
+        ORIENTATION_COMPASS_2D = 0x82, "Orientation: Compass 2D";
+        ORIENTATION_COMPASS_3D = 0x83, "Orientation: Compass 3D";
+        ORIENTATION_INCLINOMETER_1D = 0x84, "Orientation: Inclinometer 1D";
+        ORIENTATION_INCLINOMETER_2D = 0x85, "Orientation: Inclinometer 2D";
+        ORIENTATION_INCLINOMETER_3D = 0x86, "Orientation: Inclinometer 3D";
+        ORIENTATION_DISTANCE_1D = 0x87, "Orientation: Distance 1D";
+        ORIENTATION_DISTANCE_2D = 0x88, "Orientation: Distance 2D";
+        ORIENTATION_DISTANCE_3D = 0x89, "Orientation: Distance 3D";
+        ORIENTATION_DEVICE_ORIENTATION = 0x8A, "Orientation: Device Orientation";
+        SCANNER = 0x90, "Scanner";
+        TIME = 0xA0, "Time";
+        PERSONAL_ACTIVITY = 0xB0, "Personal Activity";
+        ORIENTATION_EXTENDED = 0xC0, "Orientation Extended";
+        OTHER = 0xE0, "Other";
+        OTHER_CUSTOM = 0xE1, "Other: Custom";
+        OTHER_GENERIC = 0xE2, "Other: Generic";
+        EVENT = 0x200, "Event";
+        EVENT_SENSOR_STATE = 0x201, "Event: Sensor State";
+        EVENT_SENSOR_EVENT = 0x202, "Event: Sensor Event";
+        PROPERTY = 0x300, "Property";
+        PROPERTY_FRIENDLY_NAME = 0x301, "Property: Friendly Name";
+        PROPERTY_PERSISTENT_UNIQUE_ID = 0x302, "Property: Persistent Unique ID";
+        PROPERTY_SENSOR_STATUS = 0x303, "Property: Sensor Status";
+        PROPERTY_MINIMUM_REPORT_INTERVAL = 0x304, "Property: Minimum Report Interval";
+        PROPERTY_SENSOR_MANUFACTURER = 0x305, "Property: Sensor Manufacturer";
+        PROPERTY_SENSOR_MODEL = 0x306, "Property: Sensor Model";
+        PROPERTY_SENSOR_SERIAL_NUMBER = 0x307, "Property: Sensor Serial Number";
+        PROPERTY_SENSOR_DESCRIPTION = 0x308, "Property: Sensor Description";
+        PROPERTY_SENSOR_CONNECTION_TYPE = 0x309, "Property: Sensor Connection Type";
+        PROPERTY_SENSOR_DEVICE_PATH = 0x30A, "Property: Sensor Device Path";
+        PROPERTY_HARDWARE_REVISION = 0x30B, "Property: Hardware Revision";
+        PROPERTY_FIRMWARE_VERSION = 0x30C, "Property: Firmware Version";
+        PROPERTY_RELEASE_DATE = 0x30D, "Property: Release Date";
+        PROPERTY_REPORT_INTERVAL = 0x30E, "Property: Report Interval";
+    }
+    /// Medical Instrument Page (0x40)
+    medical_instrument = 0x40, "Medical Instrument" {}
+    /// Braille Display Page (0x41)
+    braille_display = 0x41, "Braille Display" {}
+    /// Lighting And Illumination Page (0x59), which includes LampArray
+    lighting_and_illumination = 0x59, "Lighting And Illumination" {
+        LAMP_ARRAY = 0x01, "LampArray";
+        LAMP_ARRAY_ATTRIBUTES_REPORT = 0x02, "LampArrayAttributesReport";
+        LAMP_COUNT = 0x03, "LampCount";
+        BOUNDING_BOX_WIDTH_IN_MICROMETERS = 0x04, "BoundingBoxWidthInMicrometers";
+        BOUNDING_BOX_HEIGHT_IN_MICROMETERS = 0x05, "BoundingBoxHeightInMicrometers";
+        BOUNDING_BOX_DEPTH_IN_MICROMETERS = 0x06, "BoundingBoxDepthInMicrometers";
+        LAMP_ARRAY_KIND = 0x07, "LampArrayKind";
+        MIN_UPDATE_INTERVAL_IN_MICROSECONDS = 0x08, "MinUpdateIntervalInMicroseconds";
+        LAMP_ATTRIBUTES_REQUEST_REPORT = 0x20, "LampAttributesRequestReport";
+        LAMP_ID = 0x21, "LampId";
+        LAMP_ATTRIBUTES_RESPONSE_REPORT = 0x22, "LampAttributesResponseReport";
+        POSITION_XIN_MICROMETERS = 0x23, "PositionXInMicrometers";
+        POSITION_YIN_MICROMETERS = 0x24, "PositionYInMicrometers";
+        POSITION_ZIN_MICROMETERS = 0x25, "PositionZInMicrometers";
+        LAMP_PURPOSES = 0x26, "LampPurposes";
+        UPDATE_LATENCY_IN_MICROSECONDS = 0x27, "UpdateLatencyInMicroseconds";
+        RED_LEVEL_COUNT = 0x28, "RedLevelCount";
+        GREEN_LEVEL_COUNT = 0x29, "GreenLevelCount";
+        BLUE_LEVEL_COUNT = 0x2A, "BlueLevelCount";
+        INTENSITY_LEVEL_COUNT = 0x2B, "IntensityLevelCount";
+        IS_PROGRAMMABLE = 0x2C, "IsProgrammable";
+        INPUT_BINDING = 0x2D, "InputBinding";
+        LAMP_MULTI_UPDATE_REPORT = 0x50, "LampMultiUpdateReport";
+        RED_UPDATE_CHANNEL = 0x51, "RedUpdateChannel";
+        GREEN_UPDATE_CHANNEL = 0x52, "GreenUpdateChannel";
+        BLUE_UPDATE_CHANNEL = 0x53, "BlueUpdateChannel";
+        INTENSITY_UPDATE_CHANNEL = 0x54, "IntensityUpdateChannel";
+        LAMP_UPDATE_FLAGS = 0x55, "LampUpdateFlags";
+        LAMP_RANGE_UPDATE_REPORT = 0x60, "LampRangeUpdateReport";
+        LAMP_ID_START = 0x61, "LampIdStart";
+        LAMP_ID_END = 0x62, "LampIdEnd";
+        LAMP_ARRAY_CONTROL_REPORT = 0x70, "LampArrayControlReport";
+        AUTONOMOUS_MODE = 0x71, "AutonomousMode";
+    }
+    /// Monitor Page (0x80)
+    monitor = 0x80, "Monitor" {}
+    /// Power Page (0x84)
+    power = 0x84, "Power" {
+        I_NAME = 0x01, "iName";
+        PRESENT_STATUS = 0x02, "Present Status";
+        CHANGED_STATUS = 0x03, "Changed Status";
+        UPS = 0x04, "UPS";
+        POWER_SUPPLY = 0x05, "Power Supply";
+        BATTERY_SYSTEM = 0x10, "Battery System";
+        BATTERY_SYSTEM_ID = 0x11, "Battery System Id";
+        BATTERY = 0x12, "Battery";
+        BATTERY_ID = 0x13, "Battery Id";
+        CHARGER = 0x14, "Charger";
+        CHARGER_ID = 0x15, "Charger Id";
+        POWER_CONVERTER = 0x16, "Power Converter";
+        POWER_CONVERTER_ID = 0x17, "Power Converter Id";
+        OUTLET_SYSTEM = 0x18, "Outlet System";
+        OUTLET_SYSTEM_ID = 0x19, "Outlet System Id";
+        INPUT = 0x1A, "Input";
+        INPUT_ID = 0x1B, "Input Id";
+        OUTPUT = 0x1C, "Output";
+        OUTPUT_ID = 0x1D, "Output Id";
+        FLOW = 0x1E, "Flow";
+        FLOW_ID = 0x1F, "Flow Id";
+        OUTLET = 0x20, "Outlet";
+        OUTLET_ID = 0x21, "Outlet Id";
+        GANG = 0x22, "Gang";
+        GANG_ID = 0x23, "Gang Id";
+        POWER_SUMMARY = 0x24, "Power Summary";
+        POWER_SUMMARY_ID = 0x25, "Power Summary Id";
+        VOLTAGE = 0x30, "Voltage";
+        CURRENT = 0x31, "Current";
+        FREQUENCY = 0x32, "Frequency";
+        APPARENT_POWER = 0x33, "Apparent Power";
+        ACTIVE_POWER = 0x34, "Active Power";
+        PERCENT_LOAD = 0x35, "Percent Load";
+        TEMPERATURE = 0x36, "Temperature";
+        HUMIDITY = 0x37, "Humidity";
+        BAD_COUNT = 0x38, "Bad Count";
+        CONFIG_VOLTAGE = 0x40, "Config Voltage";
+        CONFIG_CURRENT = 0x41, "Config Current";
+        CONFIG_FREQUENCY = 0x42, "Config Frequency";
+        CONFIG_APPARENT_POWER = 0x43, "Config Apparent Power";
+        CONFIG_ACTIVE_POWER = 0x44, "Config Active Power";
+        CONFIG_PERCENT_LOAD = 0x45, "Config Percent Load";
+        CONFIG_TEMPERATURE = 0x46, "Config Temperature";
+        CONFIG_HUMIDITY = 0x47, "Config Humidity";
+        SWITCH_ON_CONTROL = 0x50, "Switch On Control";
+        SWITCH_OFF_CONTROL = 0x51, "Switch Off Control";
+        TOGGLE_CONTROL = 0x52, "Toggle Control";
+        LOW_VOLTAGE_TRANSFER = 0x53, "Low Voltage Transfer";
+        HIGH_VOLTAGE_TRANSFER = 0x54, "High Voltage Transfer";
+        DELAY_BEFORE_REBOOT = 0x55, "Delay Before Reboot";
+        DELAY_BEFORE_STARTUP = 0x56, "Delay Before Startup";
+        DELAY_BEFORE_SHUTDOWN = 0x57, "Delay Before Shutdown";
+        TEST = 0x58, "Test";
+        MODULE_RESET = 0x59, "Module Reset";
+        AUDIBLE_ALARM_CONTROL = 0x5A, "Audible Alarm Control";
+        PRESENT = 0x60, "Present";
+        GOOD = 0x61, "Good";
+        INTERNAL_FAILURE = 0x62, "Internal Failure";
+        VOLTAGE_OUT_OF_RANGE = 0x63, "Voltage Out Of Range";
+        FREQUENCY_OUT_OF_RANGE = 0x64, "Frequency Out Of Range";
+        OVERLOAD = 0x65, "Overload";
+        OVER_CHARGED = 0x66, "Over Charged";
+        OVER_TEMPERATURE = 0x67, "Over Temperature";
+        SHUTDOWN_REQUESTED = 0x68, "Shutdown Requested";
+        SHUTDOWN_IMMINENT = 0x69, "Shutdown Imminent";
+        SWITCH_ON_OFF = 0x6B, "Switch On/Off";
+        SWITCHABLE = 0x6C, "Switchable";
+        USED = 0x6D, "Used";
+        BOOST = 0x6E, "Boost";
+        BUCK = 0x6F, "Buck";
+        INITIALIZED = 0x70, "Initialized";
+        TESTED = 0x71, "Tested";
+        AWAITING_POWER = 0x72, "Awaiting Power";
+        COMMUNICATION_LOST = 0x73, "Communication Lost";
+        I_MANUFACTURER = 0xFD, "iManufacturer";
+        I_PRODUCT = 0xFE, "iProduct";
+        I_SERIAL_NUMBER = 0xFF, "iSerialNumber";
+    }
+    /// Battery System Page (0x85)
+    battery_system = 0x85, "Battery System" {
+        SMART_BATTERY_BATTERY_MODE = 0x01, "Smart Battery Battery Mode";
+        SMART_BATTERY_BATTERY_STATUS = 0x02, "Smart Battery Battery Status";
+        SMART_BATTERY_ALARM_WARNING = 0x03, "Smart Battery Alarm Warning";
+        SMART_BATTERY_CHARGER_MODE = 0x04, "Smart Battery Charger Mode";
+        SMART_BATTERY_CHARGER_STATUS = 0x05, "Smart Battery Charger Status";
+        SMART_BATTERY_CHARGER_SPEC_INFO = 0x06, "Smart Battery Charger Spec Info";
+        SMART_BATTERY_SELECTOR_STATE = 0x07, "Smart Battery Selector State";
+        SMART_BATTERY_SELECTOR_PRESETS = 0x08, "Smart Battery Selector Presets";
+        SMART_BATTERY_SELECTOR_INFO = 0x09, "Smart Battery Selector Info";
+        OPTIONAL_MFG_FUNCTION_1 = 0x10, "Optional Mfg Function 1";
+        OPTIONAL_MFG_FUNCTION_2 = 0x11, "Optional Mfg Function 2";
+        OPTIONAL_MFG_FUNCTION_3 = 0x12, "Optional Mfg Function 3";
+        OPTIONAL_MFG_FUNCTION_4 = 0x13, "Optional Mfg Function 4";
+        OPTIONAL_MFG_FUNCTION_5 = 0x14, "Optional Mfg Function 5";
+        CONNECTION_TO_SM_BUS = 0x15, "Connection To SM Bus";
+        OUTPUT_CONNECTION = 0x16, "Output Connection";
+        CHARGER_CONNECTION = 0x17, "Charger Connection";
+        BATTERY_INSERTION = 0x18, "Battery Insertion";
+        USE_NEXT = 0x19, "Use Next";
+        OK_TO_USE = 0x1A, "OK To Use";
+        BATTERY_SUPPORTED = 0x1B, "Battery Supported";
+        SELECTOR_REVISION = 0x1C, "Selector Revision";
+        CHARGING_INDICATOR = 0x1D, "Charging Indicator";
+        MANUFACTURER_ACCESS = 0x28, "Manufacturer Access";
+        REMAINING_CAPACITY_LIMIT = 0x29, "Remaining Capacity Limit";
+        REMAINING_TIME_LIMIT = 0x2A, "Remaining Time Limit";
+        AT_RATE = 0x2B, "At Rate";
+        CAPACITY_MODE = 0x2C, "Capacity Mode";
+        BROADCAST_TO_CHARGER = 0x2D, "Broadcast To Charger";
+        PRIMARY_BATTERY = 0x2E, "Primary Battery";
+        CHARGE_CONTROLLER = 0x2F, "Charge Controller";
+        TERMINATE_CHARGE = 0x40, "Terminate Charge";
+        TERMINATE_DISCHARGE = 0x41, "Terminate Discharge";
+        BELOW_REMAINING_CAPACITY_LIMIT = 0x42, "Below Remaining Capacity Limit";
+        REMAINING_TIME_LIMIT_EXPIRED = 0x43, "Remaining Time Limit Expired";
+        CHARGING = 0x44, "Charging";
+        DISCHARGING = 0x45, "Discharging";
+        FULLY_CHARGED = 0x46, "Fully Charged";
+        FULLY_DISCHARGED = 0x47, "Fully Discharged";
+        CONDITIONING_FLAG = 0x48, "Conditioning Flag";
+        AT_RATE_OK = 0x49, "At Rate OK";
+        SMART_BATTERY_ERROR_CODE = 0x4A, "Smart Battery Error Code";
+        NEED_REPLACEMENT = 0x4B, "Need Replacement";
+        AT_RATE_TIME_TO_FULL = 0x60, "At Rate Time To Full";
+        AT_RATE_TIME_TO_EMPTY = 0x61, "At Rate Time To Empty";
+        AVERAGE_CURRENT = 0x62, "Average Current";
+        MAX_ERROR = 0x63, "Max Error";
+        RELATIVE_STATE_OF_CHARGE = 0x64, "Relative State Of Charge";
+        ABSOLUTE_STATE_OF_CHARGE = 0x65, "Absolute State Of Charge";
+        REMAINING_CAPACITY = 0x66, "Remaining Capacity";
+        FULL_CHARGE_CAPACITY = 0x67, "Full Charge Capacity";
+        RUN_TIME_TO_EMPTY = 0x68, "Run Time To Empty";
+        AVERAGE_TIME_TO_EMPTY = 0x69, "Average Time To Empty";
+        AVERAGE_TIME_TO_FULL = 0x6A, "Average Time To Full";
+        CYCLE_COUNT = 0x6B, "Cycle Count";
+        BATTERY_PACK_MODEL_LEVEL = 0x80, "Battery Pack Model Level";
+        INTERNAL_CHARGE_CONTROLLER = 0x81, "Internal Charge Controller";
+        PRIMARY_BATTERY_SUPPORT = 0x82, "Primary Battery Support";
+        DESIGN_CAPACITY = 0x83, "Design Capacity";
+        SPECIFICATION_INFO = 0x84, "Specification Info";
+        MANUFACTURE_DATE = 0x85, "Manufacture Date";
+        SERIAL_NUMBER = 0x86, "Serial Number";
+        I_MANUFACTURER_NAME = 0x87, "iManufacturer Name";
+        I_DEVICE_NAME = 0x88, "iDevice Name";
+        I_DEVICE_CHEMISTRY = 0x89, "iDevice Chemistry";
+        MANUFACTURER_DATA = 0x8A, "Manufacturer Data";
+        RECHARGEABLE = 0x8B, "Rechargeable";
+        WARNING_CAPACITY_LIMIT = 0x8C, "Warning Capacity Limit";
+        CAPACITY_GRANULARITY_1 = 0x8D, "Capacity Granularity 1";
+        CAPACITY_GRANULARITY_2 = 0x8E, "Capacity Granularity 2";
+        I_OEM_INFORMATION = 0x8F, "iOEM Information";
+        INHIBIT_CHARGE = 0xC0, "Inhibit Charge";
+        ENABLE_POLLING = 0xC1, "Enable Polling";
+        RESET_TO_ZERO = 0xC2, "Reset To Zero";
+        AC_PRESENT = 0xD0, "AC Present";
+        BATTERY_PRESENT = 0xD1, "Battery Present";
+        POWER_FAIL = 0xD2, "Power Fail";
+        ALARM_INHIBITED = 0xD3, "Alarm Inhibited";
+        THERMISTOR_UNDER_RANGE = 0xD4, "Thermistor Under Range";
+        THERMISTOR_HOT = 0xD5, "Thermistor Hot";
+        THERMISTOR_COLD = 0xD6, "Thermistor Cold";
+        THERMISTOR_OVER_RANGE = 0xD7, "Thermistor Over Range";
+        VOLTAGE_OUT_OF_RANGE = 0xD8, "Voltage Out Of Range";
+        CURRENT_OUT_OF_RANGE = 0xD9, "Current Out Of Range";
+        CURRENT_NOT_REGULATED = 0xDA, "Current Not Regulated";
+        VOLTAGE_NOT_REGULATED = 0xDB, "Voltage Not Regulated";
+        MASTER_MODE = 0xDC, "Master Mode";
+        CHARGER_SELECTOR_SUPPORT = 0xF0, "Charger Selector Support";
+        CHARGER_SPEC = 0xF1, "Charger Spec";
+        LEVEL_2 = 0xF2, "Level 2";
+        LEVEL_3 = 0xF3, "Level 3";
+    }
+    /// Barcode Scanner Page (0x8C)
+    barcode_scanner = 0x8C, "Barcode Scanner" {}
+    /// Scales Page (0x8D)
+    scales = 0x8D, "Scales" {}
+    /// Magnetic Stripe Reader Page (0x8E)
+    magnetic_stripe_reader = 0x8E, "Magnetic Stripe Reader" {}
+    /// Camera Control Page (0x90)
+    camera_control = 0x90, "Camera Control" {}
+    /// Arcade Page (0x91)
+    arcade = 0x91, "Arcade" {}
+    /// FIDO Alliance Page (0xF1D0)
+    fido = 0xF1D0, "FIDO Alliance" {
+        U2F_AUTHENTICATOR_DEVICE = 0x01, "U2F Authenticator Device";
+        INPUT_REPORT_DATA = 0x20, "Input Report Data";
+        OUTPUT_REPORT_DATA = 0x21, "Output Report Data";
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_tables_sorted() {
+        assert!(PAGES.windows(2).all(|p| p[0].0 < p[1].0));
+        for (page, _, names) in PAGES {
+            assert!(names.windows(2).all(|n| n[0].0 < n[1].0), "page {page:#X}");
+            let unique = names
+                .iter()
+                .map(|(_, n)| n.to_ascii_lowercase())
+                .collect::<HashSet<_>>();
+            assert_eq!(unique.len(), names.len(), "page {page:#X}");
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(page_name(0x0C), Some("Consumer"));
+        assert_eq!(page_name(0xFF00), None);
+        assert_eq!(page_from_name("keyboard/keypad"), Some(keyboard::PAGE));
+        assert_eq!(page_from_name("Vendor"), None);
+
+        assert_eq!(name(consumer::AC_PAN).as_deref(), Some("AC Pan"));
+        assert_eq!(name(Usage::new(0x09, 3)).as_deref(), Some("Button 3"));
+        assert_eq!(name(Usage::new(0x0A, 2)).as_deref(), Some("Instance 2"));
+        assert_eq!(name(Usage::new(0x0A, 0)), None);
+        assert_eq!(name(Usage::new(0x01, 0x03)), None);
+        assert_eq!(name(Usage::new(0xFF00, 0x01)), None);
+
+        assert_eq!(
+            from_name(0x59, "LampArray"),
+            Some(lighting_and_illumination::LAMP_ARRAY)
+        );
+        assert_eq!(from_name(0x09, "button 12"), Some(Usage::new(0x09, 12)));
+        assert_eq!(from_name(0x09, "Button 0"), None);
+        assert_eq!(
+            from_name(0x09, "No Button Pressed"),
+            Some(button::NO_BUTTON_PRESSED)
+        );
+        assert_eq!(from_name(0x07, "Button 1"), None);
+        assert_eq!(from_name(0xFF00, "X"), None);
+
+        for (page, _, names) in PAGES {
+            for (id, n) in names.iter() {
+                assert_eq!(from_name(*page, n), Some(Usage::new(*page, *id)));
+            }
+        }
+    }
+}
@@ -61,6 +61,7 @@ pub fn get_device_info(path: &U16Str, handle: &Handle) -> DeviceInfo {
         usage: caps.Usage,
         interface_number: -1,
         bus_type: BusType::Unknown,
+        physical_path: None,
     };
 
     // If this fails just ignore it. The data might be incomplete but at least there is something
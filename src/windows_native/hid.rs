@@ -1,7 +1,9 @@
+use crate::preparsed;
 use crate::windows_native::error::{check_boolean, WinError, WinResult};
 use crate::windows_native::types::Handle;
 use std::ffi::c_void;
 use std::mem::{size_of, zeroed};
+use std::slice;
 use windows_sys::core::GUID;
 use windows_sys::Win32::Devices::HumanInterfaceDevice::{
     HidD_FreePreparsedData, HidD_GetAttributes, HidD_GetHidGuid, HidD_GetPreparsedData,
@@ -51,6 +53,14 @@ impl PreparsedData {
         self.0 as _
     }
 
+    /// The blob, with its size taken from the header
+    pub fn as_bytes(&self) -> WinResult<&[u8]> {
+        let ptr = self.0 as *const u8;
+        let header = unsafe { slice::from_raw_parts(ptr, preparsed::HEADER_SIZE) };
+        let size = preparsed::preparsed_data_size(header).ok_or(WinError::InvalidPreparsedData)?;
+        Ok(unsafe { slice::from_raw_parts(ptr, size) })
+    }
+
     pub fn get_caps(&self) -> WinResult<HIDP_CAPS> {
         unsafe {
             let mut caps = zeroed();
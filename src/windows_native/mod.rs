@@ -1,14 +1,5 @@
 //! The implementation which uses the the raw win32 api to perform operations
 
-macro_rules! ensure {
-    ($cond:expr, $result:expr) => {
-        if !($cond) {
-            return $result;
-        }
-    };
-}
-
-mod descriptor;
 mod dev_node;
 mod device_info;
 mod error;
@@ -16,7 +7,6 @@ mod hid;
 mod interfaces;
 mod string;
 mod types;
-mod utils;
 
 use std::cell::{Cell, RefCell};
 use std::ptr::{null, null_mut};
@@ -25,6 +15,7 @@ use std::{
     fmt::{self, Debug},
 };
 
+use crate::preparsed::reconstruct_descriptor;
 use crate::windows_native::dev_node::DevNode;
 use crate::windows_native::device_info::get_device_info;
 use crate::windows_native::error::{check_boolean, Win32Error, WinError, WinResult};
@@ -32,10 +23,10 @@ use crate::windows_native::hid::{get_hid_attributes, PreparsedData};
 use crate::windows_native::interfaces::Interface;
 use crate::windows_native::string::{U16Str, U16String};
 use crate::windows_native::types::{Handle, Overlapped};
-use crate::{DeviceInfo, HidDeviceBackendBase, HidDeviceBackendWindows, HidError, HidResult};
+use crate::{DeviceBackend, DeviceInfo, HidError, HidResult};
 use windows_sys::core::GUID;
 use windows_sys::Win32::Devices::HumanInterfaceDevice::{
-    HidD_GetIndexedString, HidD_SetFeature, HidD_SetNumInputBuffers,
+    HidD_GetIndexedString, HidD_SetFeature, HidD_SetNumInputBuffers, HidD_SetOutputReport,
 };
 use windows_sys::Win32::Devices::Properties::{
     DEVPKEY_Device_ContainerId, DEVPKEY_Device_InstanceId,
@@ -120,13 +111,52 @@ impl AsyncState {
     }
 }
 
+impl HidDevice {
+    /// Get a Feature or Input report with a `GET_REPORT` ioctl
+    ///
+    /// Set the first byte of `buf` to the 'Report ID' of the report to be read.
+    /// Upon return, the first byte will still contain the Report ID, and the
+    /// report data will start in `buf[1]`.
+    fn get_report(&self, ioctl: u32, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), Err(HidError::InvalidZeroSizeData));
+        let mut state = self.feature_state.borrow_mut();
+        let mut bytes_returned = 0;
+
+        let res = unsafe {
+            ResetEvent(state.overlapped.event_handle());
+            DeviceIoControl(
+                self.device_handle.as_raw(),
+                ioctl,
+                buf.as_mut_ptr() as _,
+                buf.len() as u32,
+                buf.as_mut_ptr() as _,
+                buf.len() as u32,
+                &mut bytes_returned,
+                state.overlapped.as_raw(),
+            )
+        };
+        if res != TRUE {
+            let err = Win32Error::last();
+            ensure!(err == Win32Error::IoPending, Err(err.into()))
+        }
+
+        bytes_returned = state.overlapped.get_result(&self.device_handle, None)? as u32;
+
+        if buf[0] == 0x0 {
+            bytes_returned += 1;
+        }
+
+        Ok(bytes_returned as usize)
+    }
+}
+
 impl Debug for HidDevice {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HidDevice").finish()
     }
 }
 
-impl HidDeviceBackendBase for HidDevice {
+impl DeviceBackend for HidDevice {
     fn write(&self, data: &[u8]) -> HidResult<usize> {
         ensure!(!data.is_empty(), Err(HidError::InvalidZeroSizeData));
         let mut state = self.write_state.borrow_mut();
@@ -239,41 +269,33 @@ impl HidDeviceBackendBase for HidDevice {
         Ok(())
     }
 
-    /// Set the first byte of `buf` to the 'Report ID' of the report to be read.
-    /// Upon return, the first byte will still contain the Report ID, and the
-    /// report data will start in `buf[1]`.
     fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
         #[allow(clippy::identity_op, clippy::double_parens)]
         const IOCTL_HID_GET_FEATURE: u32 = ((0x0000000b) << 16) | ((0) << 14) | ((100) << 2) | (2);
-        ensure!(!buf.is_empty(), Err(HidError::InvalidZeroSizeData));
-        let mut state = self.feature_state.borrow_mut();
-        let mut bytes_returned = 0;
+        self.get_report(IOCTL_HID_GET_FEATURE, buf)
+    }
 
-        let res = unsafe {
-            ResetEvent(state.overlapped.event_handle());
-            DeviceIoControl(
-                self.device_handle.as_raw(),
-                IOCTL_HID_GET_FEATURE,
-                buf.as_mut_ptr() as _,
-                buf.len() as u32,
-                buf.as_mut_ptr() as _,
-                buf.len() as u32,
-                &mut bytes_returned,
-                state.overlapped.as_raw(),
-            )
-        };
-        if res != TRUE {
-            let err = Win32Error::last();
-            ensure!(err == Win32Error::IoPending, Err(err.into()))
-        }
+    fn get_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        #[allow(clippy::identity_op, clippy::double_parens)]
+        const IOCTL_HID_GET_INPUT_REPORT: u32 =
+            ((0x0000000b) << 16) | ((0) << 14) | ((104) << 2) | (2);
+        self.get_report(IOCTL_HID_GET_INPUT_REPORT, buf)
+    }
 
-        bytes_returned = state.overlapped.get_result(&self.device_handle, None)? as u32;
+    fn send_output_report(&self, data: &[u8]) -> HidResult<()> {
+        ensure!(!data.is_empty(), Err(HidError::InvalidZeroSizeData));
+        let mut state = self.write_state.borrow_mut();
+        state.fill_buffer(data);
 
-        if buf[0] == 0x0 {
-            bytes_returned += 1;
-        }
+        check_boolean(unsafe {
+            HidD_SetOutputReport(
+                self.device_handle.as_raw(),
+                state.buffer_ptr() as _,
+                state.buffer_len() as u32,
+            )
+        })?;
 
-        Ok(bytes_returned as usize)
+        Ok(())
     }
 
     fn set_blocking_mode(&self, blocking: bool) -> HidResult<()> {
@@ -312,14 +334,13 @@ impl HidDeviceBackendBase for HidDevice {
     }
 
     fn get_report_descriptor(&self, buf: &mut [u8]) -> HidResult<usize> {
-        let desc = descriptor::get_descriptor(&PreparsedData::load(&self.device_handle)?)?;
+        let pp_data = PreparsedData::load(&self.device_handle)?;
+        let desc = reconstruct_descriptor(pp_data.as_bytes()?)?;
         let size = buf.len().min(desc.len());
         buf[..size].copy_from_slice(&desc[..size]);
         Ok(size)
     }
-}
 
-impl HidDeviceBackendWindows for HidDevice {
     fn get_container_id(&self) -> HidResult<GUID> {
         let path =
             U16String::try_from(self.device_info.path()).expect("device path is not valid unicode");
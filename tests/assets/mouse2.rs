@@ -0,0 +1,551 @@
+// Generated by hidapi::codegen from a report descriptor, do not edit
+
+/// The report descriptor the types were generated from
+pub const REPORT_DESCRIPTOR: &[u8] = &[
+    0x05, 0x01, 0x09, 0x02, 0xA1, 0x01, 0x85, 0x01, 0x09, 0x01, 0xA1, 0x00, 0x05, 0x09, 0x19, 0x01,
+    0x29, 0x05, 0x15, 0x00, 0x25, 0x01, 0x95, 0x05, 0x75, 0x01, 0x81, 0x02, 0x95, 0x01, 0x75, 0x03,
+    0x81, 0x01, 0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x16, 0x01, 0xF8, 0x26, 0xFF, 0x07, 0x75, 0x10,
+    0x95, 0x02, 0x81, 0x06, 0x09, 0x38, 0x15, 0x81, 0x25, 0x7F, 0x75, 0x08, 0x95, 0x01, 0x81, 0x06,
+    0x05, 0x0C, 0x0A, 0x38, 0x02, 0x95, 0x01, 0x81, 0x06, 0xC0, 0xC0, 0x05, 0x01, 0x09, 0x80, 0xA1,
+    0x01, 0x85, 0x02, 0x05, 0x01, 0x19, 0x81, 0x29, 0x83, 0x15, 0x00, 0x25, 0x01, 0x95, 0x03, 0x75,
+    0x01, 0x81, 0x06, 0x95, 0x01, 0x75, 0x05, 0x81, 0x01, 0xC0, 0x05, 0x0C, 0x09, 0x01, 0xA1, 0x01,
+    0x85, 0x03, 0x15, 0x00, 0x26, 0x80, 0x03, 0x19, 0x00, 0x2A, 0x80, 0x03, 0x75, 0x10, 0x95, 0x01,
+    0x81, 0x00, 0xC0, 0x06, 0x00, 0xFF, 0x09, 0x0E, 0xA1, 0x01, 0x85, 0xBA, 0x95, 0x1F, 0x75, 0x08,
+    0x26, 0xFF, 0x00, 0x15, 0x00, 0x09, 0x01, 0x91, 0x02, 0x85, 0xBA, 0x95, 0x1F, 0x75, 0x08, 0x26,
+    0xFF, 0x00, 0x15, 0x00, 0x09, 0x01, 0x81, 0x02, 0xC0,
+];
+
+/// Input report 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ::hidapi::report::HidReport)]
+#[hid(input, id = 1, length = 7)]
+pub struct InputReport1 {
+    /// Button: Button 1
+    pub button_1: bool,
+    /// Button: Button 2
+    pub button_2: bool,
+    /// Button: Button 3
+    pub button_3: bool,
+    /// Button: Button 4
+    pub button_4: bool,
+    /// Button: Button 5
+    pub button_5: bool,
+    /// Generic Desktop: X, logical -2047..=2047
+    #[hid(offset = 8)]
+    pub x: i16,
+    /// Generic Desktop: Y, logical -2047..=2047
+    pub y: i16,
+    /// Generic Desktop: Wheel, logical -127..=127
+    pub wheel: i8,
+    /// Consumer: AC Pan, logical -127..=127
+    pub ac_pan: i8,
+}
+
+/// Input report 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ::hidapi::report::HidReport)]
+#[hid(input, id = 2, length = 1)]
+pub struct InputReport2 {
+    /// Generic Desktop: System Power Down
+    pub system_power_down: bool,
+    /// Generic Desktop: System Sleep
+    pub system_sleep: bool,
+    /// Generic Desktop: System Wake Up
+    pub system_wake_up: bool,
+}
+
+/// Input report 3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ::hidapi::report::HidReport)]
+#[hid(input, id = 3, length = 2)]
+pub struct InputReport3 {
+    /// Selected usages, see [`InputReport3Consumer`]
+    pub consumer: u16,
+}
+
+/// The usages of `InputReport3::consumer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputReport3Consumer {
+    /// Consumer Control
+    ConsumerControl,
+    /// Numeric Key Pad
+    NumericKeyPad,
+    /// Programmable Buttons
+    ProgrammableButtons,
+    /// Microphone
+    Microphone,
+    /// Headphone
+    Headphone,
+    /// Graphic Equalizer
+    GraphicEqualizer,
+    /// +10
+    Usage10,
+    /// +100
+    Usage100,
+    /// AM/PM
+    AmPm,
+    /// Power
+    Power,
+    /// Reset
+    Reset,
+    /// Sleep
+    Sleep,
+    /// Sleep After
+    SleepAfter,
+    /// Sleep Mode
+    SleepMode,
+    /// Illumination
+    Illumination,
+    /// Function Buttons
+    FunctionButtons,
+    /// Menu
+    Menu,
+    /// Menu Pick
+    MenuPick,
+    /// Menu Up
+    MenuUp,
+    /// Menu Down
+    MenuDown,
+    /// Menu Left
+    MenuLeft,
+    /// Menu Right
+    MenuRight,
+    /// Menu Escape
+    MenuEscape,
+    /// Menu Value Increase
+    MenuValueIncrease,
+    /// Menu Value Decrease
+    MenuValueDecrease,
+    /// Data On Screen
+    DataOnScreen,
+    /// Closed Caption
+    ClosedCaption,
+    /// Closed Caption Select
+    ClosedCaptionSelect,
+    /// VCR/TV
+    VcrTv,
+    /// Broadcast Mode
+    BroadcastMode,
+    /// Snapshot
+    Snapshot,
+    /// Still
+    Still,
+    /// Display Brightness Increment
+    DisplayBrightnessIncrement,
+    /// Display Brightness Decrement
+    DisplayBrightnessDecrement,
+    /// Play
+    Play,
+    /// Pause
+    Pause,
+    /// Record
+    Record,
+    /// Fast Forward
+    FastForward,
+    /// Rewind
+    Rewind,
+    /// Scan Next Track
+    ScanNextTrack,
+    /// Scan Previous Track
+    ScanPreviousTrack,
+    /// Stop
+    Stop,
+    /// Eject
+    Eject,
+    /// Random Play
+    RandomPlay,
+    /// Play/Pause
+    PlayPause,
+    /// Volume
+    Volume,
+    /// Balance
+    Balance,
+    /// Mute
+    Mute,
+    /// Bass
+    Bass,
+    /// Treble
+    Treble,
+    /// Bass Boost
+    BassBoost,
+    /// Volume Increment
+    VolumeIncrement,
+    /// Volume Decrement
+    VolumeDecrement,
+    /// AL Consumer Control Configuration
+    AlConsumerControlConfiguration,
+    /// AL Email Reader
+    AlEmailReader,
+    /// AL Calculator
+    AlCalculator,
+    /// AL Local Machine Browser
+    AlLocalMachineBrowser,
+    /// AC New
+    AcNew,
+    /// AC Open
+    AcOpen,
+    /// AC Close
+    AcClose,
+    /// AC Exit
+    AcExit,
+    /// AC Save
+    AcSave,
+    /// AC Print
+    AcPrint,
+    /// AC Undo
+    AcUndo,
+    /// AC Copy
+    AcCopy,
+    /// AC Cut
+    AcCut,
+    /// AC Paste
+    AcPaste,
+    /// AC Find
+    AcFind,
+    /// AC Search
+    AcSearch,
+    /// AC Home
+    AcHome,
+    /// AC Back
+    AcBack,
+    /// AC Forward
+    AcForward,
+    /// AC Stop
+    AcStop,
+    /// AC Refresh
+    AcRefresh,
+    /// AC Bookmarks
+    AcBookmarks,
+    /// AC Zoom In
+    AcZoomIn,
+    /// AC Zoom Out
+    AcZoomOut,
+    /// AC Zoom
+    AcZoom,
+    /// AC Pan
+    AcPan,
+}
+
+impl InputReport3Consumer {
+    /// The usage selected by a value of the field
+    pub fn from_value(value: u64) -> Option<Self> {
+        match value {
+            1 => Some(Self::ConsumerControl),
+            2 => Some(Self::NumericKeyPad),
+            3 => Some(Self::ProgrammableButtons),
+            4 => Some(Self::Microphone),
+            5 => Some(Self::Headphone),
+            6 => Some(Self::GraphicEqualizer),
+            32 => Some(Self::Usage10),
+            33 => Some(Self::Usage100),
+            34 => Some(Self::AmPm),
+            48 => Some(Self::Power),
+            49 => Some(Self::Reset),
+            50 => Some(Self::Sleep),
+            51 => Some(Self::SleepAfter),
+            52 => Some(Self::SleepMode),
+            53 => Some(Self::Illumination),
+            54 => Some(Self::FunctionButtons),
+            64 => Some(Self::Menu),
+            65 => Some(Self::MenuPick),
+            66 => Some(Self::MenuUp),
+            67 => Some(Self::MenuDown),
+            68 => Some(Self::MenuLeft),
+            69 => Some(Self::MenuRight),
+            70 => Some(Self::MenuEscape),
+            71 => Some(Self::MenuValueIncrease),
+            72 => Some(Self::MenuValueDecrease),
+            96 => Some(Self::DataOnScreen),
+            97 => Some(Self::ClosedCaption),
+            98 => Some(Self::ClosedCaptionSelect),
+            99 => Some(Self::VcrTv),
+            100 => Some(Self::BroadcastMode),
+            101 => Some(Self::Snapshot),
+            102 => Some(Self::Still),
+            111 => Some(Self::DisplayBrightnessIncrement),
+            112 => Some(Self::DisplayBrightnessDecrement),
+            176 => Some(Self::Play),
+            177 => Some(Self::Pause),
+            178 => Some(Self::Record),
+            179 => Some(Self::FastForward),
+            180 => Some(Self::Rewind),
+            181 => Some(Self::ScanNextTrack),
+            182 => Some(Self::ScanPreviousTrack),
+            183 => Some(Self::Stop),
+            184 => Some(Self::Eject),
+            185 => Some(Self::RandomPlay),
+            205 => Some(Self::PlayPause),
+            224 => Some(Self::Volume),
+            225 => Some(Self::Balance),
+            226 => Some(Self::Mute),
+            227 => Some(Self::Bass),
+            228 => Some(Self::Treble),
+            229 => Some(Self::BassBoost),
+            233 => Some(Self::VolumeIncrement),
+            234 => Some(Self::VolumeDecrement),
+            387 => Some(Self::AlConsumerControlConfiguration),
+            394 => Some(Self::AlEmailReader),
+            402 => Some(Self::AlCalculator),
+            404 => Some(Self::AlLocalMachineBrowser),
+            513 => Some(Self::AcNew),
+            514 => Some(Self::AcOpen),
+            515 => Some(Self::AcClose),
+            516 => Some(Self::AcExit),
+            519 => Some(Self::AcSave),
+            520 => Some(Self::AcPrint),
+            538 => Some(Self::AcUndo),
+            539 => Some(Self::AcCopy),
+            540 => Some(Self::AcCut),
+            541 => Some(Self::AcPaste),
+            543 => Some(Self::AcFind),
+            545 => Some(Self::AcSearch),
+            547 => Some(Self::AcHome),
+            548 => Some(Self::AcBack),
+            549 => Some(Self::AcForward),
+            550 => Some(Self::AcStop),
+            551 => Some(Self::AcRefresh),
+            554 => Some(Self::AcBookmarks),
+            557 => Some(Self::AcZoomIn),
+            558 => Some(Self::AcZoomOut),
+            559 => Some(Self::AcZoom),
+            568 => Some(Self::AcPan),
+            _ => None,
+        }
+    }
+
+    /// The value of the field that selects this usage
+    pub fn value(self) -> u64 {
+        match self {
+            Self::ConsumerControl => 1,
+            Self::NumericKeyPad => 2,
+            Self::ProgrammableButtons => 3,
+            Self::Microphone => 4,
+            Self::Headphone => 5,
+            Self::GraphicEqualizer => 6,
+            Self::Usage10 => 32,
+            Self::Usage100 => 33,
+            Self::AmPm => 34,
+            Self::Power => 48,
+            Self::Reset => 49,
+            Self::Sleep => 50,
+            Self::SleepAfter => 51,
+            Self::SleepMode => 52,
+            Self::Illumination => 53,
+            Self::FunctionButtons => 54,
+            Self::Menu => 64,
+            Self::MenuPick => 65,
+            Self::MenuUp => 66,
+            Self::MenuDown => 67,
+            Self::MenuLeft => 68,
+            Self::MenuRight => 69,
+            Self::MenuEscape => 70,
+            Self::MenuValueIncrease => 71,
+            Self::MenuValueDecrease => 72,
+            Self::DataOnScreen => 96,
+            Self::ClosedCaption => 97,
+            Self::ClosedCaptionSelect => 98,
+            Self::VcrTv => 99,
+            Self::BroadcastMode => 100,
+            Self::Snapshot => 101,
+            Self::Still => 102,
+            Self::DisplayBrightnessIncrement => 111,
+            Self::DisplayBrightnessDecrement => 112,
+            Self::Play => 176,
+            Self::Pause => 177,
+            Self::Record => 178,
+            Self::FastForward => 179,
+            Self::Rewind => 180,
+            Self::ScanNextTrack => 181,
+            Self::ScanPreviousTrack => 182,
+            Self::Stop => 183,
+            Self::Eject => 184,
+            Self::RandomPlay => 185,
+            Self::PlayPause => 205,
+            Self::Volume => 224,
+            Self::Balance => 225,
+            Self::Mute => 226,
+            Self::Bass => 227,
+            Self::Treble => 228,
+            Self::BassBoost => 229,
+            Self::VolumeIncrement => 233,
+            Self::VolumeDecrement => 234,
+            Self::AlConsumerControlConfiguration => 387,
+            Self::AlEmailReader => 394,
+            Self::AlCalculator => 402,
+            Self::AlLocalMachineBrowser => 404,
+            Self::AcNew => 513,
+            Self::AcOpen => 514,
+            Self::AcClose => 515,
+            Self::AcExit => 516,
+            Self::AcSave => 519,
+            Self::AcPrint => 520,
+            Self::AcUndo => 538,
+            Self::AcCopy => 539,
+            Self::AcCut => 540,
+            Self::AcPaste => 541,
+            Self::AcFind => 543,
+            Self::AcSearch => 545,
+            Self::AcHome => 547,
+            Self::AcBack => 548,
+            Self::AcForward => 549,
+            Self::AcStop => 550,
+            Self::AcRefresh => 551,
+            Self::AcBookmarks => 554,
+            Self::AcZoomIn => 557,
+            Self::AcZoomOut => 558,
+            Self::AcZoom => 559,
+            Self::AcPan => 568,
+        }
+    }
+
+    pub fn usage(self) -> ::hidapi::descriptor::Usage {
+        use ::hidapi::descriptor::Usage;
+
+        match self {
+            Self::ConsumerControl => Usage::new(0x000C, 0x0001),
+            Self::NumericKeyPad => Usage::new(0x000C, 0x0002),
+            Self::ProgrammableButtons => Usage::new(0x000C, 0x0003),
+            Self::Microphone => Usage::new(0x000C, 0x0004),
+            Self::Headphone => Usage::new(0x000C, 0x0005),
+            Self::GraphicEqualizer => Usage::new(0x000C, 0x0006),
+            Self::Usage10 => Usage::new(0x000C, 0x0020),
+            Self::Usage100 => Usage::new(0x000C, 0x0021),
+            Self::AmPm => Usage::new(0x000C, 0x0022),
+            Self::Power => Usage::new(0x000C, 0x0030),
+            Self::Reset => Usage::new(0x000C, 0x0031),
+            Self::Sleep => Usage::new(0x000C, 0x0032),
+            Self::SleepAfter => Usage::new(0x000C, 0x0033),
+            Self::SleepMode => Usage::new(0x000C, 0x0034),
+            Self::Illumination => Usage::new(0x000C, 0x0035),
+            Self::FunctionButtons => Usage::new(0x000C, 0x0036),
+            Self::Menu => Usage::new(0x000C, 0x0040),
+            Self::MenuPick => Usage::new(0x000C, 0x0041),
+            Self::MenuUp => Usage::new(0x000C, 0x0042),
+            Self::MenuDown => Usage::new(0x000C, 0x0043),
+            Self::MenuLeft => Usage::new(0x000C, 0x0044),
+            Self::MenuRight => Usage::new(0x000C, 0x0045),
+            Self::MenuEscape => Usage::new(0x000C, 0x0046),
+            Self::MenuValueIncrease => Usage::new(0x000C, 0x0047),
+            Self::MenuValueDecrease => Usage::new(0x000C, 0x0048),
+            Self::DataOnScreen => Usage::new(0x000C, 0x0060),
+            Self::ClosedCaption => Usage::new(0x000C, 0x0061),
+            Self::ClosedCaptionSelect => Usage::new(0x000C, 0x0062),
+            Self::VcrTv => Usage::new(0x000C, 0x0063),
+            Self::BroadcastMode => Usage::new(0x000C, 0x0064),
+            Self::Snapshot => Usage::new(0x000C, 0x0065),
+            Self::Still => Usage::new(0x000C, 0x0066),
+            Self::DisplayBrightnessIncrement => Usage::new(0x000C, 0x006F),
+            Self::DisplayBrightnessDecrement => Usage::new(0x000C, 0x0070),
+            Self::Play => Usage::new(0x000C, 0x00B0),
+            Self::Pause => Usage::new(0x000C, 0x00B1),
+            Self::Record => Usage::new(0x000C, 0x00B2),
+            Self::FastForward => Usage::new(0x000C, 0x00B3),
+            Self::Rewind => Usage::new(0x000C, 0x00B4),
+            Self::ScanNextTrack => Usage::new(0x000C, 0x00B5),
+            Self::ScanPreviousTrack => Usage::new(0x000C, 0x00B6),
+            Self::Stop => Usage::new(0x000C, 0x00B7),
+            Self::Eject => Usage::new(0x000C, 0x00B8),
+            Self::RandomPlay => Usage::new(0x000C, 0x00B9),
+            Self::PlayPause => Usage::new(0x000C, 0x00CD),
+            Self::Volume => Usage::new(0x000C, 0x00E0),
+            Self::Balance => Usage::new(0x000C, 0x00E1),
+            Self::Mute => Usage::new(0x000C, 0x00E2),
+            Self::Bass => Usage::new(0x000C, 0x00E3),
+            Self::Treble => Usage::new(0x000C, 0x00E4),
+            Self::BassBoost => Usage::new(0x000C, 0x00E5),
+            Self::VolumeIncrement => Usage::new(0x000C, 0x00E9),
+            Self::VolumeDecrement => Usage::new(0x000C, 0x00EA),
+            Self::AlConsumerControlConfiguration => Usage::new(0x000C, 0x0183),
+            Self::AlEmailReader => Usage::new(0x000C, 0x018A),
+            Self::AlCalculator => Usage::new(0x000C, 0x0192),
+            Self::AlLocalMachineBrowser => Usage::new(0x000C, 0x0194),
+            Self::AcNew => Usage::new(0x000C, 0x0201),
+            Self::AcOpen => Usage::new(0x000C, 0x0202),
+            Self::AcClose => Usage::new(0x000C, 0x0203),
+            Self::AcExit => Usage::new(0x000C, 0x0204),
+            Self::AcSave => Usage::new(0x000C, 0x0207),
+            Self::AcPrint => Usage::new(0x000C, 0x0208),
+            Self::AcUndo => Usage::new(0x000C, 0x021A),
+            Self::AcCopy => Usage::new(0x000C, 0x021B),
+            Self::AcCut => Usage::new(0x000C, 0x021C),
+            Self::AcPaste => Usage::new(0x000C, 0x021D),
+            Self::AcFind => Usage::new(0x000C, 0x021F),
+            Self::AcSearch => Usage::new(0x000C, 0x0221),
+            Self::AcHome => Usage::new(0x000C, 0x0223),
+            Self::AcBack => Usage::new(0x000C, 0x0224),
+            Self::AcForward => Usage::new(0x000C, 0x0225),
+            Self::AcStop => Usage::new(0x000C, 0x0226),
+            Self::AcRefresh => Usage::new(0x000C, 0x0227),
+            Self::AcBookmarks => Usage::new(0x000C, 0x022A),
+            Self::AcZoomIn => Usage::new(0x000C, 0x022D),
+            Self::AcZoomOut => Usage::new(0x000C, 0x022E),
+            Self::AcZoom => Usage::new(0x000C, 0x022F),
+            Self::AcPan => Usage::new(0x000C, 0x0238),
+        }
+    }
+}
+
+/// Output report 186
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ::hidapi::report::HidReport)]
+#[hid(output, id = 186, length = 31)]
+pub struct OutputReport186 {
+    /// Usage 0xFF00:0x0001, logical 0..=255
+    pub usage_ff00_0001: [u8; 31],
+}
+
+/// Input report 186
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ::hidapi::report::HidReport)]
+#[hid(input, id = 186, length = 31)]
+pub struct InputReport186 {
+    /// Usage 0xFF00:0x0001, logical 0..=255
+    pub usage_ff00_0001: [u8; 31],
+}
+
+/// The Input reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Report1(InputReport1),
+    Report2(InputReport2),
+    Report3(InputReport3),
+    Report186(InputReport186),
+}
+
+impl Input {
+    /// Decode an Input report by its report ID
+    pub fn from_bytes(bytes: &[u8]) -> ::hidapi::HidResult<Self> {
+        use ::hidapi::report::HidReport;
+
+        match bytes.first() {
+            Some(1) => InputReport1::from_bytes(bytes).map(Self::Report1),
+            Some(2) => InputReport2::from_bytes(bytes).map(Self::Report2),
+            Some(3) => InputReport3::from_bytes(bytes).map(Self::Report3),
+            Some(186) => InputReport186::from_bytes(bytes).map(Self::Report186),
+            _ => Err(::hidapi::HidError::IoError {
+                error: ::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    "unknown Input report ID",
+                ),
+            }),
+        }
+    }
+}
+
+/// The Output reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Report186(OutputReport186),
+}
+
+impl Output {
+    /// Decode an Output report by its report ID
+    pub fn from_bytes(bytes: &[u8]) -> ::hidapi::HidResult<Self> {
+        use ::hidapi::report::HidReport;
+
+        match bytes.first() {
+            Some(186) => OutputReport186::from_bytes(bytes).map(Self::Report186),
+            _ => Err(::hidapi::HidError::IoError {
+                error: ::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    "unknown Output report ID",
+                ),
+            }),
+        }
+    }
+}